@@ -0,0 +1,883 @@
+// Star-independent astrometry parameters and the transformations
+// between catalogue (ICRS) and apparent (CIRS) places: space motion
+// and parallax, light deflection by the Sun and annual aberration.
+// Observer-dependent quantities are held in an Astrom context, which
+// is computed once for a given date and observer and then reused for
+// many stars.  The Earth's position and velocity come from
+// EarthPosVel (epv00) and the precession-nutation from the IAU 2006
+// model of the frames module.  For a ground observer, CIRS places are
+// further transformed to observed places (azimuth, zenith distance,
+// hour angle) including diurnal aberration and refraction.
+
+use crate::{
+    common::*,
+    time::{TT,TAI,UT1,UTC,TDB,D2S,DJY,DJ00,TimeError},
+    earth::{self,AUM,PosVel,EarthPosVel},
+    ellipsoid::{WGS84,Geodetic,Geodetic360,EllipsoidConverter,EllipsoidError},
+    frames,
+    locator
+};
+
+custom_error!{pub AstrometryError
+	      Time{source:TimeError}           = "time conversion error: {source}",
+	      Ellipsoid{source:EllipsoidError} = "ellipsoid error: {source}"
+}
+
+/// Light time for 1 AU [s]
+pub(crate) const AULT : R = AUM / CMPS;
+
+/// Schwarzschild radius of the Sun [AU]
+const SRS : R = 1.97412574336e-8;
+
+/// Star-independent astrometry parameters.  Vectors are with respect
+/// to the BCRS (barycentric, with ICRS orientation).
+///
+/// Source: apcs.for, apco.for
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Astrom {
+    /// PM time interval (SSB, Julian years)
+    pub pmt:R,
+
+    /// SSB to observer (vector, AU)
+    pub eb:Vec3,
+
+    /// Sun to observer (unit vector)
+    pub eh:Vec3,
+
+    /// Distance from Sun to observer (AU)
+    pub em:R,
+
+    /// Barycentric observer velocity (vector, units of c)
+    pub v:Vec3,
+
+    /// sqrt(1-|v|^2): reciprocal of Lorenz factor
+    pub bm1:R,
+
+    /// Bias-precession-nutation matrix
+    pub bpn:Mat3,
+
+    /// Longitude + s' (radians)
+    pub along:R,
+
+    /// Geodetic latitude (radians)
+    pub phi:R,
+
+    /// Polar motion xp wrt local meridian (radians)
+    pub xpl:R,
+
+    /// Polar motion yp wrt local meridian (radians)
+    pub ypl:R,
+
+    /// Sine of geodetic latitude
+    pub sphi:R,
+
+    /// Cosine of geodetic latitude
+    pub cphi:R,
+
+    /// Magnitude of diurnal aberration vector
+    pub diurab:R,
+
+    /// "Local" Earth rotation angle (radians)
+    pub eral:R,
+
+    /// Refraction constant A (radians)
+    pub refa:R,
+
+    /// Refraction constant B (radians)
+    pub refb:R
+}
+
+impl Astrom {
+    /// Star-independent astrometry parameters for an observer in
+    /// space.  PV is the observer's geocentric position and velocity
+    /// (m, m/s, GCRS) and EPV the Earth's heliocentric and barycentric
+    /// state (AU, AU/day) for the TDB date.  The matrix BPN is set to
+    /// the identity.
+    ///
+    /// Source: apcs.for
+    pub fn space(TDB((date1,date2)):TDB,pv:&PosVel,epv:&EarthPosVel)->Self {
+	// AU/d to m/s
+	let audms = AUM / D2S;
+
+	// Light time for 1 AU (day)
+	let cr = AULT / D2S;
+
+	// Time since reference epoch, years (for proper motion calculation).
+	let pmt = ( ( date1 - DJ00 ) + date2 ) / DJY;
+
+	// Adjust Earth ephemeris to observer.
+	let dp = pv.p.scale(1.0 / AUM);
+	let dv = pv.v.scale(1.0 / audms);
+	let pb = epv.barycentric.p.add(dp);
+	let vb = epv.barycentric.v.add(dv);
+	let ph = epv.heliocentric.p.add(dp);
+
+	// Barycentric position of observer (AU).
+	let eb = pb;
+
+	// Heliocentric direction and distance (unit vector and AU).
+	let em = ph.norm();
+	let eh = ph.scale(1.0 / em);
+
+	// Barycentric vel. in units of c, and reciprocal of Lorenz factor.
+	let v = vb.scale(cr);
+	let bm1 = sqrt(1.0 - v.dot(v));
+
+	Self { pmt,eb,eh,em,v,bm1,bpn:Mat3::identity(),..Self::default() }
+    }
+
+    /// Star-independent astrometry parameters for a geocentric
+    /// observer, given the Earth's state EPV.  The matrix BPN is set to
+    /// the identity.
+    ///
+    /// Source: apcg.for
+    pub fn geocentric(tdb:TDB,epv:&EarthPosVel)->Self {
+	let pv = PosVel { p:[0.0;3],v:[0.0;3] };
+	Self::space(tdb,&pv,epv) // apcs
+    }
+
+    /// Star-independent astrometry parameters for a geocentric
+    /// observer, with the CIO-based bias-precession-nutation matrix
+    /// formed from the CIP coordinates and CIO locator (X,Y,S).
+    ///
+    /// Source: apci.for
+    pub fn intermediate(tdb:TDB,epv:&EarthPosVel,(x,y,s):(R,R,R))->Self {
+	let mut astrom = Self::geocentric(tdb,epv); // apcg
+	astrom.bpn = frames::celestial_to_intermediate_from_xys(x,y,s); // c2ixys
+	astrom
+    }
+
+    /// Star-independent astrometry parameters for a geocentric
+    /// observer, computing the Earth ephemeris and the CIP/CIO with the
+    /// IAU 2006 precession-nutation model (the TDB date is used as TT).
+    /// Also returns the equation of the origins (ERA-GST).
+    ///
+    /// Based on: apci13.for
+    pub fn intermediate_for_date(tdb@TDB(d):TDB)->(Self,R) {
+	let tt = TT(d);
+
+	// Earth barycentric & heliocentric position/velocity (AU, AU/d).
+	let epv = EarthPosVel::from(tdb); // epv00
+
+	// Form the equinox based BPN matrix, IAU 2006/2000A.
+	let r = frames::precession_nutation_06(tt); // pnm06a
+
+	// Extract CIP X,Y.
+	let (x,y) = frames::xy_from_bpn(&r); // bpn2xy
+
+	// Obtain CIO locator s.
+	let s = locator::cio_06(tt,x,y); // s06
+
+	let astrom = Self::intermediate(tdb,&epv,(x,y,s)); // apci
+
+	// Equation of the origins.
+	let eo = frames::equation_of_origins(&r,s); // eors
+
+	(astrom,eo)
+    }
+
+    /// Star-independent astrometry parameters for a terrestrial
+    /// observer at geodetic position SITE (WGS84).  CIP is the CIP X,Y
+    /// and CIO locator s, THETA the Earth rotation angle, POLAR the
+    /// polar motion (xp,yp,s') and REFRACTION the refraction constants
+    /// (A,B), all in radians.  The diurnal aberration is included in the
+    /// observer's velocity, so DIURAB is set to zero.
+    ///
+    /// Source: apco.for
+    pub fn terrestrial(tdb:TDB,epv:&EarthPosVel,(x,y,s):(R,R,R),theta:R,site:&Geodetic,
+		       (xp,yp,sp):(R,R,R),(refa,refb):(R,R))->Result<Self,EllipsoidError> {
+	let &Geodetic { elong,phi,.. } = site;
+
+	// Form the rotation matrix, CIRS to apparent [HA,Dec].
+	let r = Mat3::rotation(2,elong)
+	    .compose(&Mat3::rotation(0,-yp))
+	    .compose(&Mat3::rotation(1,-xp))
+	    .compose(&Mat3::rotation(2,theta + sp));
+
+	// Solve for local Earth rotation angle.
+	let (a,b) = (r[0][0],r[0][1]);
+	let eral = if a != 0.0 || b != 0.0 { atan2(b,a) } else { 0.0 };
+
+	// Solve for polar motion [X,Y] with respect to local meridian.
+	let c = r[0][2];
+	let xpl = atan2(c,sqrt(a*a + b*b));
+	let (a,b) = (r[1][2],r[2][2]);
+	let ypl = if a != 0.0 || b != 0.0 { -atan2(a,b) } else { 0.0 };
+
+	// CIO based BPN matrix.
+	let bpn = frames::celestial_to_intermediate_from_xys(x,y,s); // c2ixys
+
+	// Observer's geocentric position and velocity (m, m/s, CIRS).
+	let pvc = observer_pv(site,xp,yp,sp,theta)?; // pvtob
+
+	// Rotate into GCRS.
+	let pv = PosVel { p:bpn.apply(pvc.p),v:bpn.apply(pvc.v) }; // trxpv
+
+	// ICRS <-> GCRS parameters.
+	let astrom = Self::space(tdb,&pv,epv); // apcs
+
+	Ok(Self {
+	    bpn,
+	    along:anpm(eral - theta),
+	    phi,
+	    xpl,
+	    ypl,
+	    sphi:sin(phi),
+	    cphi:cos(phi),
+	    diurab:0.0,
+	    eral,
+	    refa,
+	    refb,
+	    ..astrom
+	})
+    }
+
+    /// Star-independent astrometry parameters for a terrestrial
+    /// observer, for the CIRS to observed transformations only.  SP is
+    /// the TIO locator s' and THETA the Earth rotation angle; the other
+    /// arguments are as for terrestrial().  Only the fields ALONG, PHI,
+    /// XPL, YPL, SPHI, CPHI, DIURAB, ERAL, REFA and REFB are set.
+    ///
+    /// Source: apio.for
+    pub fn observer(sp:R,theta:R,site:&Geodetic,xp:R,yp:R,(refa,refb):(R,R))
+		    ->Result<Self,EllipsoidError> {
+	let &Geodetic { elong,phi,.. } = site;
+
+	// Form the rotation matrix, CIRS to apparent [HA,Dec].
+	let r = Mat3::rotation(2,elong)
+	    .compose(&Mat3::rotation(0,-yp))
+	    .compose(&Mat3::rotation(1,-xp))
+	    .compose(&Mat3::rotation(2,theta + sp));
+
+	// Solve for local Earth rotation angle.
+	let (a,b) = (r[0][0],r[0][1]);
+	let eral = if a != 0.0 || b != 0.0 { atan2(b,a) } else { 0.0 };
+
+	// Solve for polar motion [X,Y] with respect to local meridian.
+	let c = r[0][2];
+	let xpl = atan2(c,sqrt(a*a + b*b));
+	let (a,b) = (r[1][2],r[2][2]);
+	let ypl = if a != 0.0 || b != 0.0 { -atan2(a,b) } else { 0.0 };
+
+	// Observer's geocentric position and velocity (m, m/s, CIRS).
+	let pv = observer_pv(site,xp,yp,sp,theta)?; // pvtob
+
+	// Magnitude of diurnal aberration vector.
+	let diurab = sqrt(pv.v[0]*pv.v[0] + pv.v[1]*pv.v[1]) / CMPS;
+
+	Ok(Self {
+	    along:anpm(eral - theta),
+	    phi,
+	    xpl,
+	    ypl,
+	    sphi:sin(phi),
+	    cphi:cos(phi),
+	    diurab,
+	    eral,
+	    refa,
+	    refb,
+	    ..Self::default()
+	})
+    }
+
+    /// Star-independent astrometry parameters for the CIRS to observed
+    /// transformations, for an observer at the UTC date.
+    ///
+    /// Source: apio13.for
+    pub fn observer_for_date(utc:UTC,observer:&Observer)->Result<Self,AstrometryError> {
+	let &Observer { position,eop:EarthOrientation { dut1,xp,yp },atmosphere } = observer;
+
+	// UTC to other time scales.
+	let tt : TT = TAI::from_utc(utc)?.into(); // utctai, taitt
+	let ut1 = UT1::from_utc(utc,dut1)?; // utcut1
+
+	// TIO locator s'.
+	let sp = earth::tio_locator(tt); // sp00
+
+	// Earth rotation angle.
+	let theta = earth::rotation_angle(ut1); // era00
+
+	// Refraction constants A and B.
+	let refraction = atmosphere.refraction_constants(); // refco
+
+	// CIRS <-> observed astrometry parameters.
+	Ok(Self::observer(sp,theta,&position.into(),xp,yp,refraction)?) // apio
+    }
+
+    /// Star-independent astrometry parameters for the ICRS to observed
+    /// transformations, for an observer at the UTC date, using the IAU
+    /// 2006 precession-nutation model of the crate and TT as TDB.  Also
+    /// returns the equation of the origins (ERA-GST).
+    ///
+    /// Based on: apco13.for
+    pub fn terrestrial_for_date(utc:UTC,observer:&Observer)
+				->Result<(Self,R),AstrometryError> {
+	let &Observer { position,eop:EarthOrientation { dut1,xp,yp },atmosphere } = observer;
+
+	// UTC to other time scales.
+	let tt@TT(d) = TAI::from_utc(utc)?.into(); // utctai, taitt
+	let ut1 = UT1::from_utc(utc,dut1)?; // utcut1
+	let tdb = TDB(d);
+
+	// Earth barycentric & heliocentric position/velocity (AU, AU/d).
+	let epv = EarthPosVel::from(tdb); // epv00
+
+	// Form the equinox based BPN matrix, IAU 2006/2000A.
+	let r = frames::precession_nutation_06(tt); // pnm06a
+
+	// Extract CIP X,Y.
+	let (x,y) = frames::xy_from_bpn(&r); // bpn2xy
+
+	// Obtain CIO locator s.
+	let s = locator::cio_06(tt,x,y); // s06
+
+	// Earth rotation angle.
+	let theta = earth::rotation_angle(ut1); // era00
+
+	// TIO locator s'.
+	let sp = earth::tio_locator(tt); // sp00
+
+	// Refraction constants A and B.
+	let refraction = atmosphere.refraction_constants(); // refco
+
+	// Compute the star-independent astrometry parameters.
+	let astrom = Self::terrestrial(tdb,&epv,(x,y,s),theta,&position.into(),
+				       (xp,yp,sp),refraction)?; // apco
+
+	// Equation of the origins.
+	let eo = frames::equation_of_origins(&r,s); // eors
+
+	Ok((astrom,eo))
+    }
+}
+
+/// Earth orientation parameters
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+pub struct EarthOrientation {
+    /// UT1-UTC [s]
+    pub dut1:R,
+
+    /// Polar motion coordinates [rad]
+    pub xp:R,
+    pub yp:R
+}
+
+/// Ambient conditions at the observer, for refraction.  A zero
+/// pressure (the default) disables refraction.
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+pub struct Atmosphere {
+    /// Pressure at the observer [hPa = mB]
+    pub phpa:R,
+
+    /// Ambient temperature at the observer [deg C]
+    pub tc:R,
+
+    /// Relative humidity at the observer, in the range 0-1
+    pub rh:R,
+
+    /// Wavelength [micrometers]; values above 100 select radio
+    /// refraction
+    pub wl:R
+}
+
+impl Atmosphere {
+    /// ISO standard atmosphere at sea level (1013.25 hPa, 15 deg C),
+    /// dry air, visible light
+    pub const STANDARD : Self = Self { phpa:1013.25,tc:15.0,rh:0.0,wl:0.55 };
+
+    /// Refraction constants A and B (radians) for the simple model
+    /// dZ = A tan Z + B tan^3 Z, where Z is the observed zenith
+    /// distance.
+    ///
+    /// Source: refco.for
+    pub fn refraction_constants(&self)->(R,R) {
+	let &Self { phpa,tc,rh,wl } = self;
+
+	// Decide whether optical/IR or radio case:  switch at 100 microns.
+	let optic = wl <= 100.0;
+
+	// Restrict parameters to safe values.
+	let t = tc.clamp(-150.0,200.0);
+	let p = phpa.clamp(0.0,10000.0);
+	let r = rh.clamp(0.0,1.0);
+	let w = wl.clamp(0.1,1e6);
+
+	// Water vapour pressure at the observer.
+	let pw =
+	    if p > 0.0 {
+		let ps = 10.0_f64.powf( ( 0.7859 + 0.03477*t ) / ( 1.0 + 0.00412*t ) )
+		    * ( 1.0 + p * ( 4.5e-6 + 6e-10*t*t ) );
+		r*ps / ( 1.0 - ( 1.0 - r )*ps/p )
+	    } else {
+		0.0
+	    };
+
+	// Refractive index minus 1 at the observer.
+	let tk = t + 273.15;
+	let gamma =
+	    if optic {
+		let wlsq = w*w;
+		( ( 77.53484e-6 + ( 4.39108e-7 + 3.666e-9/wlsq ) / wlsq ) * p
+		   - 11.2684e-6*pw ) / tk
+	    } else {
+		( 77.6890e-6*p - ( 6.3938e-6 - 0.375463/tk ) * pw ) / tk
+	    };
+
+	// Formula for beta from Stone, with empirical adjustments.
+	let mut beta = 4.4474e-6 * tk;
+	if !optic {
+	    beta -= 0.0074 * pw * beta;
+	}
+
+	// Refraction constants from Green.
+	(gamma * ( 1.0 - beta ),
+	 - gamma * ( beta - gamma / 2.0 ))
+    }
+}
+
+/// A ground observer: geodetic position (WGS84), Earth orientation
+/// parameters and ambient conditions
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct Observer {
+    pub position:Geodetic360,
+    pub eop:EarthOrientation,
+    pub atmosphere:Atmosphere
+}
+
+/// Observed place of a source
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct ObservedPlace {
+    /// Azimuth (N=0,E=90) [rad]
+    pub azimuth:R,
+
+    /// Zenith distance [rad]
+    pub zenith_distance:R,
+
+    /// Hour angle [rad]
+    pub hour_angle:R,
+
+    /// Declination [rad]
+    pub declination:R,
+
+    /// Right ascension (CIO-based) [rad]
+    pub right_ascension:R
+}
+
+/// Observed coordinates given to the observed to CIRS transformations
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub enum ObservedCoordinates {
+    /// Right ascension (CIO-based) and declination [rad]
+    RaDec(R,R),
+
+    /// Hour angle and declination [rad]
+    HaDec(R,R),
+
+    /// Azimuth (N=0,E=90) and zenith distance [rad]
+    AzZd(R,R)
+}
+
+/// Position and velocity (m, m/s, CIRS) of a terrestrial observer at
+/// geodetic position SITE (WGS84), given the polar motion XP, YP, the
+/// TIO locator SP and the Earth rotation angle THETA (radians).
+///
+/// Source: pvtob.for
+pub fn observer_pv(site:&Geodetic,xp:R,yp:R,sp:R,theta:R)->Result<PosVel,EllipsoidError> {
+    // Geodetic to geocentric transformation (WGS84).
+    let xyzm = EllipsoidConverter::new(&WGS84)?.geodetic_to_geocentric(site)?; // gd2gc
+
+    // Polar motion and TIO position.
+    let rpm = earth::polar_motion_matrix(xp,yp,sp); // pom00
+    let [x,y,z] = rpm.apply(xyzm); // trxp
+
+    // Functions of ERA.
+    let (s,c) = (sin(theta),cos(theta));
+
+    Ok(PosVel {
+	p:[c*x - s*y,s*x + c*y,z],
+	v:[earth::OMEGA*(-s*x - c*y),earth::OMEGA*(c*x - s*y),0.0]
+    })
+}
+
+/// Proper motion and parallax.  Given the catalogue coordinates
+/// (RC,DC,PR,PD,PX,RV) with proper motions in radians per Julian year,
+/// parallax in arcsec and radial velocity in km/s, the proper motion
+/// time interval PMT (Julian years) and the SSB to observer vector POB
+/// (AU), returns the coordinate direction (BCRS unit vector).
+///
+/// Source: pmpx.for
+#[allow(clippy::too_many_arguments)]
+pub fn proper_motion_parallax(rc:R,dc:R,pr:R,pd:R,px:R,rv:R,pmt:R,pob:Vec3)->Vec3 {
+    // km/s to au/year
+    let vf = D2S * DJY / AUM * 1e3;
+
+    // Light time for 1 au, Julian years
+    let aulty = AULT / D2S / DJY;
+
+    // Spherical coordinates to unit vector (and useful functions).
+    let (sr,cr) = (sin(rc),cos(rc));
+    let (sd,cd) = (sin(dc),cos(dc));
+    let x = cr*cd;
+    let y = sr*cd;
+    let z = sd;
+    let p = [x,y,z];
+
+    // Proper motion time interval (y) including Roemer effect.
+    let dt = pmt + p.dot(pob)*aulty;
+
+    // Space motion (radians per year).
+    let pxr = px * AS2R;
+    let w = vf * rv * pxr;
+    let pdz = pd * z;
+    let pm = [- pr*y - pdz*cr + w*x,
+	      pr*x - pdz*sr + w*y,
+	      pd*cd + w*z];
+
+    // Coordinate direction of star (unit vector, BCRS).
+    p.add(pm.scale(dt)).sub(pob.scale(pxr)).normalize()
+}
+
+/// Apply light deflection by a solar-system body.  BM is the mass of
+/// the body (solar masses), P the direction from observer to source
+/// (unit vector), Q the direction from body to source (unit vector), E
+/// the direction from body to observer (unit vector), EM the distance
+/// from body to observer (AU) and DLIM the deflection limiter.
+/// Returns the observer to deflected source direction (unit vector).
+///
+/// Source: ld.for
+pub fn light_deflection(bm:R,p:Vec3,q:Vec3,e:Vec3,em:R,dlim:R)->Vec3 {
+    // q . (q + e).
+    let qdqpe = q.dot(q.add(e));
+
+    // 2 x G x bm / ( em x c^2 x ( q . (q + e) ) ).
+    let w = bm * SRS / em / qdqpe.max(dlim);
+
+    // p x (e x q).
+    let peq = p.cross(e.cross(q));
+
+    // Apply the deflection.
+    p.add(peq.scale(w))
+}
+
+/// Deflection of starlight by the Sun.  P is the direction from
+/// observer to star (unit vector), E the direction from Sun to
+/// observer (unit vector) and EM the distance from Sun to observer
+/// (AU).  Returns the observer to deflected star direction.
+///
+/// Source: ldsun.for
+pub fn light_deflection_sun(p:Vec3,e:Vec3,em:R)->Vec3 {
+    // Deflection limiter (smaller for distant observers).
+    let em2 = (em*em).max(1.0);
+    let dlim = 1e-6 / em2;
+
+    // Apply the deflection.
+    light_deflection(1.0,p,p,e,em,dlim) // ld
+}
+
+/// Apply aberration to transform natural direction into proper
+/// direction.  PNAT is the natural direction to the source (unit
+/// vector), V the observer barycentric velocity in units of c, S the
+/// distance between the Sun and the observer (AU) and BM1
+/// sqrt(1-|v|^2).  Returns the proper direction (unit vector).
+///
+/// Source: ab.for
+pub fn aberration(pnat:Vec3,v:Vec3,s:R,bm1:R)->Vec3 {
+    let pdv = pnat.dot(v);
+    let w1 = 1.0 + pdv / ( 1.0 + bm1 );
+    let w2 = SRS / s;
+    let mut p = [0.0;3];
+    for i in 0..3 {
+	p[i] = pnat[i]*bm1 + w1*v[i] + w2*( v[i] - pdv*pnat[i] );
+    }
+    p.normalize()
+}
+
+/// Quick ICRS, epoch J2000.0, to CIRS transformation, given
+/// precomputed star-independent astrometry parameters.  Returns the
+/// CIRS right ascension and declination (RI,DI).
+///
+/// Source: atciq.for
+pub fn icrs_to_cirs_quick(rc:R,dc:R,pr:R,pd:R,px:R,rv:R,astrom:&Astrom)->(R,R) {
+    // Proper motion and parallax, giving BCRS coordinate direction.
+    let pco = proper_motion_parallax(rc,dc,pr,pd,px,rv,astrom.pmt,astrom.eb); // pmpx
+
+    // Light deflection by the Sun, giving BCRS natural direction.
+    let pnat = light_deflection_sun(pco,astrom.eh,astrom.em); // ldsun
+
+    // Aberration, giving GCRS proper direction.
+    let ppr = aberration(pnat,astrom.v,astrom.em,astrom.bm1); // ab
+
+    // Bias-precession-nutation, giving CIRS proper direction.
+    let pi = astrom.bpn.mul_vec(ppr); // rxp
+
+    // CIRS RA,Dec.
+    let (w,di) = cartesian_to_spherical(pi); // c2s
+    (anp(w),di)
+}
+
+/// Quick CIRS RA,Dec to ICRS astrometric place, given the
+/// star-independent astrometry parameters.  Returns the ICRS
+/// astrometric right ascension and declination (RC,DC).
+///
+/// Source: aticq.for
+pub fn cirs_to_icrs_quick(ri:R,di:R,astrom:&Astrom)->(R,R) {
+    // CIRS RA,Dec to Cartesian.
+    let pi = spherical_to_cartesian(ri,di); // s2c
+
+    // Bias-precession-nutation, giving GCRS proper direction.
+    let ppr = astrom.bpn.apply(pi); // trxp
+
+    // Aberration, giving GCRS natural direction.
+    let mut d = [0.0;3];
+    let mut pnat = ppr;
+    for _ in 0..2 {
+	let before = ppr.sub(d).normalize();
+	let after = aberration(before,astrom.v,astrom.em,astrom.bm1); // ab
+	d = after.sub(before);
+	pnat = ppr.sub(d).normalize();
+    }
+
+    // Light deflection by the Sun, giving BCRS coordinate direction.
+    let mut d = [0.0;3];
+    let mut pco = pnat;
+    for _ in 0..5 {
+	let before = pnat.sub(d).normalize();
+	let after = light_deflection_sun(before,astrom.eh,astrom.em); // ldsun
+	d = after.sub(before);
+	pco = pnat.sub(d).normalize();
+    }
+
+    // ICRS astrometric RA,Dec.
+    let (w,dc) = cartesian_to_spherical(pco); // c2s
+    (anp(w),dc)
+}
+
+/// Transform ICRS star data, epoch J2000.0, to CIRS at the TDB date.
+/// Returns the CIRS geocentric right ascension and declination (RI,DI)
+/// and the equation of the origins EO (ERA-GST).
+///
+/// Based on: atci13.for
+pub fn icrs_to_cirs(rc:R,dc:R,pr:R,pd:R,px:R,rv:R,tdb:TDB)->(R,R,R) {
+    // The transformation parameters.
+    let (astrom,eo) = Astrom::intermediate_for_date(tdb); // apci13
+
+    // ICRS (epoch J2000.0) to CIRS.
+    let (ri,di) = icrs_to_cirs_quick(rc,dc,pr,pd,px,rv,&astrom); // atciq
+    (ri,di,eo)
+}
+
+/// Transform star RA,Dec from geocentric CIRS to ICRS astrometric at
+/// the TDB date.  Returns the ICRS astrometric right ascension and
+/// declination (RC,DC) and the equation of the origins EO (ERA-GST).
+///
+/// Based on: atic13.for
+pub fn cirs_to_icrs(ri:R,di:R,tdb:TDB)->(R,R,R) {
+    // Star-independent astrometry parameters.
+    let (astrom,eo) = Astrom::intermediate_for_date(tdb); // apci13
+
+    // CIRS to ICRS astrometric.
+    let (rc,dc) = cirs_to_icrs_quick(ri,di,&astrom); // aticq
+    (rc,dc,eo)
+}
+
+/// Quick CIRS to observed place transformation, given the
+/// star-independent astrometry parameters.
+///
+/// Source: atioq.for
+pub fn cirs_to_observed_quick(ri:R,di:R,astrom:&Astrom)->ObservedPlace {
+    // Minimum cos(alt) and sin(alt) for refraction purposes
+    const CELMIN : R = 1e-6;
+    const SELMIN : R = 0.05;
+
+    // CIRS RA,Dec to Cartesian -HA,Dec.
+    let [x,y,z] = spherical_to_cartesian(ri - astrom.eral,di); // s2c
+
+    // Polar motion.
+    let (sx,cx) = (sin(astrom.xpl),cos(astrom.xpl));
+    let (sy,cy) = (sin(astrom.ypl),cos(astrom.ypl));
+    let xhd = cx*x + sx*z;
+    let yhd = sx*sy*x + cy*y - cx*sy*z;
+    let zhd = -sx*cy*x + sy*y + cx*cy*z;
+
+    // Diurnal aberration.
+    let f = 1.0 - astrom.diurab*yhd;
+    let xhdt = f * xhd;
+    let yhdt = f * ( yhd + astrom.diurab );
+    let zhdt = f * zhd;
+
+    // Cartesian -HA,Dec to Cartesian Az,El (S=0,E=90).
+    let xaet = astrom.sphi*xhdt - astrom.cphi*zhdt;
+    let yaet = yhdt;
+    let zaet = astrom.cphi*xhdt + astrom.sphi*zhdt;
+
+    // Azimuth (N=0,E=90).
+    let azobs = if xaet != 0.0 || yaet != 0.0 { atan2(yaet,-xaet) } else { 0.0 };
+
+    // Refraction.
+    let r = sqrt(xaet*xaet + yaet*yaet).max(CELMIN);
+    let z = zaet.max(SELMIN);
+    let tz = r / z;
+    let w = astrom.refb*tz*tz;
+    let del = ( astrom.refa + w ) * tz /
+	( 1.0 + ( astrom.refa + 3.0*w ) / ( z*z ) );
+
+    // Apply the change, giving observed vector.
+    let cosdel = 1.0 - del*del/2.0;
+    let f = cosdel - del*z/r;
+    let xaeo = xaet*f;
+    let yaeo = yaet*f;
+    let zaeo = cosdel*zaet + del*r;
+
+    // Observed ZD.
+    let zdobs = atan2(sqrt(xaeo*xaeo + yaeo*yaeo),zaeo);
+
+    // Az/El vector to HA,Dec vector (both right-handed).
+    let v = [astrom.sphi*xaeo + astrom.cphi*zaeo,
+	     yaeo,
+	     - astrom.cphi*xaeo + astrom.sphi*zaeo];
+
+    // To spherical -HA,Dec.
+    let (hmobs,dcobs) = cartesian_to_spherical(v); // c2s
+
+    // Right ascension (with respect to CIO).
+    let raobs = astrom.eral + hmobs;
+
+    ObservedPlace {
+	azimuth:anp(azobs),
+	zenith_distance:zdobs,
+	hour_angle:-hmobs,
+	declination:dcobs,
+	right_ascension:anp(raobs)
+    }
+}
+
+/// Quick observed place to CIRS, given the star-independent astrometry
+/// parameters.  Returns the CIRS right ascension and declination
+/// (RI,DI).
+///
+/// Source: atoiq.for
+pub fn observed_to_cirs_quick(ob:ObservedCoordinates,astrom:&Astrom)->(R,R) {
+    // Minimum sin(alt) for refraction purposes
+    const SELMIN : R = 0.05;
+
+    let (sphi,cphi) = (astrom.sphi,astrom.cphi);
+
+    // Cartesian -HA,Dec to Cartesian Az,El (S=0,E=90).
+    let from_ha_dec = |ha:R,dec:R| {
+	let [xmhdo,ymhdo,zmhdo] = spherical_to_cartesian(-ha,dec); // s2c
+	(sphi*xmhdo - cphi*zmhdo,
+	 ymhdo,
+	 cphi*xmhdo + sphi*zmhdo)
+    };
+
+    // Coordinates to Cartesian Az,El (S=0,E=90).
+    let (xaeo,yaeo,zaeo) =
+	match ob {
+	    ObservedCoordinates::AzZd(az,zd) => {
+		let ce = sin(zd);
+		(- cos(az) * ce,sin(az) * ce,cos(zd))
+	    },
+	    // If RA,Dec, convert to HA,Dec.
+	    ObservedCoordinates::RaDec(ra,dec) => from_ha_dec(astrom.eral - ra,dec),
+	    ObservedCoordinates::HaDec(ha,dec) => from_ha_dec(ha,dec)
+	};
+
+    // Azimuth (S=0,E=90).
+    let az = if xaeo != 0.0 || yaeo != 0.0 { atan2(yaeo,xaeo) } else { 0.0 };
+
+    // Sine of observed ZD, and observed ZD.
+    let sz = sqrt(xaeo*xaeo + yaeo*yaeo);
+    let zdo = atan2(sz,zaeo);
+
+    // Refraction.
+    let tz = sz / zaeo.max(SELMIN);
+    let dref = ( astrom.refa + astrom.refb*tz*tz ) * tz;
+    let zdt = zdo + dref;
+
+    // To Cartesian Az,ZD.
+    let ce = sin(zdt);
+    let xaet = cos(az) * ce;
+    let yaet = sin(az) * ce;
+    let zaet = cos(zdt);
+
+    // Cartesian Az,ZD to Cartesian -HA,Dec.
+    let xmhda = sphi*xaet + cphi*zaet;
+    let ymhda = yaet;
+    let zmhda = - cphi*xaet + sphi*zaet;
+
+    // Diurnal aberration.
+    let f = 1.0 + astrom.diurab*ymhda;
+    let xhd = f * xmhda;
+    let yhd = f * ( ymhda - astrom.diurab );
+    let zhd = f * zmhda;
+
+    // Polar motion.
+    let (sx,cx) = (sin(astrom.xpl),cos(astrom.xpl));
+    let (sy,cy) = (sin(astrom.ypl),cos(astrom.ypl));
+    let v = [cx*xhd + sx*sy*yhd - sx*cy*zhd,
+	     cy*yhd + sy*zhd,
+	     sx*xhd - cx*sy*yhd + cx*cy*zhd];
+
+    // To spherical -HA,Dec.
+    let (hma,di) = cartesian_to_spherical(v); // c2s
+
+    // Right ascension.
+    (anp(astrom.eral + hma),di)
+}
+
+/// CIRS RA,Dec to observed place for an observer at the UTC date.
+///
+/// Source: atio13.for
+pub fn cirs_to_observed(ri:R,di:R,utc:UTC,observer:&Observer)
+			->Result<ObservedPlace,AstrometryError> {
+    // Star-independent astrometry parameters for CIRS->observed.
+    let astrom = Astrom::observer_for_date(utc,observer)?; // apio13
+
+    // Transform CIRS to observed.
+    Ok(cirs_to_observed_quick(ri,di,&astrom)) // atioq
+}
+
+/// Observed place for an observer at the UTC date to CIRS RA,Dec.
+///
+/// Source: atoi13.for
+pub fn observed_to_cirs(ob:ObservedCoordinates,utc:UTC,observer:&Observer)
+			->Result<(R,R),AstrometryError> {
+    // Star-independent astrometry parameters for CIRS->observed.
+    let astrom = Astrom::observer_for_date(utc,observer)?; // apio13
+
+    // Transform observed to CIRS.
+    Ok(observed_to_cirs_quick(ob,&astrom)) // atoiq
+}
+
+/// ICRS RA,Dec to observed place for an observer at the UTC date.  The
+/// star data are as for icrs_to_cirs().  Also returns the equation of
+/// the origins (ERA-GST).
+///
+/// Based on: atco13.for
+#[allow(clippy::too_many_arguments)]
+pub fn icrs_to_observed(rc:R,dc:R,pr:R,pd:R,px:R,rv:R,utc:UTC,observer:&Observer)
+			->Result<(ObservedPlace,R),AstrometryError> {
+    // Star-independent astrometry parameters.
+    let (astrom,eo) = Astrom::terrestrial_for_date(utc,observer)?; // apco13
+
+    // Transform ICRS to CIRS.
+    let (ri,di) = icrs_to_cirs_quick(rc,dc,pr,pd,px,rv,&astrom); // atciq
+
+    // Transform CIRS to observed.
+    Ok((cirs_to_observed_quick(ri,di,&astrom),eo)) // atioq
+}
+
+/// Observed place for an observer at the UTC date to ICRS astrometric
+/// RA,Dec.
+///
+/// Based on: atoc13.for
+pub fn observed_to_icrs(ob:ObservedCoordinates,utc:UTC,observer:&Observer)
+			->Result<(R,R),AstrometryError> {
+    // Star-independent astrometry parameters.
+    let (astrom,_) = Astrom::terrestrial_for_date(utc,observer)?; // apco13
+
+    // Transform observed to CIRS.
+    let (ri,di) = observed_to_cirs_quick(ob,&astrom); // atoiq
+
+    // Transform CIRS to ICRS.
+    Ok(cirs_to_icrs_quick(ri,di,&astrom)) // aticq
+}
@@ -46,7 +46,7 @@ impl GregorianDate {
 	    return Err(CalendarError::BadYear);
 	}
 
-	if month < 1 || month > 12 {
+	if !(1..=12).contains(&month) {
 	    return Err(CalendarError::BadMonth);
 	}
 
@@ -87,7 +87,7 @@ impl GregorianDate {
     /// Source: jd2cal.for
     pub fn from_julian(dj1:R,dj2:R)->Result<(Self,R),CalendarError> {
 	let dj = dj1 + dj2;
-	if dj < DJ_MIN || dj > DJ_MAX {
+	if !(DJ_MIN..=DJ_MAX).contains(&dj) {
 	    return Err(CalendarError::BadJulian);
 	}
 
@@ -140,7 +140,7 @@ impl HMS {
     }
     
     pub fn from_fraction_of_day(f:f64)->Result<Self,CalendarError> {
-	if f < 0.0 || f > 1.0 {
+	if !(0.0..=1.0).contains(&f) {
 	    return Err(CalendarError::BadFract)
 	}
 	let s = 86400.0 * f;
@@ -21,10 +21,19 @@ pub const PI : R = std::f64::consts::PI;
 pub const DEGREE : R = PI/180.0;
 pub const EPSILON : R = R::EPSILON;
 
+#[allow(clippy::excessive_precision,clippy::approx_constant)]
 pub const TWO_PI : R = 6.283185307179586476925287;
+#[allow(clippy::excessive_precision)]
 pub const AS2R : R = 4.848136811095359935899141e-6;
 pub const MAS2R : R = AS2R / 1e3;
 pub const TURNAS : R = 1296000.0;
+#[allow(clippy::excessive_precision)]
+pub const DS2R : R = 7.272205216643039903848712e-5;
+#[allow(clippy::excessive_precision)]
+pub const DR2AS : R = 206264.8062470963551564734;
+
+/// Speed of light (m/s)
+pub const CMPS : R = 299792458.0;
 
 pub fn anp(a:R)->R {
     // Normalize angle to range [0,2π[
@@ -35,6 +44,119 @@ pub fn anp(a:R)->R {
     w
 }
 
+pub fn anpm(a:R)->R {
+    // Normalize angle to range [-π,π[
+    let mut w = a % TWO_PI;
+    if abs(w) >= PI {
+	w -= TWO_PI.copysign(a);
+    }
+    w
+}
+
+/// Value at T of the polynomial with coefficients C, constant term
+/// first, by Horner's scheme
+pub fn polynomial(c:&[R],t:R)->R {
+    c.iter().rev().fold(0.0,|p,ck| p * t + ck)
+}
+
+/// Convert spherical coordinates to a unit vector
+///
+/// Source: s2c.for
+pub fn spherical_to_cartesian(theta:R,phi:R)->Vec3 {
+    let cp = cos(phi);
+    [cos(theta) * cp,
+     sin(theta) * cp,
+     sin(phi)]
+}
+
+/// Convert a vector to spherical coordinates (theta,phi)
+///
+/// Source: c2s.for
+pub fn cartesian_to_spherical([x,y,z]:Vec3)->(R,R) {
+    let d2 = x*x + y*y;
+    let theta = if d2 == 0.0 { 0.0 } else { atan2(y,x) };
+    let phi = if z == 0.0 { 0.0 } else { atan2(z,sqrt(d2)) };
+    (theta,phi)
+}
+
+/// Convert spherical polar coordinates to a p-vector
+///
+/// Source: s2p.for
+pub fn spherical_to_position(theta:R,phi:R,r:R)->Vec3 {
+    spherical_to_cartesian(theta,phi).scale(r) // s2c, sxp
+}
+
+/// Convert a p-vector to spherical polar coordinates (theta,phi,r)
+///
+/// Source: p2s.for
+pub fn position_to_spherical(p:Vec3)->(R,R,R) {
+    let (theta,phi) = cartesian_to_spherical(p); // c2s
+    (theta,phi,p.norm())
+}
+
+/// Angular separation between two sets of spherical coordinates
+///
+/// Source: seps.for
+pub fn separation_spherical(al:R,ap:R,bl:R,bp:R)->R {
+    spherical_to_cartesian(al,ap).angle(spherical_to_cartesian(bl,bp)) // s2c, sepp
+}
+
+/// Position-angle from two p-vectors: the bearing of B from A,
+/// measured from north (towards the pole) through east.
+///
+/// Source: pap.for
+pub fn position_angle(a:Vec3,b:Vec3)->R {
+    let am = a.norm();
+    let bm = b.norm();
+    let (st,ct) =
+	if am == 0.0 || bm == 0.0 {
+	    (0.0,1.0)
+	} else {
+	    let au = a.scale(1.0 / am);
+
+	    // Construct a point "north" of A, in the direction of the
+	    // pole.
+	    let [xa,ya,za] = a;
+	    let eta = [-xa*za,-ya*za,xa*xa + ya*ya];
+
+	    // The "east" axis.
+	    let xi = eta.cross(au);
+
+	    // Position angle of B from A.
+	    let a2b = b.sub(a);
+	    let st = a2b.dot(xi);
+	    let ct = a2b.dot(eta);
+
+	    // Deal with degenerate cases.
+	    if st == 0.0 && ct == 0.0 { (0.0,1.0) } else { (st,ct) }
+	};
+    atan2(st,ct)
+}
+
+/// Position-angle from spherical coordinates
+///
+/// Source: pas.for
+pub fn position_angle_spherical(al:R,ap:R,bl:R,bp:R)->R {
+    let dl = bl - al;
+    let y = sin(dl) * cos(bp);
+    let x = sin(bp) * cos(ap) - cos(bp) * sin(ap) * cos(dl);
+    if x != 0.0 || y != 0.0 { atan2(y,x) } else { 0.0 }
+}
+
+/// Form the rotation matrix corresponding to a given rotation vector
+///
+/// Source: rv2m.for
+pub fn rotation_vector_to_matrix(w:Vec3)->Mat3 {
+    let phi = w.norm();
+    let s = sin(phi);
+    let c = cos(phi);
+    let f = 1.0 - c;
+    let [x,y,z] = if phi > 0.0 { w.scale(1.0 / phi) } else { w };
+    [[x*x*f + c,   x*y*f + z*s, x*z*f - y*s],
+     [y*x*f - z*s, y*y*f + c,   y*z*f + x*s],
+     [z*x*f + y*s, z*y*f - x*s, z*z*f + c]]
+}
+
 pub trait Vector {
     fn norm(self)->R;
     fn normalize(self)->Self;
@@ -43,6 +165,7 @@ pub trait Vector {
     fn sub(self,b:Self)->Self;
     fn neg(self)->Self;
     fn dot(self,b:Self)->R;
+    fn cross(self,b:Self)->Self;
     fn angle(self,b:Self)->R;
 }
 
@@ -83,8 +206,20 @@ impl Vector for Vec3 {
 	self[0]*b[0] + self[1]*b[1] + self[2]*b[2]
     }
 
+    fn cross(self,b:Self)->Self {
+	[self[1]*b[2] - self[2]*b[1],
+	 self[2]*b[0] - self[0]*b[2],
+	 self[0]*b[1] - self[1]*b[0]]
+    }
+
+    /// Angular separation between two vectors, accurate at all
+    /// angles
+    ///
+    /// Source: sepp.for
     fn angle(self,b:Self)->R {
-	acos(self.normalize().dot(b.normalize()))
+	let ss = self.cross(b).norm();
+	let cs = self.dot(b);
+	if ss != 0.0 || cs != 0.0 { atan2(ss,cs) } else { 0.0 }
     }
 }
 
@@ -93,9 +228,13 @@ pub trait Matrix {
     fn zero()->Self;
     fn identity()->Self;
     fn apply(&self,x:Self::Vector)->Self::Vector;
+    fn mul_vec(&self,x:Self::Vector)->Self::Vector;
     fn compose(&self,b:&Self)->Self;
     fn transpose(&self)->Self;
+    fn add(&self,b:&Self)->Self;
+    fn scale(&self,c:R)->Self;
     fn rotation(axis:usize,theta:R)->Self;
+    fn rotation_derivative(axis:usize,theta:R)->Self;
 }
 
 impl Matrix for Mat3 {
@@ -119,6 +258,13 @@ impl Matrix for Mat3 {
 	 self[0][2]*x[0] + self[1][2]*x[1] + self[2][2]*x[2]]
     }
 
+    /// Matrix-vector product, the SOFA rxp
+    fn mul_vec(&self,x:Vec3)->Vec3 {
+	[self[0][0]*x[0] + self[0][1]*x[1] + self[0][2]*x[2],
+	 self[1][0]*x[0] + self[1][1]*x[1] + self[1][2]*x[2],
+	 self[2][0]*x[0] + self[2][1]*x[1] + self[2][2]*x[2]]
+    }
+
     fn compose(&self,b:&Self)->Self {
 	let mut c = Self::zero();
 	for i in 0..3 {
@@ -143,6 +289,26 @@ impl Matrix for Mat3 {
 	c
     }
 
+    fn add(&self,b:&Self)->Self {
+	let mut c = Self::zero();
+	for i in 0..3 {
+	    for j in 0..3 {
+		c[i][j] = self[i][j] + b[i][j];
+	    }
+	}
+	c
+    }
+
+    fn scale(&self,k:R)->Self {
+	let mut c = Self::zero();
+	for i in 0..3 {
+	    for j in 0..3 {
+		c[i][j] = k*self[i][j];
+	    }
+	}
+	c
+    }
+
     // RX : [ 1  0  0
     //        0  C  S  
     //        0 -S  C ]
@@ -170,4 +336,18 @@ impl Matrix for Mat3 {
 	r[axis][axis] = 1.0;
 	r
     }
+
+    /// Derivative of rotation(axis,theta) with respect to theta
+    fn rotation_derivative(axis:usize,theta:R)->Self {
+	let c = cos(theta);
+	let s = sin(theta);
+	let i0 = (axis + 1) % 3;
+	let i1 = (axis + 2) % 3;
+	let mut r = Self::zero();
+	r[i0][i0] = -s;
+	r[i0][i1] = c;
+	r[i1][i0] = -c;
+	r[i1][i1] = -s;
+	r
+    }
 }
@@ -1,10 +1,16 @@
 use crate::common::*;
-use crate::time::{TDB,UT1,DJY,DJ00};
+use crate::time::{TT,TDB,UT1,D2S,DJY,DJ00,DJC};
+use crate::frames;
 use crate::epv00_data::*;
 
 /// Astronomical unit (m, IAU 2012)
 pub const AUM : R = 149597870.7e3;
 
+/// Nominal Earth rotation rate, from the rate of the Earth rotation
+/// angle (rad/s)
+#[allow(clippy::excessive_precision)]
+pub const OMEGA : R = TWO_PI * 1.00273781191135448 / D2S;
+
 const AM12 : R =  0.000000211284;
 const AM13 : R = -0.000000091603;
 const AM21 : R = -0.000000230286;
@@ -51,6 +57,76 @@ pub struct PosVel {
     pub v:Vec3
 }
 
+impl PosVel {
+    /// Convert position/velocity from spherical to Cartesian
+    /// coordinates.
+    ///    THETA    longitude angle (radians)
+    ///    PHI      latitude angle (radians)
+    ///    R        radial distance
+    ///    TD       rate of change of THETA
+    ///    PD       rate of change of PHI
+    ///    RD       rate of change of R
+    ///
+    /// Source: s2pv.for
+    pub fn from_spherical(theta:R,phi:R,r:R,td:R,pd:R,rd:R)->Self {
+	let (st,ct) = (sin(theta),cos(theta));
+	let (sp,cp) = (sin(phi),cos(phi));
+	let rcp = r*cp;
+	let x = rcp*ct;
+	let y = rcp*st;
+	let rpd = r*pd;
+	let w = rpd*sp - cp*rd;
+	PosVel {
+	    p:[x,y,r*sp],
+	    v:[-y*td - w*ct,
+	       x*td - w*st,
+	       rpd*cp + sp*rd]
+	}
+    }
+
+    /// Convert position/velocity from Cartesian to spherical
+    /// coordinates.  Returns (THETA,PHI,R,TD,PD,RD) as for
+    /// from_spherical().
+    ///
+    /// Source: pv2s.for
+    pub fn to_spherical(&self)->(R,R,R,R,R,R) {
+	let [mut x,mut y,mut z] = self.p;
+	let [xd,yd,zd] = self.v;
+
+	let mut rxy2 = x*x + y*y;
+	let mut r2 = rxy2 + z*z;
+	let rtrue = sqrt(r2);
+
+	// If null vector, move the origin along the direction of movement.
+	let mut rw = rtrue;
+	if rtrue == 0.0 {
+	    x = xd;
+	    y = yd;
+	    z = zd;
+	    rxy2 = x*x + y*y;
+	    r2 = rxy2 + z*z;
+	    rw = sqrt(r2);
+	}
+
+	let rxy = sqrt(rxy2);
+	let xyp = x*xd + y*yd;
+	let (theta,phi,td,pd) =
+	    if rxy2 != 0.0 {
+		(atan2(y,x),
+		 atan2(z,rxy),
+		 ( x*yd - y*xd ) / rxy2,
+		 ( zd*rxy2 - z*xyp ) / ( r2*rxy ))
+	    } else {
+		(0.0,
+		 if z != 0.0 { atan2(z,rxy) } else { 0.0 },
+		 0.0,
+		 0.0)
+	    };
+	let rd = if rw != 0.0 { ( xyp + z*zd ) / rw } else { 0.0 };
+	(theta,phi,rtrue,td,pd,rd)
+    }
+}
+
 #[derive(Debug,Clone)]
 pub struct EarthPosVel {
     pub heliocentric:PosVel,
@@ -70,6 +146,7 @@ impl From<TDB> for EarthPosVel {
     /// The date should be in the 1900-2100 AD range
     ///
     /// Source: IAU SOFA epv00.for
+    #[allow(clippy::needless_range_loop)]
     fn from(TDB((date1,date2)):TDB)->Self {
 	let t = ( ( date1 - DJ00 ) + date2 ) / DJY;
 	let t2 = t*t;
@@ -224,3 +301,62 @@ pub fn polar_motion_matrix(xp:R,yp:R,sp:R)->Mat3 {
 	.compose(&Mat3::rotation(1,-xp))
 	.compose(&Mat3::rotation(2,sp))
 }
+
+/// Partial derivatives of the polar motion matrix with respect to XP
+/// and YP.
+pub fn polar_motion_partials(xp:R,yp:R,sp:R)->(Mat3,Mat3) {
+    let d_dxp = Mat3::rotation(0,-yp)
+	.compose(&Mat3::rotation_derivative(1,-xp).scale(-1.0))
+	.compose(&Mat3::rotation(2,sp));
+    let d_dyp = Mat3::rotation_derivative(0,-yp).scale(-1.0)
+	.compose(&Mat3::rotation(1,-xp))
+	.compose(&Mat3::rotation(2,sp));
+    (d_dxp,d_dyp)
+}
+
+/// The TIO locator s', positioning the Terrestrial Intermediate Origin
+/// on the equator of the Celestial Intermediate Pole.
+///
+/// Source: sp00.for
+pub fn tio_locator(TT((date1,date2)):TT)->R {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+    -47e-6 * t * AS2R
+}
+
+// Coefficients of IAU 1982 GMST-UT1 model
+const GMST82_A : R = 24110.54841 - D2S / 2.0;
+const GMST82_B : R = 8640184.812866;
+const GMST82_C : R = 0.093104;
+const GMST82_D : R = -6.2e-6;
+
+/// Greenwich mean sidereal time (consistent with IAU 1982/94
+/// resolutions).
+///
+/// Source: gmst82.for
+pub fn mean_sidereal_time_82(UT1((dj1,dj2)):UT1)->R {
+    let (d1,d2) = if dj1 < dj2 { (dj1,dj2) } else { (dj2,dj1) };
+    let t = ( d1 + ( d2 - DJ00 ) ) / DJC;
+
+    // Fractional part of JD(UT1), in seconds.
+    let f = D2S * ( d1 % 1.0 + d2 % 1.0 );
+
+    anp(DS2R * ( ( GMST82_A + ( GMST82_B + ( GMST82_C + GMST82_D * t ) * t ) * t ) + f ))
+}
+
+/// Rate of the IAU 1982 Greenwich mean sidereal time with respect to
+/// UT1 [rad/s]
+pub fn mean_sidereal_time_82_rate(UT1((dj1,dj2)):UT1)->R {
+    let t = ( ( dj1 - DJ00 ) + dj2 ) / DJC;
+    DS2R * ( 1.0 + ( GMST82_B + ( 2.0 * GMST82_C + 3.0 * GMST82_D * t ) * t ) / ( DJC * D2S ) )
+}
+
+/// Greenwich apparent sidereal time, consistent with IAU 1982/94
+/// resolutions.  UT1 is used in place of TT for the equation of the
+/// equinoxes, which is adequate for all practical purposes.
+///
+/// Source: gst94.for
+pub fn apparent_sidereal_time_94(ut1@UT1(ut):UT1)->R {
+    let gmst82 = mean_sidereal_time_82(ut1);
+    let eqeq94 = frames::equation_of_equinoxes_94(TT(ut));
+    anp(gmst82 + eqeq94)
+}
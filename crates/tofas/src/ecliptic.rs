@@ -0,0 +1,99 @@
+// Ecliptic coordinates.  The IAU 2006 functions use the mean ecliptic
+// and equinox of date; the long-term functions use the Vondrák et al.
+// (2011) precession model and take Julian epochs.  Equatorial
+// coordinates are with respect to the ICRS (or GCRS for geocentric
+// directions).
+
+use crate::{
+    common::*,
+    time::TT,
+    frames,
+    longterm,
+    typed::{Rotation,Position,Gcrs,Ecliptic}
+};
+
+/// ICRS equatorial to ecliptic rotation matrix, IAU 2006.
+///
+/// Source: ecm06.for
+pub fn ecliptic_matrix_06(tt:TT)->Mat3 {
+    let ob = frames::mean_obliquity_06(tt); // obl06
+    let bp = frames::bias_and_precession_06(tt); // pmat06
+    Mat3::rotation(0,ob).compose(&bp)
+}
+
+/// Transformation from ICRS equatorial coordinates to ecliptic
+/// coordinates (mean equinox and ecliptic of date) using IAU 2006
+/// precession model.  Returns (DL,DB).
+///
+/// Source: eqec06.for
+pub fn equatorial_to_ecliptic_06(tt:TT,dr:R,dd:R)->(R,R) {
+    let rm = ecliptic_matrix_06(tt); // ecm06
+    let v = rm.mul_vec(spherical_to_cartesian(dr,dd)); // s2c, rxp
+    let (dl,db) = cartesian_to_spherical(v); // c2s
+    (anp(dl),anpm(db))
+}
+
+/// Transformation from ecliptic coordinates (mean equinox and ecliptic
+/// of date) to ICRS RA,Dec, using the IAU 2006 precession model.
+/// Returns (DR,DD).
+///
+/// Source: eceq06.for
+pub fn ecliptic_to_equatorial_06(tt:TT,dl:R,db:R)->(R,R) {
+    let rm = ecliptic_matrix_06(tt); // ecm06
+    let v = rm.transpose().mul_vec(spherical_to_cartesian(dl,db)); // s2c, trxp
+    let (dr,dd) = cartesian_to_spherical(v); // c2s
+    (anp(dr),anpm(dd))
+}
+
+/// ICRS equatorial to ecliptic rotation matrix, long-term.
+///
+/// Source: ltecm.for
+pub fn ecliptic_matrix_long_term(epj:R)->Mat3 {
+    let p = longterm::equator_pole(epj); // ltpequ
+    let z = longterm::ecliptic_pole(epj); // ltpecl
+
+    // Equinox (top row of matrix).
+    let x = p.cross(z).normalize();
+
+    // Middle row of matrix.
+    let y = z.cross(x);
+
+    // Combine with frame bias.
+    frames::with_frame_bias_first_order(&[x,y,z])
+}
+
+/// Transformation from ICRS equatorial coordinates to ecliptic
+/// coordinates (mean equinox and ecliptic of date) using a long-term
+/// precession model.  Returns (DL,DB).
+///
+/// Source: lteqec.for
+pub fn equatorial_to_ecliptic_long_term(epj:R,dr:R,dd:R)->(R,R) {
+    let rm = ecliptic_matrix_long_term(epj); // ltecm
+    let v = rm.mul_vec(spherical_to_cartesian(dr,dd)); // s2c, rxp
+    let (dl,db) = cartesian_to_spherical(v); // c2s
+    (anp(dl),anpm(db))
+}
+
+/// Transformation from ecliptic coordinates (mean equinox and ecliptic
+/// of date) to ICRS RA,Dec, using a long-term precession model.
+/// Returns (DR,DD).
+///
+/// Source: lteceq.for
+pub fn ecliptic_to_equatorial_long_term(epj:R,dl:R,db:R)->(R,R) {
+    let rm = ecliptic_matrix_long_term(epj); // ltecm
+    let v = rm.transpose().mul_vec(spherical_to_cartesian(dl,db)); // s2c, trxp
+    let (dr,dd) = cartesian_to_spherical(v); // c2s
+    (anp(dr),anpm(dd))
+}
+
+/// Rotation from the GCRS to the mean ecliptic and equinox of date,
+/// IAU 2006.
+pub fn gcrs_to_ecliptic(tt:TT)->Rotation<Gcrs,Ecliptic> {
+    Rotation::new(ecliptic_matrix_06(tt),tt)
+}
+
+/// Ecliptic longitude in [0,2π[ and latitude of a position
+pub fn longitude_latitude(p:&Position<Ecliptic>)->(R,R) {
+    let (l,b) = cartesian_to_spherical(p.xyz); // c2s
+    (anp(l),b)
+}
@@ -55,8 +55,8 @@ impl From<&Geodetic> for Geodetic360 {
     fn from(gd:&Geodetic)->Self {
 	let lon = (gd.elong/DEGREE + 180.0).rem_euclid(360.0) - 180.0;
 	let lat = (gd.phi/DEGREE + 90.0).rem_euclid(180.0) - 90.0;
-	debug_assert!(-180.0 <= lon && lon <= 180.0);
-	debug_assert!(-90.0 <= lat && lat <= 90.0);
+	debug_assert!((-180.0..=180.0).contains(&lon));
+	debug_assert!((-90.0..=90.0).contains(&lat));
 	Self {
 	    lat,
 	    lon,
@@ -125,7 +125,7 @@ impl EllipsoidConverter {
     pub fn new(ellipsoid:&Ellipsoid)->Result<Self,EllipsoidError> {
 	let &Ellipsoid{ a,f } = ellipsoid;
 
-	if f < 0.0 || f >= 1.0 {
+	if !(0.0..1.0).contains(&f) {
 	    return Err(EllipsoidError::InvalidFlattening);
 	}
 
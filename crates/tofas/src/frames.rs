@@ -1,15 +1,536 @@
 use crate::{
     common::*,
-    time::{TT,UT1,DJ00,DJC},
-    earth,
-    locator
+    time::{TT,UT1,D2S,DJ00,DJC},
+    earth::{self,PosVel},
+    locator,
+    iers_tables,
+    majorant::{self,Majorant,MajorantMatrix},
+    typed::{Rotation,FrameError,Gcrs,Cirs,Tirs,Itrs,Mod,Tod,Pef}
 };
 
+/// Precession-nutation model used to assemble the celestial to
+/// terrestrial transformation.
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub enum PrecessionNutationModel {
+    /// IAU 1976 precession with IAU 1980 nutation, equinox-based with
+    /// IAU 1982 sidereal time and the IAU 1994 equation of the
+    /// equinoxes.  These models start from the J2000.0 mean equator
+    /// and equinox; as for TEME, the IAU 2000 frame bias takes the GCRS
+    /// to it.
+    Iau1980,
+
+    /// IAU 2000 precession with IAU 2000B nutation (c2t00b)
+    #[default]
+    Iau2000B,
+
+    /// IAU 2000 precession with IAU 2000A nutation (c2t00a).  The
+    /// nutation series are read from the IERS tables, see
+    /// iers_tables::nutation_2000a; the methods panic without them.
+    Iau2000A,
+
+    /// IAU 2006 precession with IAU 2000A nutation adjusted to IAU
+    /// 2006 (c2t06a).  The IAU 2000B series stand in for IAU 2000A,
+    /// within about 1 mas, until the IERS tables are installed (see
+    /// nutation_06).
+    Iau2006
+}
+
+impl PrecessionNutationModel {
+    /// Form the matrix of precession-nutation for a given date
+    /// (including frame bias), equinox-based.
+    pub(crate) fn precession_nutation(self,tt:TT)->Mat3 {
+	match self {
+	    Self::Iau1980 => gcrs_precession_nutation_80(tt),
+	    Self::Iau2000B => precession_nutation(tt),
+	    Self::Iau2000A => precession_nutation_00a(tt),
+	    Self::Iau2006 => precession_nutation_06(tt)
+	}
+    }
+
+    /// Form the celestial-to-intermediate matrix for a given date.
+    /// For the equinox-based IAU 1980 model, the CIP and CIO are
+    /// derived from its precession-nutation matrix.
+    pub(crate) fn celestial_to_intermediate(self,tt:TT)->Mat3 {
+	match self {
+	    Self::Iau1980 =>
+		celestial_to_intermediate_with_bpn(tt,&gcrs_precession_nutation_80(tt)),
+	    Self::Iau2000B => celestial_to_intermediate(tt),
+	    Self::Iau2000A => celestial_to_intermediate_00a(tt),
+	    Self::Iau2006 => celestial_to_intermediate_06(tt)
+	}
+    }
+
+    /// The CIP coordinates X,Y and the CIO locator s, consistent with
+    /// gcrs_to_cirs().
+    pub fn cip_xys(self,tt:TT)->(R,R,R) {
+	match self {
+	    Self::Iau1980 => {
+		let (x,y) = xy_from_bpn(&gcrs_precession_nutation_80(tt)); // bpn2xy
+		(x,y,locator::cio(tt,x,y)) // s00
+	    },
+	    Self::Iau2000B => cip_xys_00b(tt),
+	    Self::Iau2000A => cip_xys_00a(tt),
+	    Self::Iau2006 => cip_xys_06(tt)
+	}
+    }
+
+    /// Majorants of X, Y and s of cip_xys() and of
+    /// cio_origin_shift_tt() between TC - H and TC + H Julian
+    /// centuries from J2000.0.  They follow the evaluation of each
+    /// model, planetary nutation and precession included.
+    ///
+    /// # Panics
+    ///
+    /// For the IAU 2000A model, if its series are not installed.
+    pub(crate) fn cip_xys_majorants(self,tc:R,h:R)->[Majorant;4] {
+	let tmax = abs(tc) + h;
+	let tt = TT((DJ00,tc * DJC));
+	let mut m = match self {
+	    Self::Iau1980 => gcrs_precession_nutation_80_majorant(tmax),
+	    Self::Iau2000B => {
+		let (dpsi,deps) = nutation_majorants(tmax);
+		precession_nutation_majorant(tmax,&dpsi,&deps)
+	    },
+	    Self::Iau2000A => {
+		let (dpsi,deps) = nutation_00a_majorants(tmax);
+		precession_nutation_majorant(tmax,&dpsi,&deps)
+	    },
+	    Self::Iau2006 => {
+		let (dpsi,deps) = nutation_06_majorants(tmax);
+		precession_nutation_06_majorant(tmax,&dpsi,&deps)
+	    }
+	};
+
+	// X and Y are small: bound them from their values at TC
+	let (x0,y0,_) = self.cip_xys(tt);
+	m.0[2][0] = m.0[2][0].with_value(x0,h);
+	m.0[2][1] = m.0[2][1].with_value(y0,h);
+	let (x,y) = (m.0[2][0],m.0[2][1]);
+
+	let s = match self {
+	    Self::Iau2006 => locator::cio_06_majorant(tmax,&x,&y),
+	    _ => locator::cio_majorant(tmax,&x,&y)
+	};
+	let q = match self {
+	    Self::Iau1980 =>
+		equation_of_equinoxes_94_majorant(tmax) +
+		equation_of_origins_majorant(&m,&gcrs_precession_nutation_80(tt),h,&s),
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 => Majorant::default()
+	};
+	[x,y,s,q]
+    }
+
+    /// Form the celestial to terrestrial matrix given the date, the
+    /// UT1 and the polar motion.
+    pub(crate) fn celestial_to_terrestrial(self,tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
+	match self {
+	    Self::Iau1980 =>
+		celestial_to_terrestrial_80(tt,ut1,xp,yp).compose(&frame_bias_matrix()),
+	    Self::Iau2000B => celestial_to_terrestrial(tt,ut1,xp,yp),
+	    Self::Iau2000A => celestial_to_terrestrial_00a(tt,ut1,xp,yp),
+	    Self::Iau2006 => celestial_to_terrestrial_06(tt,ut1,xp,yp)
+	}
+    }
+
+    /// Rotation from the GCRS to the ITRS at the given date.
+    pub fn gcrs_to_itrs(self,tt:TT,ut1:UT1,xp:R,yp:R)->Rotation<Gcrs,Itrs> {
+	Rotation::new(self.celestial_to_terrestrial(tt,ut1,xp,yp),tt)
+    }
+
+    /// Rotation from the GCRS to the true equator and equinox of date,
+    /// i.e. the precession-nutation matrix.
+    pub fn gcrs_to_tod(self,tt:TT)->Rotation<Gcrs,Tod> {
+	Rotation::new(self.precession_nutation(tt),tt)
+    }
+
+    /// Rotation from the GCRS to the CIRS, i.e. the
+    /// celestial-to-intermediate matrix.
+    pub fn gcrs_to_cirs(self,tt:TT)->Rotation<Gcrs,Cirs> {
+	Rotation::new(self.celestial_to_intermediate(tt),tt)
+    }
+
+    /// Bias-precession and nutation matrices for a given date.
+    /// Returns (RBP,RN).
+    pub fn bias_precession_and_nutation(self,tt:TT)->(Mat3,Mat3) {
+	match self {
+	    Self::Iau1980 =>
+		(precession_76(tt).compose(&frame_bias_matrix()),nutation_matrix_80(tt)),
+	    Self::Iau2000B => {
+		let pn : PrecessionNutation = tt.into(); // pn00b
+		(pn.rbp,pn.rn)
+	    },
+	    Self::Iau2000A => {
+		let (dpsi,deps) = nutation_00a(tt); // nut00a
+		let pn = PrecessionNutation::from_tt_with_nutation(tt,dpsi,deps); // pn00
+		(pn.rbp,pn.rn)
+	    },
+	    Self::Iau2006 => {
+		let (dpsi,deps) = nutation_06(tt);
+		let pn = PrecessionNutation::from_tt_with_nutation_06(tt,dpsi,deps); // pn06
+		(pn.rbp,pn.rn)
+	    }
+	}
+    }
+
+    /// The TIO locator s' used by the model; it is neglected by the
+    /// IAU 1980 and IAU 2000B chains.
+    pub fn tio_locator(self,tt:TT)->R {
+	match self {
+	    Self::Iau1980 | Self::Iau2000B => 0.0,
+	    Self::Iau2000A | Self::Iau2006 => earth::tio_locator(tt) // sp00
+	}
+    }
+
+    /// Greenwich apparent sidereal time.  For the IAU 2000 and 2006
+    /// models it is obtained from the Earth rotation angle and the
+    /// equation of the origins, which makes the equinox-based and
+    /// CIO-based chains consistent.
+    ///
+    /// Based on: gst94.for, gst06.for
+    pub fn apparent_sidereal_time(self,tt:TT,ut1:UT1)->R {
+	match self {
+	    Self::Iau1980 =>
+		anp(earth::mean_sidereal_time_82(ut1) + equation_of_equinoxes_94(tt)),
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 => {
+		let rnpb = self.precession_nutation(tt);
+		let (x,y) = xy_from_bpn(&rnpb); // bpn2xy
+		let s = match self {
+		    Self::Iau2006 => locator::cio_06(tt,x,y), // s06
+		    _ => locator::cio(tt,x,y) // s00
+		};
+		anp(earth::rotation_angle(ut1) - equation_of_origins(&rnpb,s))
+	    }
+	}
+    }
+
+    /// TT-dependent part [rad] of cio_origin_shift(): for the IAU
+    /// 1980 model, the equation of the equinoxes EE94 plus the
+    /// equation of the origins of NPB80·B with the s00 locator
+    pub(crate) fn cio_origin_shift_tt(self,tt:TT)->R {
+	match self {
+	    Self::Iau1980 => {
+		let rnpb = gcrs_precession_nutation_80(tt);
+		let (x,y) = xy_from_bpn(&rnpb); // bpn2xy
+		let s = locator::cio(tt,x,y); // s00
+		equation_of_equinoxes_94(tt) + equation_of_origins(&rnpb,s) // eqeq94, eors
+	    },
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 => 0.0
+	}
+    }
+
+    /// cio_origin_shift() from its TT-dependent part Q
+    pub(crate) fn cio_origin_shift_from_tt_part(self,q:R,ut1:UT1)->R {
+	match self {
+	    Self::Iau1980 =>
+		anpm(earth::mean_sidereal_time_82(ut1) - earth::rotation_angle(ut1) + q),
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 => 0.0
+	}
+    }
+
+    /// Angle [rad] about the CIP from the CIO, as located by
+    /// gcrs_to_cirs(), to the origin of the CIRS of the
+    /// CIO-based chain.  It is zero except for the IAU 1980 model,
+    /// whose chain must reproduce the GAST94 of its equinox-based
+    /// chain: the angle is then GAST94 - (ERA - EO), a few
+    /// milliarcseconds, where EO is the equation of the origins for
+    /// the s00 locator.
+    pub fn cio_origin_shift(self,tt:TT,ut1:UT1)->R {
+	self.cio_origin_shift_from_tt_part(self.cio_origin_shift_tt(tt),ut1)
+    }
+
+    /// Rate [rad/s] of the Earth rotation about the CIP with respect
+    /// to UT1 in the CIO-based chain: that of the ERA, or for the IAU
+    /// 1980 model that of GMST82, the origin shift varying with UT1.
+    pub fn sidereal_rate(self,ut1:UT1)->R {
+	match self {
+	    Self::Iau1980 => earth::mean_sidereal_time_82_rate(ut1),
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 => earth::OMEGA
+	}
+    }
+
+    /// The GCRS to CIRS matrix of the CIO-based chain.  For the IAU
+    /// 1980 model it is Rz(GAST94 - ERA)·NPB80·B, and thus depends on
+    /// UT1.
+    fn cio_celestial_to_intermediate(self,tt:TT,ut1:UT1)->Mat3 {
+	match self {
+	    Self::Iau1980 => {
+		let gst = self.apparent_sidereal_time(tt,ut1);
+		let era = earth::rotation_angle(ut1);
+		Mat3::rotation(2,gst - era).compose(&gcrs_precession_nutation_80(tt))
+	    },
+	    Self::Iau2000B | Self::Iau2000A | Self::Iau2006 =>
+		self.celestial_to_intermediate(tt)
+	}
+    }
+
+    /// Decompose the celestial to terrestrial transformation into its
+    /// CIO-based stages.  For the IAU 1980 model, the CIRS origin is
+    /// shifted from the CIO by cio_origin_shift() so that the chain
+    /// reproduces gcrs_to_itrs().
+    pub fn cio_chain(self,tt:TT,ut1:UT1,xp:R,yp:R)->CioChain {
+	CioChain {
+	    gcrs_to_cirs:Rotation::new(self.cio_celestial_to_intermediate(tt,ut1),tt),
+	    cirs_to_tirs:Rotation::new(Mat3::rotation(2,earth::rotation_angle(ut1)),tt),
+	    tirs_to_itrs:Rotation::new(
+		earth::polar_motion_matrix(xp,yp,self.tio_locator(tt)),tt)
+	}
+    }
+
+    /// Decompose the celestial to terrestrial transformation into its
+    /// equinox-based stages.
+    pub fn equinox_chain(self,tt:TT,ut1:UT1,xp:R,yp:R)->EquinoxChain {
+	let (rbp,rn) = self.bias_precession_and_nutation(tt);
+	let gst = self.apparent_sidereal_time(tt,ut1);
+	EquinoxChain {
+	    gcrs_to_mod:Rotation::new(rbp,tt),
+	    mod_to_tod:Rotation::new(rn,tt),
+	    tod_to_pef:Rotation::new(Mat3::rotation(2,gst),tt),
+	    pef_to_itrs:Rotation::new(
+		earth::polar_motion_matrix(xp,yp,self.tio_locator(tt)),tt)
+	}
+    }
+}
+
+/// Rates used when transforming state vectors between the GCRS and
+/// the ITRS.
+#[derive(Copy,Clone,Debug,Default)]
+pub struct EarthRotationRates {
+    /// Excess length of day [s], from EOP when available
+    pub lod:R,
+
+    /// Rates of the polar motion coordinates [rad/s]
+    pub xp_rate:R,
+    pub yp_rate:R,
+
+    /// Whether to include the rate of the precession-nutation matrix.
+    /// That rate, about 7e-12 rad/s, is not differentiated
+    /// analytically but by central differences over +/- one hour: the
+    /// truncation error, from the short-period nutation terms, is
+    /// about 3e-16 rad/s, or 2 nm/s at 7000 km.
+    pub precession_nutation:bool
+}
+
+impl EarthRotationRates {
+    /// Earth angular velocity vector in the TIRS [rad/s], at the rate
+    /// of the Earth rotation angle (see also
+    /// PrecessionNutationModel::sidereal_rate())
+    pub fn omega(&self)->Vec3 {
+	[0.0,0.0,earth::OMEGA * (1.0 - self.lod / D2S)]
+    }
+}
+
+/// Half-width of the interval used to differentiate the
+/// precession-nutation matrix [d]
+const PN_RATE_STEP : R = 1.0/24.0;
+
+/// The celestial to terrestrial matrix with its time derivative and
+/// its partial derivatives with respect to the Earth orientation
+/// parameters
+#[derive(Copy,Clone,Debug)]
+pub struct CelestialToTerrestrialPartials {
+    pub matrix:Mat3,
+
+    /// Time derivative [1/s]
+    pub d_dt:Mat3,
+
+    /// Derivatives with respect to the polar motion coordinates [1/rad]
+    pub d_dxp:Mat3,
+    pub d_dyp:Mat3,
+
+    /// Derivative with respect to UT1-UTC [1/s]
+    pub d_ddut1:Mat3
+}
+
+impl PrecessionNutationModel {
+    /// Partial derivative with respect to TT of the GCRS to CIRS
+    /// matrix of the CIO-based chain [1/s], by central differences
+    /// over a short interval
+    fn celestial_to_intermediate_rate(self,TT((tt1,tt2)):TT,ut1:UT1)->Mat3 {
+	let cp = self.cio_celestial_to_intermediate(TT((tt1,tt2 + PN_RATE_STEP)),ut1);
+	let cm = self.cio_celestial_to_intermediate(TT((tt1,tt2 - PN_RATE_STEP)),ut1);
+	cp.add(&cm.scale(-1.0)).scale(0.5 / (PN_RATE_STEP * D2S))
+    }
+
+    /// Earth angular velocity vector in the TIRS of the CIO-based
+    /// chain [rad/s]
+    fn omega(self,ut1:UT1,rates:&EarthRotationRates)->Vec3 {
+	[0.0,0.0,self.sidereal_rate(ut1) * (1.0 - rates.lod / D2S)]
+    }
+
+    /// Time derivative of W·p [1/s], where W is the polar motion
+    /// matrix
+    fn polar_motion_rate(self,tt:TT,xp:R,yp:R,rates:&EarthRotationRates,p:Vec3)->Vec3 {
+	let (d_dxp,d_dyp) = earth::polar_motion_partials(xp,yp,self.tio_locator(tt));
+	d_dxp.scale(rates.xp_rate).add(&d_dyp.scale(rates.yp_rate)).mul_vec(p)
+    }
+
+    /// The celestial to terrestrial matrix, following the CIO-based
+    /// chain, with its derivatives.  The Earth rotation and polar
+    /// motion contributions are analytic; the slow precession-nutation
+    /// rate, when requested, is obtained by differencing (see
+    /// EarthRotationRates::precession_nutation for its accuracy).
+    pub fn celestial_to_terrestrial_partials(self,tt:TT,ut1:UT1,xp:R,yp:R,
+					     rates:&EarthRotationRates)
+					     ->CelestialToTerrestrialPartials {
+	let c = self.cio_celestial_to_intermediate(tt,ut1);
+	let era = earth::rotation_angle(ut1);
+	let r = Mat3::rotation(2,era);
+	let w = earth::polar_motion_matrix(xp,yp,self.tio_locator(tt));
+	let (wx,wy) = earth::polar_motion_partials(xp,yp,self.tio_locator(tt));
+
+	let rc = r.compose(&c);
+	let matrix = w.compose(&rc);
+	let d_dxp = wx.compose(&rc);
+	let d_dyp = wy.compose(&rc);
+	let d_ddut1 = w.compose(&Mat3::rotation_derivative(2,era)).compose(&c)
+	    .scale(self.sidereal_rate(ut1));
+
+	let mut d_dt = d_ddut1.scale(1.0 - rates.lod / D2S)
+	    .add(&d_dxp.scale(rates.xp_rate))
+	    .add(&d_dyp.scale(rates.yp_rate));
+	if rates.precession_nutation {
+	    d_dt = d_dt.add(&w.compose(&r).compose(&self.celestial_to_intermediate_rate(tt,ut1)));
+	}
+
+	CelestialToTerrestrialPartials { matrix,d_dt,d_dxp,d_dyp,d_ddut1 }
+    }
+
+    /// Transform a state vector from the GCRS to the ITRS.  Velocities
+    /// are in units of position per second.  The velocity includes the
+    /// Earth rotation at sidereal_rate() corrected for the excess
+    /// length of day, the polar motion rates and, when requested, the
+    /// precession-nutation rate.
+    pub fn gcrs_to_itrs_state(self,tt:TT,ut1:UT1,xp:R,yp:R,
+			      rates:&EarthRotationRates,pv:&PosVel)->PosVel {
+	let chain = self.cio_chain(tt,ut1,xp,yp);
+	let c = &chain.gcrs_to_cirs.matrix;
+	let r = &chain.cirs_to_tirs.matrix;
+	let w = &chain.tirs_to_itrs.matrix;
+
+	let p1 = c.mul_vec(pv.p);
+	let mut v1 = c.mul_vec(pv.v);
+	if rates.precession_nutation {
+	    v1 = v1.add(self.celestial_to_intermediate_rate(tt,ut1).mul_vec(pv.p));
+	}
+
+	let p2 = r.mul_vec(p1);
+	let v2 = r.mul_vec(v1).sub(self.omega(ut1,rates).cross(p2));
+
+	let p3 = w.mul_vec(p2);
+	let v3 = w.mul_vec(v2).add(self.polar_motion_rate(tt,xp,yp,rates,p2));
+
+	PosVel { p:p3,v:v3 }
+    }
+
+    /// Transform a state vector from the ITRS to the GCRS.  Velocities
+    /// are in units of position per second.
+    pub fn itrs_to_gcrs_state(self,tt:TT,ut1:UT1,xp:R,yp:R,
+			      rates:&EarthRotationRates,pv:&PosVel)->PosVel {
+	let chain = self.cio_chain(tt,ut1,xp,yp);
+	let ct = chain.gcrs_to_cirs.matrix.transpose();
+	let rt = chain.cirs_to_tirs.matrix.transpose();
+	let wt = chain.tirs_to_itrs.matrix.transpose();
+
+	let p2 = wt.mul_vec(pv.p);
+	let v2 = wt.mul_vec(pv.v.sub(self.polar_motion_rate(tt,xp,yp,rates,p2)));
+
+	let p1 = rt.mul_vec(p2);
+	let v1 = rt.mul_vec(v2.add(self.omega(ut1,rates).cross(p2)));
+
+	let p = ct.mul_vec(p1);
+	let v =
+	    if rates.precession_nutation {
+		ct.mul_vec(v1.sub(self.celestial_to_intermediate_rate(tt,ut1).mul_vec(p)))
+	    } else {
+		ct.mul_vec(v1)
+	    };
+
+	PosVel { p,v }
+    }
+}
+
+/// The celestial to terrestrial transformation as its CIO-based
+/// stages: GCRS to CIRS (precession-nutation), CIRS to TIRS (Earth
+/// rotation angle) and TIRS to ITRS (polar motion).
+#[derive(Copy,Clone,Debug)]
+pub struct CioChain {
+    pub gcrs_to_cirs:Rotation<Gcrs,Cirs>,
+    pub cirs_to_tirs:Rotation<Cirs,Tirs>,
+    pub tirs_to_itrs:Rotation<Tirs,Itrs>
+}
+
+impl CioChain {
+    pub fn gcrs_to_tirs(&self)->Rotation<Gcrs,Tirs> {
+	Rotation::new(self.cirs_to_tirs.matrix.compose(&self.gcrs_to_cirs.matrix),
+		      self.gcrs_to_cirs.epoch)
+    }
+
+    pub fn gcrs_to_itrs(&self)->Rotation<Gcrs,Itrs> {
+	Rotation::new(self.tirs_to_itrs.matrix.compose(&self.gcrs_to_tirs().matrix),
+		      self.gcrs_to_cirs.epoch)
+    }
+}
+
+/// The celestial to terrestrial transformation as its equinox-based
+/// stages: GCRS to mean of date (frame bias and precession), mean to
+/// true of date (nutation), true of date to PEF (apparent sidereal
+/// time) and PEF to ITRS (polar motion).
+///
+/// For the IAU 1980 model the GCRS to mean of date stage is the frame
+/// bias followed by the IAU 1976 precession from J2000.0.
+#[derive(Copy,Clone,Debug)]
+pub struct EquinoxChain {
+    pub gcrs_to_mod:Rotation<Gcrs,Mod>,
+    pub mod_to_tod:Rotation<Mod,Tod>,
+    pub tod_to_pef:Rotation<Tod,Pef>,
+    pub pef_to_itrs:Rotation<Pef,Itrs>
+}
+
+impl EquinoxChain {
+    pub fn gcrs_to_tod(&self)->Rotation<Gcrs,Tod> {
+	Rotation::new(self.mod_to_tod.matrix.compose(&self.gcrs_to_mod.matrix),
+		      self.gcrs_to_mod.epoch)
+    }
+
+    pub fn gcrs_to_pef(&self)->Rotation<Gcrs,Pef> {
+	Rotation::new(self.tod_to_pef.matrix.compose(&self.gcrs_to_tod().matrix),
+		      self.gcrs_to_mod.epoch)
+    }
+
+    pub fn gcrs_to_itrs(&self)->Rotation<Gcrs,Itrs> {
+	Rotation::new(self.pef_to_itrs.matrix.compose(&self.gcrs_to_pef().matrix),
+		      self.gcrs_to_mod.epoch)
+    }
+}
+
+impl std::str::FromStr for PrecessionNutationModel {
+    type Err = FramesError;
+
+    fn from_str(s:&str)->Result<Self,Self::Err> {
+	match s {
+	    "1980" => Ok(Self::Iau1980),
+	    "2000B"|"2000b" => Ok(Self::Iau2000B),
+	    "2000A"|"2000a" =>
+		match iers_tables::nutation_2000a() {
+		    Some(_) => Ok(Self::Iau2000A),
+		    None => Err(FramesError::MissingNutation2000A)
+		},
+	    "2006" => Ok(Self::Iau2006),
+	    _ => Err(FramesError::UnknownModel)
+	}
+    }
+}
+
+custom_error!{pub FramesError
+	      UnknownModel          = "unknown precession-nutation model",
+	      MissingNutation2000A  = "IAU 2000A nutation series not installed"
+}
+
 /// Form the celestial to terrestrial matrix given the date, the UT1
 /// and the polar motion, using the IAU 2000B nutation model.
 ///
 /// Source: c2t00b.for
-pub fn celestial_to_terrestrial(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
+pub(crate) fn celestial_to_terrestrial(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
     let rc2i = celestial_to_intermediate(tt);
     let era = earth::rotation_angle(ut1);
     let rpom = earth::polar_motion_matrix(xp,yp,0.0);
@@ -20,7 +541,7 @@ pub fn celestial_to_terrestrial(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
 /// IAU 2000B precession-nutation model.
 ///
 /// Source: c2i00b.for
-pub fn celestial_to_intermediate(tt:TT)->Mat3 {
+pub(crate) fn celestial_to_intermediate(tt:TT)->Mat3 {
     let rbpn = precession_nutation(tt); // pnm00b
     celestial_to_intermediate_with_bpn(tt,&rbpn) //c2ibpn
 }
@@ -29,7 +550,7 @@ pub fn celestial_to_intermediate(tt:TT)->Mat3 {
 /// frame bias), equinox-based, IAU 2000B model.
 ///
 /// Source: pnm00b.for
-pub fn precession_nutation(tt:TT)->Mat3 {
+pub(crate) fn precession_nutation(tt:TT)->Mat3 {
     let pn : PrecessionNutation = tt.into();
     pn.rbpn
 }
@@ -40,22 +561,22 @@ pub struct PrecessionNutation {
     pub dpsi:R,
 
     /// Mean obliquity
-    epsa:R,
+    pub epsa:R,
 
     /// Frame bias matrix
-    rb:Mat3,
+    pub rb:Mat3,
 
     /// Precession matrix
-    rp:Mat3,
+    pub rp:Mat3,
 
     /// Bias-precession matrix
-    rbp:Mat3,
+    pub rbp:Mat3,
 
     /// Nutation matrix
-    rn:Mat3,
+    pub rn:Mat3,
 
     /// GCRS-to-true matrix
-    rbpn:Mat3
+    pub rbpn:Mat3
 }
 
 impl From<TT> for PrecessionNutation {
@@ -86,6 +607,24 @@ impl PrecessionNutation {
     }
 }
 
+/// Majorant of the matrix rbpn of
+/// PrecessionNutation::from_tt_with_nutation() for |t| <= TMAX
+/// centuries, given those of the nutation
+fn precession_nutation_majorant(tmax:R,dpsi:&Majorant,deps:&Majorant)->MajorantMatrix {
+    let arcsec = |c:&[R]| Majorant::polynomial(c,tmax).scale(AS2R);
+    let epsa = arcsec(&OBL80) + Majorant::polynomial(&[0.0,OBLCOR],tmax);
+    let psia = arcsec(&PSIA77) + Majorant::polynomial(&[0.0,PRECOR],tmax);
+    let oma = Majorant::constant(EPS0) + arcsec(&OMA77) + Majorant::polynomial(&[0.0,OBLCOR],tmax);
+    let rp =
+	MajorantMatrix::rotation(2,&arcsec(&CHIA))
+	.compose(&MajorantMatrix::rotation(0,&oma))
+	.compose(&MajorantMatrix::rotation(2,&psia))
+	.compose(&MajorantMatrix::rotation(0,&Majorant::constant(EPS0)));
+    nutation_matrix_majorant(&epsa,dpsi,deps)
+	.compose(&rp)
+	.compose(&MajorantMatrix::constant(&frame_bias_matrix()))
+}
+
 const DPPLAN : R = - 0.135 * MAS2R;
 const DEPLAN : R =   0.388 * MAS2R;
 
@@ -252,14 +791,20 @@ const CLS : &[[R;6]] = &[
 const NLS : usize = 77;
 const U2R : R = AS2R/1e7;
 
+/// Fundamental arguments l, l', F, D and Omega of the IAU 2000B model:
+/// their linear expressions [arcsec]
+const ARGS_00B : [[R;2];5] = [
+    [485868.249036,1717915923.2178],
+    [1287104.79305,129596581.0481],
+    [335779.526232,1739527262.8478],
+    [1072260.70369,1602961601.2090],
+    [450160.398036,-6962890.5431]
+];
+
 /// Source: nut00b.for
 pub fn nutation(TT((date1,date2)):TT)->(R,R) {
     let t = (( date1 - DJ00 ) + date2) / DJC;
-    let el = ((485868.249036 + 1717915923.2178 * t) % TURNAS) * AS2R;
-    let elp = ((1287104.79305 + 129596581.0481 * t) % TURNAS) * AS2R;
-    let f   = ((335779.526232 + 1739527262.8478 * t) % TURNAS) * AS2R;
-    let d   = ((1072260.70369 + 1602961601.2090 * t) % TURNAS) * AS2R;
-    let om  = ((450160.398036 - 6962890.5431 * t) % TURNAS) * AS2R;
+    let [el,elp,f,d,om] = ARGS_00B.map(|c| (polynomial(&c,t) % TURNAS) * AS2R);
 
     let mut dp = 0.0;
     let mut de = 0.0;
@@ -290,47 +835,65 @@ pub fn nutation(TT((date1,date2)):TT)->(R,R) {
     (dpsi,deps)
 }
 
+/// Majorants of nutation() for |t| <= TMAX centuries
+fn nutation_majorants(tmax:R)->(Majorant,Majorant) {
+    let args = ARGS_00B.map(|c| c.map(|ck| ck * AS2R));
+    let mut dp = Majorant::default();
+    let mut de = Majorant::default();
+    for (n,c) in NALS.iter().zip(CLS) {
+	let arg = Majorant::polynomial(&majorant::combination(n.map(R::from),&args),tmax);
+	let (sarg,carg) = (arg.sin(),arg.cos());
+	dp = dp + Majorant::polynomial(&c[0..2],tmax) * sarg + carg.scale(c[2]);
+	de = de + Majorant::polynomial(&c[3..5],tmax) * carg + sarg.scale(c[5]);
+    }
+    (dp.scale(U2R) + Majorant::constant(DPPLAN),de.scale(U2R) + Majorant::constant(DEPLAN))
+}
+
+/// Precession and obliquity corrections of pr00 [rad/century]
+const PRECOR : R = -0.29965 * AS2R;
+const OBLCOR : R = -0.02524 * AS2R;
+
 /// Source: pr00.for
 pub fn precession_rate(TT((date1,date2)):TT)->(R,R) {
-    const PRECOR : R = -0.29965 * AS2R;
-    const OBLCOR : R = -0.02524 * AS2R;
     let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
     let dpsipr = PRECOR * t;
     let depspr = OBLCOR * t;
     (dpsipr,depspr)
 }
 
+/// Mean obliquity of obl80, polynomial [arcsec]
+const OBL80 : [R;4] = [84381.448,-46.8150,-0.00059,0.001813];
+
 /// Source: obl80.for
 pub fn mean_obliquity(TT((date1,date2)):TT)->R {
     let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
-    AS2R * ( 84381.448 +
-              ( -46.8150 +
-		 ( -0.00059 +
-                    0.001813 * t ) * t ) * t )
+    AS2R * polynomial(&OBL80,t)
 }
 
+/// J2000.0 obliquity (Lieske et al. 1977)
+const EPS0 : R = 84381.448 * AS2R;
+
+// Precession angles of bp00 (Lieske et al. 1977), polynomials [arcsec]:
+// ψA, the part of ωA - ε0 and χA.
+const PSIA77 : [R;4] = [0.0,5038.7784,-1.07259,-0.001147];
+const OMA77 : [R;4] = [0.0,0.0,0.05127,-0.007726];
+const CHIA : [R;4] = [0.0,10.5526,-2.38064,-0.001125];
+
 /// Returns (RB,RP,RBP)
 /// Source: bp00.for
 pub fn bias_and_precession(tt@TT((date1,date2)):TT)->(Mat3,Mat3,Mat3) {
     let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
-    let (dpsibi,depsbi,dra0) = frame_bias();
 
-    const EPS0 : R = 84381.448 * AS2R;
-
-    let psia77 = ( 5038.7784 + ( -1.07259 + ( -0.001147 ) * t ) * t ) * t * AS2R;
-    let oma77 = EPS0 + (( 0.05127 + ( -0.007726 ) * t ) * t ) * t * AS2R;
-    let chia = ( 10.5526 + ( -2.38064 + ( -0.001125 ) * t ) * t ) * t * AS2R;
+    let psia77 = polynomial(&PSIA77,t) * AS2R;
+    let oma77 = EPS0 + polynomial(&OMA77,t) * AS2R;
+    let chia = polynomial(&CHIA,t) * AS2R;
 
     let (dpsipr,depspr) = precession_rate(tt); // pr00
 
     let psia = psia77 + dpsipr;
     let oma = oma77 + depspr;
 
-    let rb = 
-	Mat3::identity()
-	.compose(&Mat3::rotation(0,-depsbi))
-	.compose(&Mat3::rotation(1,dpsibi*sin(EPS0)))
-	.compose(&Mat3::rotation(2,dra0));
+    let rb = frame_bias_matrix();
 
     let rp =
 	Mat3::identity()
@@ -352,6 +915,30 @@ pub fn frame_bias()->(R,R,R) {
     (DPBIAS,DEBIAS,DRA0)
 }
 
+/// Frame bias matrix, from the GCRS to the J2000.0 mean equator and
+/// equinox, IAU 2000 (as in bp00).
+pub fn frame_bias_matrix()->Mat3 {
+    let (dpsibi,depsbi,dra0) = frame_bias(); // bi00
+    Mat3::identity()
+	.compose(&Mat3::rotation(0,-depsbi))
+	.compose(&Mat3::rotation(1,dpsibi*sin(EPS0)))
+	.compose(&Mat3::rotation(2,dra0))
+}
+
+/// Combine a matrix from the J2000.0 mean equator and equinox with the
+/// ICRS frame bias of the IERS Conventions 2010 (Eqs. 5.21 and 5.33),
+/// to first order, giving a matrix from the GCRS (as in ltpb, ltecm).
+pub fn with_frame_bias_first_order(rm:&Mat3)->Mat3 {
+    const DX : R = -0.016617 * AS2R;
+    const DE : R = -0.0068192 * AS2R;
+    const DR : R = -0.0146 * AS2R;
+
+    let row = |v:Vec3| [  v[0]    - v[1]*DR + v[2]*DX,
+			  v[0]*DR + v[1]    + v[2]*DE,
+			 -v[0]*DX - v[1]*DE + v[2]];
+    [row(rm[0]),row(rm[1]),row(rm[2])]
+}
+
 /// Source: numat.for
 pub fn nutation_matrix(epsa:R,dpsi:R,deps:R)->Mat3 {
     Mat3::identity()
@@ -360,20 +947,27 @@ pub fn nutation_matrix(epsa:R,dpsi:R,deps:R)->Mat3 {
 	.compose(&Mat3::rotation(0,epsa))
 }
 
+/// Majorant of nutation_matrix()
+fn nutation_matrix_majorant(epsa:&Majorant,dpsi:&Majorant,deps:&Majorant)->MajorantMatrix {
+    MajorantMatrix::rotation(0,&(*epsa + *deps))
+	.compose(&MajorantMatrix::rotation(2,dpsi))
+	.compose(&MajorantMatrix::rotation(0,epsa))
+}
+
 /// Source: c2ibpn.for
-pub fn celestial_to_intermediate_with_bpn(tt:TT,rbpn:&Mat3)->Mat3 {
+pub(crate) fn celestial_to_intermediate_with_bpn(tt:TT,rbpn:&Mat3)->Mat3 {
     let (x,y) = xy_from_bpn(rbpn); // bpn2xy
     celestial_to_intermediate_with_bpn_and_xy(tt,x,y)
 }
 
 /// Source: c2ixy.for
-pub fn celestial_to_intermediate_with_bpn_and_xy(tt:TT,x:R,y:R)->Mat3 {
+pub(crate) fn celestial_to_intermediate_with_bpn_and_xy(tt:TT,x:R,y:R)->Mat3 {
     let s00 = locator::cio(tt,x,y); // s00
     celestial_to_intermediate_from_xys(x,y,s00) // c2ixys
 }
 
 /// Source: c2ixys.for
-pub fn celestial_to_intermediate_from_xys(x:R,y:R,s:R)->Mat3 {
+pub(crate) fn celestial_to_intermediate_from_xys(x:R,y:R,s:R)->Mat3 {
     let r2 = x*x + y*y;
     let e =
 	if r2 > 0.0 {
@@ -394,6 +988,908 @@ pub fn xy_from_bpn(rbpn:&Mat3)->(R,R) {
 }
 
 /// Source: c2tcio.for
-pub fn celestial_to_terrestrial_from_cio_components(rc2i:&Mat3,era:R,rpom:&Mat3)->Mat3 {
-    rpom.compose(&Mat3::rotation(2,era).compose(&rc2i))
+pub(crate) fn celestial_to_terrestrial_from_cio_components(rc2i:&Mat3,era:R,rpom:&Mat3)->Mat3 {
+    rpom.compose(&Mat3::rotation(2,era).compose(rc2i))
+}
+
+/// Mean obliquity of obl06, polynomial [arcsec]
+const OBL06 : [R;6] = [84381.406,-46.836769,-0.0001831,0.00200340,-0.000000576,-0.0000000434];
+
+/// Mean obliquity of the ecliptic, IAU 2006 precession model.
+///
+/// Source: obl06.for
+pub fn mean_obliquity_06(TT((date1,date2)):TT)->R {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+    polynomial(&OBL06,t) * AS2R
+}
+
+// Fukushima-Williams angles γ̄, φ̄ and ψ̄ of pfw06, polynomials [arcsec]
+const GAMB06 : [R;6] = [-0.052928,10.556378,0.4932044,-0.00031238,-0.000002788,0.0000000260];
+const PHIB06 : [R;6] = [84381.412819,-46.811016,0.0511268,0.00053289,-0.000000440,-0.0000000176];
+const PSIB06 : [R;6] = [-0.041775,5038.481484,1.5584175,-0.00018522,-0.000026452,-0.0000000148];
+
+/// Precession angles, IAU 2006 (Fukushima-Williams 4-angle formulation).
+///
+/// Returns (GAMB,PHIB,PSIB,EPSA), i.e. the F-W angles γ̄, φ̄, ψ̄ and
+/// the mean obliquity εA.
+///
+/// Source: pfw06.for
+pub fn fukushima_williams_angles(tt@TT((date1,date2)):TT)->(R,R,R,R) {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+    let gamb = polynomial(&GAMB06,t) * AS2R;
+    let phib = polynomial(&PHIB06,t) * AS2R;
+    let psib = polynomial(&PSIB06,t) * AS2R;
+    let epsa = mean_obliquity_06(tt); // obl06
+    (gamb,phib,psib,epsa)
+}
+
+/// Form rotation matrix given the Fukushima-Williams angles.
+///
+/// Source: fw2m.for
+pub fn fukushima_williams_matrix(gamb:R,phib:R,psi:R,eps:R)->Mat3 {
+    Mat3::identity()
+	.compose(&Mat3::rotation(0,-eps))
+	.compose(&Mat3::rotation(2,-psi))
+	.compose(&Mat3::rotation(0,phib))
+	.compose(&Mat3::rotation(2,gamb))
+}
+
+/// CIP X,Y given Fukushima-Williams bias-precession-nutation angles.
+///
+/// Source: fw2xy.for
+pub fn xy_from_fukushima_williams(gamb:R,phib:R,psi:R,eps:R)->(R,R) {
+    let r = fukushima_williams_matrix(gamb,phib,psi,eps); // fw2m
+    xy_from_bpn(&r) // bpn2xy
+}
+
+/// Precession matrix (including frame bias) from GCRS to a specified
+/// date, IAU 2006 model.
+///
+/// Source: pmat06.for
+pub fn bias_and_precession_06(tt:TT)->Mat3 {
+    let (gamb,phib,psib,epsa) = fukushima_williams_angles(tt); // pfw06
+    fukushima_williams_matrix(gamb,phib,psib,epsa) // fw2m
+}
+
+/// Apply the IAU 2006 adjustments to IAU 2000 nutation angles: the
+/// J2 secular variation and the change in the obliquity of the ecliptic
+/// between the IAU 2000 and IAU 2006 precession models.  Applied to the
+/// IAU 2000A angles this is the IAU 2006/2000A nutation.
+///
+/// Source: nut06a.for
+pub fn nutation_06_from_00(tt:TT,dp:R,de:R)->(R,R) {
+    let (fpsi,feps) = nutation_06_factors(tt);
+    (dp * fpsi,de * feps)
+}
+
+/// Secular variation of J2 [1/century] and change of obliquity
+/// factor of the IAU 2006 adjustments
+const FJ2 : R = -2.7774e-6;
+const FEPS : R = 0.4697e-6;
+
+/// Factors applied by the IAU 2006 adjustments to the IAU 2000
+/// nutation in longitude and obliquity
+pub(crate) fn nutation_06_factors(TT((date1,date2)):TT)->(R,R) {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+    let fj2 = FJ2 * t;
+    (1.0 + ( FEPS + fj2 ),1.0 + fj2)
+}
+
+/// Majorants of nutation_06_factors() for |t| <= TMAX centuries
+fn nutation_06_factor_majorants(tmax:R)->(Majorant,Majorant) {
+    (Majorant::polynomial(&[1.0 + FEPS,FJ2],tmax),Majorant::polynomial(&[1.0,FJ2],tmax))
+}
+
+/// Nutation, IAU 2000A series with the IAU 2006 adjustments.
+///
+/// The IAU 2000A series are read from the IERS tables and must have
+/// been installed with iers_tables::install_nutation_2000a().  Until
+/// then, the IAU 2000B luni-solar series and fixed planetary offsets
+/// stand in for them, which agrees with IAU 2006/2000A to about 1 mas.
+///
+/// Based on: nut06a.for
+pub fn nutation_06(tt:TT)->(R,R) {
+    match iers_tables::nutation_2000a() {
+	Some(nutation) => nutation.nutation_06a(tt), // nut06a
+	None => {
+	    let (dp,de) = nutation(tt); // nut00b
+	    nutation_06_from_00(tt,dp,de)
+	}
+    }
+}
+
+/// Majorants of nutation_06() for |t| <= TMAX centuries
+fn nutation_06_majorants(tmax:R)->(Majorant,Majorant) {
+    match iers_tables::nutation_2000a() {
+	Some(nutation) => nutation.majorants_06a(tmax),
+	None => {
+	    let (dp,de) = nutation_majorants(tmax);
+	    let (fpsi,feps) = nutation_06_factor_majorants(tmax);
+	    (dp * fpsi,de * feps)
+	}
+    }
+}
+
+impl PrecessionNutation {
+    /// Precession-nutation, IAU 2006 model, given the nutation.  The
+    /// nutation angles must be compatible with IAU 2006, for example
+    /// from [nutation_06].
+    ///
+    /// Source: pn06.for
+    pub fn from_tt_with_nutation_06(tt:TT,dpsi:R,deps:R)->Self {
+	// Bias-precession Fukushima-Williams angles of J2000.0 = frame bias.
+	let (gamb,phib,psib,eps) = fukushima_williams_angles(TT((DJ00,0.0)));
+	let rb = fukushima_williams_matrix(gamb,phib,psib,eps);
+
+	// Bias-precession Fukushima-Williams angles of date.
+	let (gamb,phib,psib,eps) = fukushima_williams_angles(tt);
+	let rbp = fukushima_williams_matrix(gamb,phib,psib,eps);
+	let rp = rbp.compose(&rb.transpose());
+
+	// Equinox based bias-precession-nutation matrix.
+	let rbpn = fukushima_williams_matrix(gamb,phib,psib + dpsi,eps + deps);
+	let rn = rbpn.compose(&rbp.transpose());
+
+	Self {
+	    dpsi,
+	    epsa:eps,
+	    rb,
+	    rp,
+	    rbp,
+	    rn,
+	    rbpn
+	}
+    }
+}
+
+/// Form the matrix of precession-nutation for a given date (including
+/// frame bias), equinox-based, IAU 2006 precession and IAU 2000A
+/// nutation with the IAU 2006 adjustments (see [nutation_06]).
+///
+/// Based on: pnm06a.for
+pub(crate) fn precession_nutation_06(tt:TT)->Mat3 {
+    let (gamb,phib,psib,epsa) = fukushima_williams_angles(tt); // pfw06
+    let (dp,de) = nutation_06(tt);
+    fukushima_williams_matrix(gamb,phib,psib + dp,epsa + de) // fw2m
+}
+
+/// Majorant of precession_nutation_06() for |t| <= TMAX centuries,
+/// given those of the nutation
+fn precession_nutation_06_majorant(tmax:R,dp:&Majorant,de:&Majorant)->MajorantMatrix {
+    let arcsec = |c:&[R]| Majorant::polynomial(c,tmax).scale(AS2R);
+    MajorantMatrix::rotation(0,&(arcsec(&OBL06) + *de))
+	.compose(&MajorantMatrix::rotation(2,&(arcsec(&PSIB06) + *dp)))
+	.compose(&MajorantMatrix::rotation(0,&arcsec(&PHIB06)))
+	.compose(&MajorantMatrix::rotation(2,&arcsec(&GAMB06)))
+}
+
+/// Form the celestial-to-intermediate matrix for a given date using the
+/// IAU 2006 precession model.
+///
+/// Based on: c2i06a.for
+pub(crate) fn celestial_to_intermediate_06(tt:TT)->Mat3 {
+    let (x,y,s) = cip_xys_06(tt);
+    celestial_to_intermediate_from_xys(x,y,s) // c2ixys
+}
+
+/// Form the celestial to terrestrial matrix given the date, the UT1
+/// and the polar motion, using the IAU 2006 precession model.  Unlike
+/// [celestial_to_terrestrial], the TIO locator s' is included.
+///
+/// Based on: c2t06a.for
+pub(crate) fn celestial_to_terrestrial_06(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
+    let rc2i = celestial_to_intermediate_06(tt);
+    let era = earth::rotation_angle(ut1);
+    let sp = earth::tio_locator(tt); // sp00
+    let rpom = earth::polar_motion_matrix(xp,yp,sp);
+    celestial_to_terrestrial_from_cio_components(&rc2i,era,&rpom)
+}
+
+/// CIP X,Y coordinates for a given date, IAU 2006 precession and IAU
+/// 2000A nutation.
+///
+/// The coordinates are evaluated directly from the series of the IERS
+/// Conventions once installed (see iers_tables::cip_series), without
+/// forming the bias-precession-nutation matrix.  Until then, they are
+/// those of [precession_nutation_06] (see [nutation_06]), obtained from
+/// the Fukushima-Williams angles.
+///
+/// Based on: xy06.for
+pub fn cip_xy_06(tt:TT)->(R,R) {
+    match iers_tables::cip_series() {
+	Some(series) => series.xy_06(tt), // xy06
+	None => cip_xy_fukushima_williams_06(tt)
+    }
+}
+
+/// CIP X,Y coordinates of [precession_nutation_06], from the
+/// Fukushima-Williams angles and the nutation
+fn cip_xy_fukushima_williams_06(tt:TT)->(R,R) {
+    let (gamb,phib,psib,epsa) = fukushima_williams_angles(tt); // pfw06
+    let (dp,de) = nutation_06(tt);
+    let psi = psib + dp;
+    let eps = epsa + de;
+
+    // Third row of the matrix given by fw2m
+    let a = sin(eps)*sin(psi);
+    let b = sin(eps)*cos(psi)*cos(phib) - cos(eps)*sin(phib);
+    let x = a*cos(gamb) - b*sin(gamb);
+    let y = a*sin(gamb) + b*cos(gamb);
+    (x,y)
+}
+
+/// CIP X,Y coordinates and the CIO locator s for a given date, IAU
+/// 2006 precession and IAU 2000A nutation with the IAU 2006
+/// adjustments (see [nutation_06]).  As in SOFA, X and Y are those of
+/// [precession_nutation_06] rather than of the series.
+///
+/// Based on: xys06a.for
+pub fn cip_xys_06(tt:TT)->(R,R,R) {
+    let (x,y) = cip_xy_fukushima_williams_06(tt);
+    let s = locator::cio_06(tt,x,y); // s06
+    (x,y,s)
+}
+
+/// The installed IAU 2000A nutation series
+///
+/// # Panics
+///
+/// If they are not installed (see iers_tables::nutation_2000a).
+fn installed_nutation_2000a()->&'static iers_tables::Nutation2000A {
+    iers_tables::nutation_2000a()
+	.expect("IAU 2000A nutation series not installed, see iers_tables")
+}
+
+/// Nutation, IAU 2000A model, from the installed series.
+///
+/// # Panics
+///
+/// If the series are not installed (see iers_tables::nutation_2000a).
+///
+/// Based on: nut00a.for
+pub fn nutation_00a(tt:TT)->(R,R) {
+    installed_nutation_2000a().nutation_00a(tt) // nut00a
+}
+
+/// Majorants of nutation_00a() for |t| <= TMAX centuries
+///
+/// # Panics
+///
+/// If the series are not installed.
+fn nutation_00a_majorants(tmax:R)->(Majorant,Majorant) {
+    let (dp,de) = installed_nutation_2000a().majorants_06a(tmax);
+    let (fpsi,feps) = nutation_06_factor_majorants(tmax);
+    (dp * fpsi.recip(1.0 + FEPS - abs(FJ2) * tmax),
+     de * feps.recip(1.0 - abs(FJ2) * tmax))
+}
+
+/// Form the matrix of precession-nutation for a given date (including
+/// frame bias), equinox-based, IAU 2000A model.
+///
+/// # Panics
+///
+/// If the IAU 2000A series are not installed.
+///
+/// Source: pnm00a.for
+pub(crate) fn precession_nutation_00a(tt:TT)->Mat3 {
+    let (dpsi,deps) = nutation_00a(tt); // nut00a
+    PrecessionNutation::from_tt_with_nutation(tt,dpsi,deps).rbpn // pn00
+}
+
+/// CIP X,Y coordinates and the CIO locator s for a given date, IAU
+/// 2000A precession-nutation model.
+///
+/// # Panics
+///
+/// If the IAU 2000A series are not installed.
+///
+/// Source: xys00a.for
+pub fn cip_xys_00a(tt:TT)->(R,R,R) {
+    let rbpn = precession_nutation_00a(tt); // pnm00a
+    let (x,y) = xy_from_bpn(&rbpn); // bpn2xy
+    let s = locator::cio(tt,x,y); // s00
+    (x,y,s)
+}
+
+/// Form the celestial-to-intermediate matrix for a given date using the
+/// IAU 2000A precession-nutation model.
+///
+/// # Panics
+///
+/// If the IAU 2000A series are not installed.
+///
+/// Source: c2i00a.for
+pub(crate) fn celestial_to_intermediate_00a(tt:TT)->Mat3 {
+    let rbpn = precession_nutation_00a(tt); // pnm00a
+    celestial_to_intermediate_with_bpn(tt,&rbpn) // c2ibpn
+}
+
+/// Form the celestial to terrestrial matrix given the date, the UT1
+/// and the polar motion, using the IAU 2000A nutation model.  The TIO
+/// locator s' is included.
+///
+/// # Panics
+///
+/// If the IAU 2000A series are not installed.
+///
+/// Source: c2t00a.for
+pub(crate) fn celestial_to_terrestrial_00a(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
+    let rc2i = celestial_to_intermediate_00a(tt); // c2i00a
+    let era = earth::rotation_angle(ut1); // era00
+    let sp = earth::tio_locator(tt); // sp00
+    let rpom = earth::polar_motion_matrix(xp,yp,sp); // pom00
+    celestial_to_terrestrial_from_cio_components(&rc2i,era,&rpom) // c2tcio
+}
+
+/// CIP X,Y coordinates and the CIO locator s for a given date, IAU
+/// 2000B precession-nutation model.
+///
+/// Source: xys00b.for
+pub fn cip_xys_00b(tt:TT)->(R,R,R) {
+    let rbpn = precession_nutation(tt); // pnm00b
+    let (x,y) = xy_from_bpn(&rbpn); // bpn2xy
+    let s = locator::cio(tt,x,y); // s00
+    (x,y,s)
+}
+
+// IAU 1980 nutation series: multipliers of L, L', F, D, Ω and the
+// coefficients of longitude and obliquity in units of 0.1 mas.
+
+const NALS80 : &[[i8;5]] = &[
+    [ 0,  0,  0,  0,  1],
+    [ 0,  0,  0,  0,  2],
+    [-2,  0,  2,  0,  1],
+    [ 2,  0, -2,  0,  0],
+    [-2,  0,  2,  0,  2],
+    [ 1, -1,  0, -1,  0],
+    [ 0, -2,  2, -2,  1],
+    [ 2,  0, -2,  0,  1],
+    [ 0,  0,  2, -2,  2],
+    [ 0,  1,  0,  0,  0],
+    [ 0,  1,  2, -2,  2],
+    [ 0, -1,  2, -2,  2],
+    [ 0,  0,  2, -2,  1],
+    [ 2,  0,  0, -2,  0],
+    [ 0,  0,  2, -2,  0],
+    [ 0,  2,  0,  0,  0],
+    [ 0,  1,  0,  0,  1],
+    [ 0,  2,  2, -2,  2],
+    [ 0, -1,  0,  0,  1],
+    [-2,  0,  0,  2,  1],
+    [ 0, -1,  2, -2,  1],
+    [ 2,  0,  0, -2,  1],
+    [ 0,  1,  2, -2,  1],
+    [ 1,  0,  0, -1,  0],
+    [ 2,  1,  0, -2,  0],
+    [ 0,  0, -2,  2,  1],
+    [ 0,  1, -2,  2,  0],
+    [ 0,  1,  0,  0,  2],
+    [-1,  0,  0,  1,  1],
+    [ 0,  1,  2, -2,  0],
+    [ 0,  0,  2,  0,  2],
+    [ 1,  0,  0,  0,  0],
+    [ 0,  0,  2,  0,  1],
+    [ 1,  0,  2,  0,  2],
+    [ 1,  0,  0, -2,  0],
+    [-1,  0,  2,  0,  2],
+    [ 0,  0,  0,  2,  0],
+    [ 1,  0,  0,  0,  1],
+    [-1,  0,  0,  0,  1],
+    [-1,  0,  2,  2,  2],
+    [ 1,  0,  2,  0,  1],
+    [ 0,  0,  2,  2,  2],
+    [ 2,  0,  0,  0,  0],
+    [ 1,  0,  2, -2,  2],
+    [ 2,  0,  2,  0,  2],
+    [ 0,  0,  2,  0,  0],
+    [-1,  0,  2,  0,  1],
+    [-1,  0,  0,  2,  1],
+    [ 1,  0,  0, -2,  1],
+    [-1,  0,  2,  2,  1],
+    [ 1,  1,  0, -2,  0],
+    [ 0,  1,  2,  0,  2],
+    [ 0, -1,  2,  0,  2],
+    [ 1,  0,  2,  2,  2],
+    [ 1,  0,  0,  2,  0],
+    [ 2,  0,  2, -2,  2],
+    [ 0,  0,  0,  2,  1],
+    [ 0,  0,  2,  2,  1],
+    [ 1,  0,  2, -2,  1],
+    [ 0,  0,  0, -2,  1],
+    [ 1, -1,  0,  0,  0],
+    [ 2,  0,  2,  0,  1],
+    [ 0,  1,  0, -2,  0],
+    [ 1,  0, -2,  0,  0],
+    [ 0,  0,  0,  1,  0],
+    [ 1,  1,  0,  0,  0],
+    [ 1,  0,  2,  0,  0],
+    [ 1, -1,  2,  0,  2],
+    [-1, -1,  2,  2,  2],
+    [-2,  0,  0,  0,  1],
+    [ 3,  0,  2,  0,  2],
+    [ 0, -1,  2,  2,  2],
+    [ 1,  1,  2,  0,  2],
+    [-1,  0,  2, -2,  1],
+    [ 2,  0,  0,  0,  1],
+    [ 1,  0,  0,  0,  2],
+    [ 3,  0,  0,  0,  0],
+    [ 0,  0,  2,  1,  2],
+    [-1,  0,  0,  0,  2],
+    [ 1,  0,  0, -4,  0],
+    [-2,  0,  2,  2,  2],
+    [-1,  0,  2,  4,  2],
+    [ 2,  0,  0, -4,  0],
+    [ 1,  1,  2, -2,  2],
+    [ 1,  0,  2,  2,  1],
+    [-2,  0,  2,  4,  2],
+    [-1,  0,  4,  0,  2],
+    [ 1, -1,  0, -2,  0],
+    [ 2,  0,  2, -2,  1],
+    [ 2,  0,  2,  2,  2],
+    [ 1,  0,  0,  2,  1],
+    [ 0,  0,  4, -2,  2],
+    [ 3,  0,  2, -2,  2],
+    [ 1,  0,  2, -2,  0],
+    [ 0,  1,  2,  0,  1],
+    [-1, -1,  0,  2,  1],
+    [ 0,  0, -2,  0,  1],
+    [ 0,  0,  2, -1,  2],
+    [ 0,  1,  0,  2,  0],
+    [ 1,  0, -2, -2,  0],
+    [ 0, -1,  2,  0,  1],
+    [ 1,  1,  0, -2,  1],
+    [ 1,  0, -2,  2,  0],
+    [ 2,  0,  0,  2,  0],
+    [ 0,  0,  2,  4,  2],
+    [ 0,  1,  0,  1,  0],
+];
+
+const CLS80 : &[[R;4]] = &[
+    [-171996.0, -174.2, 92025.0,  8.9],
+    [   2062.0,    0.2,  -895.0,  0.5],
+    [     46.0,    0.0,   -24.0,  0.0],
+    [     11.0,    0.0,     0.0,  0.0],
+    [     -3.0,    0.0,     1.0,  0.0],
+    [     -3.0,    0.0,     0.0,  0.0],
+    [     -2.0,    0.0,     1.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [ -13187.0,   -1.6,  5736.0, -3.1],
+    [   1426.0,   -3.4,    54.0, -0.1],
+    [   -517.0,    1.2,   224.0, -0.6],
+    [    217.0,   -0.5,   -95.0,  0.3],
+    [    129.0,    0.1,   -70.0,  0.0],
+    [     48.0,    0.0,     1.0,  0.0],
+    [    -22.0,    0.0,     0.0,  0.0],
+    [     17.0,   -0.1,     0.0,  0.0],
+    [    -15.0,    0.0,     9.0,  0.0],
+    [    -16.0,    0.1,     7.0,  0.0],
+    [    -12.0,    0.0,     6.0,  0.0],
+    [     -6.0,    0.0,     3.0,  0.0],
+    [     -5.0,    0.0,     3.0,  0.0],
+    [      4.0,    0.0,    -2.0,  0.0],
+    [      4.0,    0.0,    -2.0,  0.0],
+    [     -4.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [  -2274.0,   -0.2,   977.0, -0.5],
+    [    712.0,    0.1,    -7.0,  0.0],
+    [   -386.0,   -0.4,   200.0,  0.0],
+    [   -301.0,    0.0,   129.0, -0.1],
+    [   -158.0,    0.0,    -1.0,  0.0],
+    [    123.0,    0.0,   -53.0,  0.0],
+    [     63.0,    0.0,    -2.0,  0.0],
+    [     63.0,    0.1,   -33.0,  0.0],
+    [    -58.0,   -0.1,    32.0,  0.0],
+    [    -59.0,    0.0,    26.0,  0.0],
+    [    -51.0,    0.0,    27.0,  0.0],
+    [    -38.0,    0.0,    16.0,  0.0],
+    [     29.0,    0.0,    -1.0,  0.0],
+    [     29.0,    0.0,   -12.0,  0.0],
+    [    -31.0,    0.0,    13.0,  0.0],
+    [     26.0,    0.0,    -1.0,  0.0],
+    [     21.0,    0.0,   -10.0,  0.0],
+    [     16.0,    0.0,    -8.0,  0.0],
+    [    -13.0,    0.0,     7.0,  0.0],
+    [    -10.0,    0.0,     5.0,  0.0],
+    [     -7.0,    0.0,     0.0,  0.0],
+    [      7.0,    0.0,    -3.0,  0.0],
+    [     -7.0,    0.0,     3.0,  0.0],
+    [     -8.0,    0.0,     3.0,  0.0],
+    [      6.0,    0.0,     0.0,  0.0],
+    [      6.0,    0.0,    -3.0,  0.0],
+    [     -6.0,    0.0,     3.0,  0.0],
+    [     -7.0,    0.0,     3.0,  0.0],
+    [      6.0,    0.0,    -3.0,  0.0],
+    [     -5.0,    0.0,     3.0,  0.0],
+    [      5.0,    0.0,     0.0,  0.0],
+    [     -5.0,    0.0,     3.0,  0.0],
+    [     -4.0,    0.0,     0.0,  0.0],
+    [      4.0,    0.0,     0.0,  0.0],
+    [     -4.0,    0.0,     0.0,  0.0],
+    [     -3.0,    0.0,     0.0,  0.0],
+    [      3.0,    0.0,     0.0,  0.0],
+    [     -3.0,    0.0,     1.0,  0.0],
+    [     -3.0,    0.0,     1.0,  0.0],
+    [     -2.0,    0.0,     1.0,  0.0],
+    [     -3.0,    0.0,     1.0,  0.0],
+    [     -3.0,    0.0,     1.0,  0.0],
+    [      2.0,    0.0,    -1.0,  0.0],
+    [     -2.0,    0.0,     1.0,  0.0],
+    [      2.0,    0.0,    -1.0,  0.0],
+    [     -2.0,    0.0,     1.0,  0.0],
+    [      2.0,    0.0,     0.0,  0.0],
+    [      2.0,    0.0,    -1.0,  0.0],
+    [      1.0,    0.0,    -1.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,    -1.0,  0.0],
+    [     -2.0,    0.0,     1.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,    -1.0,  0.0],
+    [     -1.0,    0.0,     1.0,  0.0],
+    [     -1.0,    0.0,     1.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,    -1.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+    [     -1.0,    0.0,     0.0,  0.0],
+    [      1.0,    0.0,     0.0,  0.0],
+];
+
+/// Units of CLS80 [rad]
+const U2R80 : R = AS2R/1e4;
+
+/// Fundamental arguments of the IAU 1980 model: polynomial [arcsec]
+/// and whole turns per century of
+const ARGS_80 : [([R;4],R);5] = [
+    // the mean anomaly of the Moon,
+    ([485866.733,715922.633,31.310,0.064],1325.0),
+    // the mean anomaly of the Sun,
+    ([1287099.804,1292581.224,-0.577,-0.012],99.0),
+    // the mean argument of the latitude of the Moon,
+    ([335778.877,295263.137,-13.257,0.011],1342.0),
+    // the mean elongation of the Moon from the Sun,
+    ([1072261.307,1105601.328,-6.891,0.019],1236.0),
+    // the longitude of the mean ascending node of the lunar orbit on
+    // the ecliptic, measured from the mean equinox of date.
+    ([450160.280,-482890.539,7.455,0.008],-5.0)
+];
+
+fn argument_80((c,turns):([R;4],R),t:R)->R {
+    anpm(polynomial(&c,t) * AS2R + ( ( turns * t ) % 1.0 ) * TWO_PI)
+}
+
+/// Polynomial [rad] of argument_80()
+fn argument_80_polynomial((c,turns):([R;4],R))->[R;4] {
+    let mut p = c.map(|ck| ck * AS2R);
+    p[1] += turns * TWO_PI;
+    p
+}
+
+/// Mean longitude of the Moon's ascending node, IAU 1980 model.
+fn node_80(t:R)->R {
+    argument_80(ARGS_80[4],t)
+}
+
+/// Nutation, IAU 1980 model.
+///
+/// Source: nut80.for
+pub fn nutation_80(TT((date1,date2)):TT)->(R,R) {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+
+    let [el,elp,f,d,om] = ARGS_80.map(|a| argument_80(a,t));
+
+    let mut dp = 0.0;
+    let mut de = 0.0;
+
+    for j in (0..NALS80.len()).rev() {
+	let arg =
+	    NALS80[j][0] as R * el  +
+	    NALS80[j][1] as R * elp +
+	    NALS80[j][2] as R * f   +
+	    NALS80[j][3] as R * d   +
+	    NALS80[j][4] as R * om;
+
+	let s = CLS80[j][0] + CLS80[j][1] * t;
+	let c = CLS80[j][2] + CLS80[j][3] * t;
+	if s != 0.0 {
+	    dp += s * sin(arg);
+	}
+	if c != 0.0 {
+	    de += c * cos(arg);
+	}
+    }
+
+    (dp * U2R80,de * U2R80)
+}
+
+/// Majorants of nutation_80() for |t| <= TMAX centuries
+fn nutation_80_majorants(tmax:R)->(Majorant,Majorant) {
+    let args = ARGS_80.map(argument_80_polynomial);
+    let mut dp = Majorant::default();
+    let mut de = Majorant::default();
+    for (n,c) in NALS80.iter().zip(CLS80) {
+	let arg = Majorant::polynomial(&majorant::combination(n.map(R::from),&args),tmax);
+	dp = dp + Majorant::polynomial(&c[0..2],tmax) * arg.sin();
+	de = de + Majorant::polynomial(&c[2..4],tmax) * arg.cos();
+    }
+    (dp.scale(U2R80),de.scale(U2R80))
+}
+
+/// Form the matrix of nutation for a given date, IAU 1980 model.
+///
+/// Source: nutm80.for
+pub fn nutation_matrix_80(tt:TT)->Mat3 {
+    let (dpsi,deps) = nutation_80(tt); // nut80
+    let epsa = mean_obliquity(tt); // obl80
+    nutation_matrix(epsa,dpsi,deps) // numat
+}
+
+/// IAU 1976 precession model: the three Euler angles (ZETA,Z,THETA)
+/// which implement general precession between two epochs.
+///
+/// Source: prec76.for
+pub fn precession_angles_76(TT((date01,date02)):TT,TT((date11,date12)):TT)->(R,R,R) {
+    // Interval between fundamental epoch J2000.0 and start epoch (JC).
+    let t0 = ( ( date01 - DJ00 ) + date02 ) / DJC;
+
+    // Interval over which precession required (JC).
+    let t = ( ( date11 - date01 ) + ( date12 - date02 ) ) / DJC;
+
+    let tas2r = t * AS2R;
+    let [zeta,z,theta] = precession_coefficients_76(t0).map(|c| ( c[0] + ( c[1] + c[2] * t ) * t ) * tas2r);
+    (zeta,z,theta)
+}
+
+/// Coefficients [arcsec] of ZETA/t, Z/t and THETA/t of
+/// precession_angles_76(), quadratic in t, for a start epoch T0
+/// centuries from J2000.0
+fn precession_coefficients_76(t0:R)->[[R;3];3] {
+    let w = 2306.2181 + ( 1.39656 - 0.000139 * t0 ) * t0;
+    [[w,0.30188 - 0.000344 * t0,0.017998],
+     [w,1.09468 + 0.000066 * t0,0.018203],
+     [2004.3109 + ( -0.85330 - 0.000217 * t0 ) * t0,-0.42665 - 0.000217 * t0,-0.041833]]
+}
+
+/// Precession matrix from J2000.0 to a specified date, IAU 1976 model.
+///
+/// Source: pmat76.for
+pub fn precession_76(tt:TT)->Mat3 {
+    let (zeta,z,theta) = precession_angles_76(TT((DJ00,0.0)),tt); // prec76
+    Mat3::identity()
+	.compose(&Mat3::rotation(2,-z))
+	.compose(&Mat3::rotation(1,theta))
+	.compose(&Mat3::rotation(2,-zeta))
+}
+
+/// Form the matrix of precession/nutation for a given date, IAU 1976
+/// precession model, IAU 1980 nutation model.
+///
+/// Source: pnm80.for
+pub(crate) fn precession_nutation_80(tt:TT)->Mat3 {
+    let rmatp = precession_76(tt); // pmat76
+    let rmatn = nutation_matrix_80(tt); // nutm80
+    rmatn.compose(&rmatp)
+}
+
+/// The IAU 1976/1980 precession-nutation matrix from the GCRS, i.e.
+/// applied after the IAU 2000 frame bias.
+fn gcrs_precession_nutation_80(tt:TT)->Mat3 {
+    precession_nutation_80(tt).compose(&frame_bias_matrix())
+}
+
+/// Majorant of gcrs_precession_nutation_80() for |t| <= TMAX
+/// centuries
+fn gcrs_precession_nutation_80_majorant(tmax:R)->MajorantMatrix {
+    let [zeta,z,theta] = precession_coefficients_76(0.0)
+	.map(|c| Majorant::polynomial(&[0.0,c[0],c[1],c[2]],tmax).scale(AS2R));
+    let rp =
+	MajorantMatrix::rotation(2,&z)
+	.compose(&MajorantMatrix::rotation(1,&theta))
+	.compose(&MajorantMatrix::rotation(2,&zeta));
+    let (dpsi,deps) = nutation_80_majorants(tmax);
+    let epsa = Majorant::polynomial(&OBL80,tmax).scale(AS2R);
+    nutation_matrix_majorant(&epsa,&dpsi,&deps)
+	.compose(&rp)
+	.compose(&MajorantMatrix::constant(&frame_bias_matrix()))
+}
+
+/// Complementary terms of eqeq94 [arcsec]: coefficients of sin(Ω)
+/// and sin(2Ω)
+const CT94 : [R;2] = [0.00264,0.000063];
+
+/// Equation of the equinoxes, IAU 1994 model.
+///
+/// Source: eqeq94.for
+pub fn equation_of_equinoxes_94(tt@TT((date1,date2)):TT)->R {
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+    let om = node_80(t);
+    let (dpsi,_deps) = nutation_80(tt); // nut80
+    let eps0 = mean_obliquity(tt); // obl80
+    dpsi*cos(eps0) + AS2R*( CT94[0]*sin(om) + CT94[1]*sin(om+om) )
+}
+
+/// Majorant of equation_of_equinoxes_94() for |t| <= TMAX centuries
+fn equation_of_equinoxes_94_majorant(tmax:R)->Majorant {
+    let om = Majorant::polynomial(&argument_80_polynomial(ARGS_80[4]),tmax);
+    let (dpsi,_deps) = nutation_80_majorants(tmax);
+    let eps0 = Majorant::polynomial(&OBL80,tmax).scale(AS2R);
+    dpsi * eps0.cos() + ( om.sin().scale(CT94[0]) + om.scale(2.0).sin().scale(CT94[1]) ).scale(AS2R)
+}
+
+/// Form the celestial to terrestrial matrix given the date, the UT1
+/// and the polar motion, using the equinox-based IAU 1976/1980/1994
+/// chain: precession, nutation, apparent sidereal time and polar
+/// motion.
+pub(crate) fn celestial_to_terrestrial_80(tt:TT,ut1:UT1,xp:R,yp:R)->Mat3 {
+    let rnpb = precession_nutation_80(tt); // pnm80
+    let gst = anp(earth::mean_sidereal_time_82(ut1) + equation_of_equinoxes_94(tt));
+    let rpom = earth::polar_motion_matrix(xp,yp,0.0);
+    rpom.compose(&Mat3::rotation(2,gst).compose(&rnpb))
+}
+
+/// Rotation from the GCRS to the CIRS, IAU 2000B model.
+pub fn gcrs_to_cirs(tt:TT)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate(tt),tt)
+}
+
+/// Rotation from the GCRS to the CIRS, IAU 2000A model.
+pub fn gcrs_to_cirs_00a(tt:TT)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate_00a(tt),tt) // c2i00a
+}
+
+/// Rotation from the GCRS to the CIRS, IAU 2006/2000A model.
+pub fn gcrs_to_cirs_06(tt:TT)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate_06(tt),tt) // c2i06a
+}
+
+/// Rotation from the GCRS to the CIRS, given the rotation from the
+/// GCRS to the true equator and equinox of date.
+pub fn gcrs_to_cirs_from_tod(rbpn:&Rotation<Gcrs,Tod>)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate_with_bpn(rbpn.epoch,&rbpn.matrix),rbpn.epoch) // c2ibpn
+}
+
+/// Rotation from the GCRS to the CIRS, given the CIP coordinates X,Y;
+/// the CIO locator is s00.
+pub fn gcrs_to_cirs_from_xy(tt:TT,x:R,y:R)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate_with_bpn_and_xy(tt,x,y),tt) // c2ixy
+}
+
+/// Rotation from the GCRS to the CIRS, given the CIP coordinates X,Y
+/// and the CIO locator s.
+pub fn gcrs_to_cirs_from_xys(tt:TT,x:R,y:R,s:R)->Rotation<Gcrs,Cirs> {
+    Rotation::new(celestial_to_intermediate_from_xys(x,y,s),tt) // c2ixys
+}
+
+/// Rotation from the CIRS to the TIRS: the Earth rotation angle.
+pub fn cirs_to_tirs(tt:TT,ut1:UT1)->Rotation<Cirs,Tirs> {
+    Rotation::new(Mat3::rotation(2,earth::rotation_angle(ut1)),tt)
+}
+
+/// Rotation from the TIRS to the ITRS: polar motion, including the
+/// TIO locator s'.
+pub fn tirs_to_itrs(tt:TT,xp:R,yp:R)->Rotation<Tirs,Itrs> {
+    let sp = earth::tio_locator(tt); // sp00
+    Rotation::new(earth::polar_motion_matrix(xp,yp,sp),tt)
+}
+
+/// Rotation from the GCRS to the ITRS, IAU 2000B model.
+pub fn gcrs_to_itrs(tt:TT,ut1:UT1,xp:R,yp:R)->Rotation<Gcrs,Itrs> {
+    Rotation::new(celestial_to_terrestrial(tt,ut1,xp,yp),tt)
+}
+
+/// Rotation from the GCRS to the ITRS, IAU 2000A model.
+pub fn gcrs_to_itrs_00a(tt:TT,ut1:UT1,xp:R,yp:R)->Rotation<Gcrs,Itrs> {
+    Rotation::new(celestial_to_terrestrial_00a(tt,ut1,xp,yp),tt) // c2t00a
+}
+
+/// Rotation from the GCRS to the ITRS, IAU 2006/2000A model.
+pub fn gcrs_to_itrs_06(tt:TT,ut1:UT1,xp:R,yp:R)->Rotation<Gcrs,Itrs> {
+    Rotation::new(celestial_to_terrestrial_06(tt,ut1,xp,yp),tt) // c2t06a
+}
+
+/// Rotation from the GCRS to the ITRS, IAU 1976/1980 model with
+/// GAST94, after the frame bias (see PrecessionNutationModel::Iau1980).
+pub fn gcrs_to_itrs_80(tt:TT,ut1:UT1,xp:R,yp:R)->Rotation<Gcrs,Itrs> {
+    Rotation::new(celestial_to_terrestrial_80(tt,ut1,xp,yp).compose(&frame_bias_matrix()),tt)
+}
+
+/// Rotation from the GCRS to the ITRS given its CIO-based stages.
+/// Fails if their epochs differ.
+pub fn gcrs_to_itrs_from_cio_components(rc2i:&Rotation<Gcrs,Cirs>,era:R,
+					rpom:&Rotation<Tirs,Itrs>)
+					->Result<Rotation<Gcrs,Itrs>,FrameError> {
+    let rera = Rotation::<Cirs,Tirs>::new(Mat3::rotation(2,era),rc2i.epoch);
+    rc2i.then(&rera)?.then(rpom) // c2tcio
+}
+
+/// Rotation from the GCRS to the true equator and equinox of date,
+/// IAU 2000B model.
+pub fn gcrs_to_tod(tt:TT)->Rotation<Gcrs,Tod> {
+    Rotation::new(precession_nutation(tt),tt) // pnm00b
+}
+
+/// Rotation from the GCRS to the true equator and equinox of date,
+/// IAU 2000A model.
+pub fn gcrs_to_tod_00a(tt:TT)->Rotation<Gcrs,Tod> {
+    Rotation::new(precession_nutation_00a(tt),tt) // pnm00a
+}
+
+/// Rotation from the GCRS to the true equator and equinox of date,
+/// IAU 2006/2000A model.
+pub fn gcrs_to_tod_06(tt:TT)->Rotation<Gcrs,Tod> {
+    Rotation::new(precession_nutation_06(tt),tt) // pnm06a
+}
+
+/// Rotation from the GCRS to the true equator and equinox of date,
+/// IAU 1976/1980 model, after the frame bias.
+pub fn gcrs_to_tod_80(tt:TT)->Rotation<Gcrs,Tod> {
+    Rotation::new(gcrs_precession_nutation_80(tt),tt)
+}
+
+/// Equation of the origins, given the classical NPB matrix and the
+/// quantity s.
+///
+/// Source: eors.for
+pub fn equation_of_origins(rnpb:&Mat3,s:R)->R {
+    let (p,q) = equation_of_origins_terms(rnpb);
+    if p != 0.0 || q != 0.0 {
+	s - atan2(q,p)
+    } else {
+	s
+    }
+}
+
+/// The terms P and Q of expression (16) of Wallace & Capitaine
+/// (2006), atan2(Q,P) being s less the equation of the origins (as in
+/// eors)
+fn equation_of_origins_terms(rnpb:&Mat3)->(R,R) {
+    let x = rnpb[2][0];
+    let ax = x / (1.0 + rnpb[2][2]);
+    let xs = 1.0 - ax*x;
+    let ys = -ax*rnpb[2][1];
+    let zs = -x;
+    let p = rnpb[0][0]*xs + rnpb[0][1]*ys + rnpb[0][2]*zs;
+    let q = rnpb[1][0]*xs + rnpb[1][1]*ys + rnpb[1][2]*zs;
+    (p,q)
+}
+
+/// Majorant of equation_of_origins() given those of the matrix and
+/// of s.  RNPB is the
+/// matrix at a point within H of all others.  The bound is infinite
+/// unless P of equation_of_origins_terms() stays positive.
+fn equation_of_origins_majorant(m:&MajorantMatrix,rnpb:&Mat3,h:R,s:&Majorant)->Majorant {
+    let m = &m.0;
+    let x = m[2][0];
+
+    // 1 + rnpb[2][2] is at least 1 + sqrt(1 - X^2 - Y^2)
+    let r2 = sq(m[2][0].0[0]) + sq(m[2][1].0[0]);
+    let ax = x * m[2][2].recip(1.0 + sqrt((1.0 - r2).max(0.0)));
+    let xs = Majorant::constant(1.0) + ax * x;
+    let ys = ax * m[2][1];
+    let zs = x;
+    let p = m[0][0] * xs + m[0][1] * ys + m[0][2] * zs;
+    let q = m[1][0] * xs + m[1][1] * ys + m[1][2] * zs;
+
+    // atan2(q,p) = atan(q/p) for p > 0
+    let (p0,q0) = equation_of_origins_terms(rnpb);
+    let v = (q * p.recip(p0 - p.0[1] * h)).with_value(q0 / p0,h);
+    *s + v.atan()
 }
@@ -1,69 +1,127 @@
 use crate::common::*;
 
+// Polynomials in t of the fundamental arguments, constant term first:
+// those of the Delaunay arguments in arcseconds, those of the mean
+// longitudes of the planets and of the general precession in radians.
+
+const L03 : [R;5] = [485868.249036,1717915923.2178,31.8792,0.051635,-0.00024470];
+const LP03 : [R;5] = [1287104.793048,129596581.0481,-0.5532,0.000136,-0.00001149];
+const F03 : [R;5] = [335779.526232,1739527262.8478,-12.7512,-0.001037,0.00000417];
+const D03 : [R;5] = [1072260.703692,1602961601.2090,-6.3706,0.006593,-0.00003169];
+const OM03 : [R;5] = [450160.398036,-6962890.5431,7.4722,0.007702,-0.00005939];
+const ME03 : [R;2] = [4.402608842,2608.7903141574];
+const VE03 : [R;2] = [3.176146697,1021.3285546211];
+const E03 : [R;2] = [1.753470314,628.3075849991];
+const MA03 : [R;2] = [6.203480913,334.0612426700];
+const JU03 : [R;2] = [0.599546497,52.9690962641];
+const SA03 : [R;2] = [0.874016757,21.3299104960];
+const UR03 : [R;2] = [5.481293872,7.4781598567];
+const NE03 : [R;2] = [5.311886287,3.8133035638];
+const PA03 : [R;3] = [0.0,0.024381750,0.00000538691];
+
 /// Mean anomaly of the Moon (IERS Conventions 2003).
 /// Source: fal03.for
 pub fn l03(t:R)->R {
-    (485868.249036 +
-     t*(1717915923.2178 +
-	t*(31.8792 +
-	   t*(0.051635 +
-	      t*(-0.00024470)))) % TURNAS) * AS2R
+    (polynomial(&L03,t) % TURNAS) * AS2R
 }
 
 /// Mean anomaly of the Sun (IERS Conventions 2003).
 /// Source: falp03.for
 pub fn lp03(t:R)->R {
-    (1287104.793048 +
-      t*(129596581.0481 +
-	  t*(- 0.5532 +
-		t*(0.000136 +
-		    t*(-0.00001149)))) % TURNAS) * AS2R
+    (polynomial(&LP03,t) % TURNAS) * AS2R
 }
 
 /// Mean longitude of the Moon minus mean longitude of the ascending
 /// node.
 /// Source: faf03.for
 pub fn f03(t:R)->R {
-    (335779.526232 +
-     t*(1739527262.8478 +
-	t*(-12.7512 +
-	   t*(-0.001037 +
-	      t*(0.00000417 )))) % TURNAS) * AS2R
+    (polynomial(&F03,t) % TURNAS) * AS2R
 }
 
 /// Mean elongation of the Moon from the Sun.
 /// Source: fad03.for
 pub fn d03(t:R)->R {
-    (1072260.703692 +
-     t*(1602961601.2090 +
-	t*(-6.3706 +
-	   t*(0.006593 +
-	      t*(-0.00003169)))) % TURNAS) * AS2R
+    (polynomial(&D03,t) % TURNAS) * AS2R
 }
 
 /// Mean longitude of the Moon's ascending node.
 /// Source: faom03.for
 pub fn om03(t:R)->R {
-    (450160.398036 +
-     t*(-6962890.5431 +
-	t*(7.4722 +
-	   t*(0.007702 +
-	      t*(-0.00005939 )))) % TURNAS) * AS2R
+    (polynomial(&OM03,t) % TURNAS) * AS2R
+}
+
+/// Mean longitude of Mercury.
+/// Source: fame03.for
+pub fn me03(t:R)->R {
+    polynomial(&ME03,t) % TWO_PI
 }
 
 /// Mean longitude of Venus.
 /// Source: fave03.for
 pub fn ve03(t:R)->R {
-    (3.176146697 + 1021.3285546211 * t) % TWO_PI
+    polynomial(&VE03,t) % TWO_PI
 }
 
 /// Mean longitude of Earth.
 /// Source: fae03.for
 pub fn e03(t:R)->R {
-    (1.753470314 + 628.3075849991 * t) % TWO_PI
+    polynomial(&E03,t) % TWO_PI
+}
+
+/// Mean longitude of Mars.
+/// Source: fama03.for
+pub fn ma03(t:R)->R {
+    polynomial(&MA03,t) % TWO_PI
+}
+
+/// Mean longitude of Jupiter.
+/// Source: faju03.for
+pub fn ju03(t:R)->R {
+    polynomial(&JU03,t) % TWO_PI
+}
+
+/// Mean longitude of Saturn.
+/// Source: fasa03.for
+pub fn sa03(t:R)->R {
+    polynomial(&SA03,t) % TWO_PI
+}
+
+/// Mean longitude of Uranus.
+/// Source: faur03.for
+pub fn ur03(t:R)->R {
+    polynomial(&UR03,t) % TWO_PI
+}
+
+/// Mean longitude of Neptune.
+/// Source: fane03.for
+pub fn ne03(t:R)->R {
+    polynomial(&NE03,t) % TWO_PI
 }
 
 /// General accumulated precession in longitude.
 pub fn pa03(t:R)->R {
-    (0.024381750 + 0.00000538691 * t) * t
+    polynomial(&PA03,t)
+}
+
+/// Polynomials [rad] of the 14 fundamental arguments, in the order l,
+/// l', F, D, Omega, Mercury to Neptune and the general precession.
+pub(crate) fn polynomials_03()->[[R;5];14] {
+    let delaunay = |c:&[R;5]| c.map(|ck| ck * AS2R);
+    let linear = |c:&[R;2]| [c[0],c[1],0.0,0.0,0.0];
+    [
+	delaunay(&L03),
+	delaunay(&LP03),
+	delaunay(&F03),
+	delaunay(&D03),
+	delaunay(&OM03),
+	linear(&ME03),
+	linear(&VE03),
+	linear(&E03),
+	linear(&MA03),
+	linear(&JU03),
+	linear(&SA03),
+	linear(&UR03),
+	linear(&NE03),
+	[PA03[0],PA03[1],PA03[2],0.0,0.0]
+    ]
 }
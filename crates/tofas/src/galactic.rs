@@ -0,0 +1,39 @@
+// Galactic coordinates, using the ICRS to galactic rotation matrix
+// derived from the FK5 definition of the galactic frame (Hipparcos
+// catalogue, Vol. 1, Section 1.5.3).
+
+use crate::common::*;
+
+/// ICRS to galactic rotation matrix
+#[allow(clippy::excessive_precision)]
+pub const ICRS_TO_GALACTIC : Mat3 = [
+    [-0.054875560416215368492398900454,
+     -0.873437090234885048760383168409,
+     -0.483835015548713226831774175116],
+    [ 0.494109427875583673525222371358,
+     -0.444829629960011178146614061616,
+      0.746982244497218890527388004556],
+    [-0.867666149019004701181616534570,
+     -0.198076373431201528180486091412,
+      0.455983776175066922272100478348]
+];
+
+/// Transformation from ICRS to Galactic Coordinates.  Returns the
+/// galactic longitude and latitude (DL,DB).
+///
+/// Source: icrs2g.for
+pub fn icrs_to_galactic(dr:R,dd:R)->(R,R) {
+    let v = ICRS_TO_GALACTIC.mul_vec(spherical_to_cartesian(dr,dd)); // s2c, rxp
+    let (dl,db) = cartesian_to_spherical(v); // c2s
+    (anp(dl),anpm(db))
+}
+
+/// Transformation from Galactic Coordinates to ICRS.  Returns the
+/// ICRS right ascension and declination (DR,DD).
+///
+/// Source: g2icrs.for
+pub fn galactic_to_icrs(dl:R,db:R)->(R,R) {
+    let v = ICRS_TO_GALACTIC.transpose().mul_vec(spherical_to_cartesian(dl,db)); // s2c, trxp
+    let (dr,dd) = cartesian_to_spherical(v); // c2s
+    (anp(dr),anpm(dd))
+}
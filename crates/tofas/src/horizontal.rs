@@ -0,0 +1,88 @@
+// Local horizontal coordinates for an observer at a geodetic position:
+// conversions between hour angle and declination and azimuth and
+// elevation, the parallactic angle, and the local hour angle from the
+// Earth rotation angle or the Greenwich apparent sidereal time.
+// Azimuths are counted from North through East.  Polar motion and the
+// deflection of the vertical are neglected: the latitude is the
+// geodetic one.
+
+use crate::{
+    common::*,
+    time::{TT,UT1},
+    earth,
+    ellipsoid::Geodetic,
+    frames::PrecessionNutationModel
+};
+
+/// Equatorial to horizon coordinates: transform hour angle and
+/// declination to azimuth (N=0,E=90, range 0-2π) and elevation for
+/// the observer SITE.
+///
+/// Source: hd2ae.for
+pub fn hour_angle_dec_to_az_el(ha:R,dec:R,site:&Geodetic)->(R,R) {
+    // Useful trig functions.
+    let (sh,ch) = (sin(ha),cos(ha));
+    let (sd,cd) = (sin(dec),cos(dec));
+    let (sp,cp) = (sin(site.phi),cos(site.phi));
+
+    // Az,Alt unit vector.
+    let x = - ch*cd*sp + sd*cp;
+    let y = - sh*cd;
+    let z = ch*cd*cp + sd*sp;
+
+    // To spherical.
+    let r = sqrt(x*x + y*y);
+    let a = if r != 0.0 { atan2(y,x) } else { 0.0 };
+    let az = if a < 0.0 { a + TWO_PI } else { a };
+    let el = atan2(z,r);
+    (az,el)
+}
+
+/// Horizon to equatorial coordinates: transform azimuth (N=0,E=90)
+/// and elevation to hour angle (range ±π) and declination for the
+/// observer SITE.
+///
+/// Source: ae2hd.for
+pub fn az_el_to_hour_angle_dec(az:R,el:R,site:&Geodetic)->(R,R) {
+    // Useful trig functions.
+    let (sa,ca) = (sin(az),cos(az));
+    let (se,ce) = (sin(el),cos(el));
+    let (sp,cp) = (sin(site.phi),cos(site.phi));
+
+    // HA,Dec unit vector.
+    let x = - ca*ce*sp + se*cp;
+    let y = - sa*ce;
+    let z = ca*ce*cp + se*sp;
+
+    // To spherical.
+    let r = sqrt(x*x + y*y);
+    let ha = if r != 0.0 { atan2(y,x) } else { 0.0 };
+    let dec = atan2(z,r);
+    (ha,dec)
+}
+
+/// Parallactic angle for a given hour angle and declination, i.e. the
+/// angle between the direction to the North celestial pole and the
+/// zenith, positive when the object is West of the meridian.
+///
+/// Source: hd2pa.for
+pub fn parallactic_angle(ha:R,dec:R,site:&Geodetic)->R {
+    let cp = cos(site.phi);
+    let sqsz = cp*sin(ha);
+    let cqsz = sin(site.phi)*cos(dec) - cp*sin(dec)*cos(ha);
+    if sqsz != 0.0 || cqsz != 0.0 { atan2(sqsz,cqsz) } else { 0.0 }
+}
+
+/// Local hour angle (range ±π) of a CIO-based right ascension RA
+/// (i.e. with respect to the CIRS), from the Earth rotation angle.
+pub fn local_hour_angle_era(ut1:UT1,ra:R,site:&Geodetic)->R {
+    anpm(earth::rotation_angle(ut1) + site.elong - ra) // era00
+}
+
+/// Local hour angle (range ±π) of an equinox-based (true of date)
+/// right ascension RA, from the Greenwich apparent sidereal time of
+/// the model.
+pub fn local_hour_angle_gast(model:PrecessionNutationModel,tt:TT,ut1:UT1,
+			     ra:R,site:&Geodetic)->R {
+    anpm(model.apparent_sidereal_time(tt,ut1) + site.elong - ra)
+}
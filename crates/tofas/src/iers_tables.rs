@@ -0,0 +1,350 @@
+// Series read from the tables of the IERS Conventions (2010),
+// Chapter 5, such as tab5.3a.txt.  Each table lists, for successive
+// powers j of the time in Julian centuries since J2000.0, the
+// coefficients in microarcseconds of the sine and cosine of a linear
+// combination of 14 fundamental arguments: l, l', F, D, Omega, the
+// mean longitudes of Mercury to Neptune and the general precession in
+// longitude.  These series run to thousands of terms and are read at
+// run time rather than embedded in the crate.  The CIP coordinates X
+// and Y (Tables 5.2a and 5.2b) add a polynomial part.
+//
+// The IAU 2000A nutation of Tables 5.3a and 5.3b includes the IAU 2006
+// adjustments, so that its sum is the nutation of nut06a; that of
+// nut00a is obtained by removing the adjustments.
+
+use std::{
+    fs::File,
+    io::{BufRead,BufReader},
+    path::Path,
+    sync::OnceLock
+};
+
+use crate::{
+    common::*,
+    time::{TT,DJ00,DJC},
+    fundargs,
+    frames,
+    majorant::{self,Majorant}
+};
+
+custom_error!{pub SeriesError
+	      Io{source:std::io::Error} = "I/O error: {source}",
+	      MissingPower{line:usize}  = "term before any \"j = \" line at line {line}",
+	      Empty                     = "no terms in the table"
+}
+
+/// Microarcseconds to radians
+const UAS2R : R = AS2R / 1e6;
+
+/// Number of fundamental arguments
+const NARGS : usize = 14;
+
+/// Term of a series
+#[derive(Clone,Debug)]
+struct Term {
+    /// Coefficients of the sine and cosine of the argument [rad]
+    s:R,
+    c:R,
+
+    /// Multipliers of the fundamental arguments
+    n:[i32;NARGS]
+}
+
+impl Term {
+    /// Whether the argument involves the planets or the precession
+    fn is_planetary(&self)->bool {
+	self.n[5..].iter().any(|&k| k != 0)
+    }
+}
+
+/// Poisson series read from an IERS table: the terms multiplying each
+/// power of t.
+#[derive(Clone,Debug,Default)]
+pub struct PoissonSeries {
+    powers:Vec<Vec<Term>>
+}
+
+impl PoissonSeries {
+    /// Read a table in the format of the IERS Conventions.  A line
+    /// "j = N ..." starts the terms of power N; a term line holds its
+    /// index, the sine and cosine coefficients [µas] and the 14
+    /// multipliers.  Other lines are headers and are ignored.
+    pub fn from_reader(reader:impl BufRead)->Result<Self,SeriesError> {
+	let mut powers : Vec<Vec<Term>> = Vec::new();
+	let mut power = None;
+	for (i,line) in reader.lines().enumerate() {
+	    let line = line?;
+	    if let Some(j) = parse_power(&line) {
+		if powers.len() <= j {
+		    powers.resize(j + 1,Vec::new());
+		}
+		power = Some(j);
+	    } else if let Some(term) = parse_term(&line) {
+		let j = power.ok_or(SeriesError::MissingPower { line:i + 1 })?;
+		powers[j].push(term);
+	    }
+	}
+	if powers.iter().all(|terms| terms.is_empty()) {
+	    return Err(SeriesError::Empty);
+	}
+	Ok(Self { powers })
+    }
+
+    pub fn open<P:AsRef<Path>>(path:P)->Result<Self,SeriesError> {
+	Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Number of terms for each power of t
+    pub fn counts(&self)->Vec<usize> {
+	self.powers.iter().map(|terms| terms.len()).collect()
+    }
+
+    /// Sum [rad] of the series at T Julian centuries since J2000.0,
+    /// given the fundamental arguments of the luni-solar terms and
+    /// those of the planetary terms.  The terms of each power are
+    /// summed from the smallest, the tables listing them by decreasing
+    /// amplitude.
+    fn sum(&self,t:R,ls:&[R;NARGS],pl:&[R;NARGS])->R {
+	let mut total = 0.0;
+	for terms in self.powers.iter().rev() {
+	    let mut sj = 0.0;
+	    for term in terms.iter().rev() {
+		let args = if term.is_planetary() { pl } else { ls };
+		let arg : R = term.n.iter().zip(args).map(|(&k,a)| k as R * a).sum();
+		sj += term.s * sin(arg) + term.c * cos(arg);
+	    }
+	    total = total * t + sj;
+	}
+	total
+    }
+
+    /// Majorant of the sum for |t| <= TMAX centuries, given the
+    /// polynomials of the fundamental arguments of the luni-solar
+    /// terms and of the planetary terms
+    fn majorant(&self,tmax:R,ls:&[[R;5];NARGS],pl:&[[R;5];NARGS])->Majorant {
+	let mut total = Majorant::default();
+	for (j,terms) in self.powers.iter().enumerate() {
+	    let tj = Majorant::power(j,tmax);
+	    for term in terms {
+		let args = if term.is_planetary() { pl } else { ls };
+		let arg = majorant::combination(term.n.map(R::from),args);
+		let a = Majorant::polynomial(&arg,tmax);
+		total = total + tj * (a.sin().scale(term.s) + a.cos().scale(term.c));
+	    }
+	}
+	total
+    }
+}
+
+/// The power N of a "j = N" line
+fn parse_power(line:&str)->Option<usize> {
+    let rest = line.trim_start().strip_prefix('j')?.trim_start().strip_prefix('=')?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+fn parse_term(line:&str)->Option<Term> {
+    let tokens : Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() != 3 + NARGS {
+	return None;
+    }
+    tokens[0].parse::<usize>().ok()?;
+    let s : R = tokens[1].parse().ok()?;
+    let c : R = tokens[2].parse().ok()?;
+    let mut n = [0;NARGS];
+    for (nk,token) in n.iter_mut().zip(&tokens[3..]) {
+	*nk = token.parse().ok()?;
+    }
+    Some(Term { s:s * UAS2R,c:c * UAS2R,n })
+}
+
+/// The 14 fundamental arguments, IERS Conventions 2003
+fn arguments_03(t:R)->[R;NARGS] {
+    [
+	fundargs::l03(t),
+	fundargs::lp03(t),
+	fundargs::f03(t),
+	fundargs::d03(t),
+	fundargs::om03(t),
+	fundargs::me03(t),
+	fundargs::ve03(t),
+	fundargs::e03(t),
+	fundargs::ma03(t),
+	fundargs::ju03(t),
+	fundargs::sa03(t),
+	fundargs::ur03(t),
+	fundargs::ne03(t),
+	fundargs::pa03(t)
+    ]
+}
+
+/// The fundamental arguments of the planetary nutation terms in the
+/// IAU 2000A model: the luni-solar and Neptune arguments are the
+/// simplified expressions of the MHB2000 model.
+///
+/// Source: nut00a.for
+fn planetary_arguments_00a(t:R)->[R;NARGS] {
+    let mut args = arguments_03(t);
+    for (i,c) in PLANETARY_ARGUMENTS_00A {
+	args[i] = polynomial(&c,t) % TWO_PI;
+    }
+    args
+}
+
+/// Arguments of the planetary terms differing from arguments_03(),
+/// with their linear expressions [rad]
+const PLANETARY_ARGUMENTS_00A : [(usize,[R;2]);5] = [
+    (0,[2.35555598,8328.6914269554]),
+    (2,[1.627905234,8433.466158131]),
+    (3,[5.198466741,7771.3771468121]),
+    (4,[2.18243920,-33.757045]),
+    (12,[5.321159000,3.8127774000])
+];
+
+/// Polynomials of arguments_03() and planetary_arguments_00a()
+fn argument_polynomials_00a()->([[R;5];NARGS],[[R;5];NARGS]) {
+    let ls = fundargs::polynomials_03();
+    let mut pl = ls;
+    for (i,c) in PLANETARY_ARGUMENTS_00A {
+	pl[i] = [c[0],c[1],0.0,0.0,0.0];
+    }
+    (ls,pl)
+}
+
+/// IAU 2000A nutation series, from Tables 5.3a (longitude) and 5.3b
+/// (obliquity) of the IERS Conventions (2010)
+#[derive(Clone,Debug)]
+pub struct Nutation2000A {
+    longitude:PoissonSeries,
+    obliquity:PoissonSeries
+}
+
+impl Nutation2000A {
+    pub fn new(longitude:PoissonSeries,obliquity:PoissonSeries)->Self {
+	Self { longitude,obliquity }
+    }
+
+    /// Read the files tab5.3a.txt and tab5.3b.txt of the directory DIR
+    pub fn open<P:AsRef<Path>>(dir:P)->Result<Self,SeriesError> {
+	let dir = dir.as_ref();
+	Ok(Self::new(PoissonSeries::open(dir.join("tab5.3a.txt"))?,
+		     PoissonSeries::open(dir.join("tab5.3b.txt"))?))
+    }
+
+    /// Nutation, IAU 2000A model with the IAU 2006 adjustments: the
+    /// sums of the tables.  Returns (DPSI,DEPS).
+    ///
+    /// Based on: nut06a.for
+    pub fn nutation_06a(&self,TT((date1,date2)):TT)->(R,R) {
+	let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+	let ls = arguments_03(t);
+	let pl = planetary_arguments_00a(t);
+	(self.longitude.sum(t,&ls,&pl),self.obliquity.sum(t,&ls,&pl))
+    }
+
+    /// Nutation, IAU 2000A model, with the IAU 2006 adjustments
+    /// removed from nutation_06a().  Returns (DPSI,DEPS).
+    ///
+    /// Based on: nut00a.for
+    pub fn nutation_00a(&self,tt:TT)->(R,R) {
+	let (dpsi,deps) = self.nutation_06a(tt);
+	let (fpsi,feps) = frames::nutation_06_factors(tt);
+	(dpsi / fpsi,deps / feps)
+    }
+
+    /// Majorants of the nutation_06a() angles for |t| <= TMAX
+    /// centuries
+    pub(crate) fn majorants_06a(&self,tmax:R)->(Majorant,Majorant) {
+	let (ls,pl) = argument_polynomials_00a();
+	(self.longitude.majorant(tmax,&ls,&pl),self.obliquity.majorant(tmax,&ls,&pl))
+    }
+}
+
+/// Series of the CIP coordinates X and Y, IAU 2006/2000A, from Tables
+/// 5.2a and 5.2b of the IERS Conventions (2010)
+#[derive(Clone,Debug)]
+pub struct CipSeries {
+    x:PoissonSeries,
+    y:PoissonSeries
+}
+
+/// Polynomial parts of X and Y [µas]
+const XPOL : [R;6] = [-16617.0,2004191898.0,-429782.9,-198618.34,7.578,5.9285];
+const YPOL : [R;6] = [-6951.0,-25896.0,-22407274.7,1900.59,1112.526,0.1358];
+
+impl CipSeries {
+    pub fn new(x:PoissonSeries,y:PoissonSeries)->Self {
+	Self { x,y }
+    }
+
+    /// Read the files tab5.2a.txt and tab5.2b.txt of the directory DIR
+    pub fn open<P:AsRef<Path>>(dir:P)->Result<Self,SeriesError> {
+	let dir = dir.as_ref();
+	Ok(Self::new(PoissonSeries::open(dir.join("tab5.2a.txt"))?,
+		     PoissonSeries::open(dir.join("tab5.2b.txt"))?))
+    }
+
+    /// CIP X,Y coordinates for a given date, IAU 2006 precession and
+    /// IAU 2000A nutation, from the series.
+    ///
+    /// Source: xy06.for
+    pub fn xy_06(&self,TT((date1,date2)):TT)->(R,R) {
+	let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+	let args = arguments_03(t);
+	(polynomial(&XPOL,t) * UAS2R + self.x.sum(t,&args,&args),
+	 polynomial(&YPOL,t) * UAS2R + self.y.sum(t,&args,&args))
+    }
+}
+
+/// Environment variable giving the directory of the IERS tables read
+/// on first use of the series
+pub const TABLES_VAR : &str = "TOFAS_IERS_TABLES";
+
+static NUTATION_2000A : OnceLock<Option<Nutation2000A>> = OnceLock::new();
+static CIP_SERIES : OnceLock<Option<CipSeries>> = OnceLock::new();
+
+/// Make the IAU 2000A nutation series available to the functions that
+/// use them, such as frames::nutation_06.  This must be done before
+/// their first use; the series are handed back otherwise.
+pub fn install_nutation_2000a(nutation:Nutation2000A)->Result<(),Nutation2000A> {
+    NUTATION_2000A.set(Some(nutation)).map_err(|n| n.unwrap())
+}
+
+/// Make the CIP X,Y series available to frames::cip_xy_06, before its
+/// first use; the series are handed back otherwise.
+pub fn install_cip_series(series:CipSeries)->Result<(),CipSeries> {
+    CIP_SERIES.set(Some(series)).map_err(|s| s.unwrap())
+}
+
+/// The IAU 2000A nutation series, if installed.  Unless that was done
+/// with install_nutation_2000a(), they are read on the first call from
+/// the directory given by the environment variable TOFAS_IERS_TABLES,
+/// if set.
+///
+/// # Panics
+///
+/// If the variable is set but the tables cannot be read.
+pub fn nutation_2000a()->Option<&'static Nutation2000A> {
+    installed(&NUTATION_2000A,|dir| Nutation2000A::open(dir))
+}
+
+/// The CIP X,Y series, if installed, in the same way as
+/// nutation_2000a().
+///
+/// # Panics
+///
+/// If the variable TOFAS_IERS_TABLES is set but the tables cannot be
+/// read.
+pub fn cip_series()->Option<&'static CipSeries> {
+    installed(&CIP_SERIES,|dir| CipSeries::open(dir))
+}
+
+fn installed<T>(cell:&'static OnceLock<Option<T>>,
+		open:fn(&Path)->Result<T,SeriesError>)->Option<&'static T> {
+    cell.get_or_init(|| {
+	let dir = std::env::var_os(TABLES_VAR)?;
+	match open(Path::new(&dir)) {
+	    Ok(series) => Some(series),
+	    Err(e) => panic!("{TABLES_VAR}={}: {e}",dir.to_string_lossy())
+	}
+    }).as_ref()
+}
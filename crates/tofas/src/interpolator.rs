@@ -0,0 +1,174 @@
+// Fast evaluation of the celestial to terrestrial matrix for
+// high-rate processing.  The slowly varying precession-nutation part
+// (the CIP coordinates X, Y and the CIO locator s) is tabulated on a
+// coarse grid of TT dates and interpolated with four-point Lagrange
+// polynomials, while the Earth rotation angle and the polar motion are
+// computed exactly at each call.  The result follows the CIO-based
+// chain of the model; for the IAU 1980 model the TT-dependent part of
+// the shift of its CIRS origin is tabulated as well.
+//
+// The interpolation errors are bounded by the Lagrange remainder,
+// from majorants of the fourth derivatives of the tabulated
+// quantities over the tabulated range (see majorant.rs).
+
+use crate::{
+    common::*,
+    time::{TT,UT1,DJ00,DJC},
+    earth,
+    frames::{self,PrecessionNutationModel},
+    typed::{Rotation,Gcrs,Itrs}
+};
+
+custom_error!{pub InterpolatorError
+	      BadRange   = "invalid interpolation range or step",
+	      OutOfRange = "date outside of the interpolation range"
+}
+
+/// Allowance [rad] for the rounding errors of the double precision
+/// evaluation of X, Y and s or of a rotation matrix
+const ROUNDING : R = 1e-15;
+
+/// Interpolator for the CIP coordinates and CIO locator of a
+/// precession-nutation model
+#[derive(Clone,Debug)]
+pub struct CipInterpolator {
+    model:PrecessionNutationModel,
+    tt0:TT,
+    step:R,
+    intervals:usize,
+    nodes:Vec<(R,R,R)>,
+    shifts:Vec<R>,
+    bounds:(R,R,R),
+    rotation_bound:R
+}
+
+impl CipInterpolator {
+    /// Tabulate X, Y and s for the given model between the TT dates
+    /// START and END, with nodes every STEP days.
+    ///
+    /// # Panics
+    ///
+    /// For the IAU 2000A model, if its series are not installed.
+    pub fn new(model:PrecessionNutationModel,start:TT,end:TT,step:R)
+	       ->Result<Self,InterpolatorError> {
+	let (TT((s1,s2)),TT((e1,e2))) = (start,end);
+	let span = (e1 - s1) + (e2 - s2);
+	if step.is_nan() || span.is_nan() || step <= 0.0 || span < 0.0 {
+	    return Err(InterpolatorError::BadRange);
+	}
+	let intervals = ((span / step).ceil() as usize).max(1);
+
+	// One padding node on each side keeps the stencil centered
+	let tt0 = TT((s1,s2 - step));
+	let node = |k:usize| TT((s1,s2 + (k as R - 1.0) * step));
+	let nodes = (0..intervals + 3).map(|k| model.cip_xys(node(k))).collect();
+	let shifts = match model {
+	    PrecessionNutationModel::Iau1980 =>
+		(0..intervals + 3).map(|k| model.cio_origin_shift_tt(node(k))).collect(),
+	    _ => Vec::new()
+	};
+
+	// Majorants of X, Y, s and of the shift over the span of the nodes
+	let ta = ((s1 - DJ00) + s2 - step) / DJC;
+	let tb = ta + (intervals + 2) as R * step / DJC;
+	let majorants = model.cip_xys_majorants((ta + tb) / 2.0,(tb - ta) / 2.0);
+
+	// Lagrange remainder in the central interval of a four-point
+	// stencil: max |(u+1)u(u-1)(u-2)| / 4! = (9/16) / 24.  The
+	// weights add up to at most 1.25 in absolute value.
+	let h4 = (step / DJC).powi(4);
+	let [bx,by,bs,bq] = majorants.map(|m| 9.0 / 16.0 / 24.0 * h4 * m.fourth() + 2.25 * ROUNDING);
+
+	let r2 = sq(majorants[0].0[0]) + sq(majorants[1].0[0]);
+	let tilt = if r2 < 1.0 { 1.0 / sqrt(1.0 - r2) } else { R::INFINITY };
+	let shift = if shifts.is_empty() { 0.0 } else { bq };
+	let rotation_bound = tilt * sqrt(bx*bx + by*by) + bs + shift + 2.0 * ROUNDING;
+
+	Ok(Self { model,tt0,step,intervals,nodes,shifts,bounds:(bx,by,bs),rotation_bound })
+    }
+
+    pub fn model(&self)->PrecessionNutationModel {
+	self.model
+    }
+
+    /// Bounds [rad] on the interpolation errors of X, Y and s.  They
+    /// cover all the terms of the model: luni-solar and planetary
+    /// nutation, precession and the XY/2 part of s, with an allowance
+    /// for rounding errors.
+    pub fn error_bounds(&self)->(R,R,R) {
+	self.bounds
+    }
+
+    /// Bound [rad] on the rotation angle between the interpolated and
+    /// the directly computed celestial to terrestrial matrices.  The
+    /// errors dX and dY tilt the CIP by at most
+    /// sqrt(dX^2 + dY^2) / sqrt(1 - X^2 - Y^2), to which the error in
+    /// s, and for the IAU 1980 model that in the origin shift, add a
+    /// rotation about the CIP.
+    pub fn rotation_error_bound(&self)->R {
+	self.rotation_bound
+    }
+
+    /// Index of the first node of the stencil and Lagrange weights
+    fn stencil(&self,TT((tt1,tt2)):TT)->Result<(usize,[R;4]),InterpolatorError> {
+	let TT((t01,t02)) = self.tt0;
+	let u = ((tt1 - t01) + (tt2 - t02)) / self.step;
+	let umax = (self.intervals + 1) as R;
+	if u.is_nan() || u < 1.0 - 1e-9 || u > umax + 1e-9 {
+	    return Err(InterpolatorError::OutOfRange);
+	}
+	let i = (u.floor() as usize).clamp(1,self.intervals);
+	let f = u - i as R;
+
+	let w = [
+	    -f * (f - 1.0) * (f - 2.0) / 6.0,
+	    (f + 1.0) * (f - 1.0) * (f - 2.0) / 2.0,
+	    -(f + 1.0) * f * (f - 2.0) / 2.0,
+	    (f + 1.0) * f * (f - 1.0) / 6.0
+	];
+	Ok((i - 1,w))
+    }
+
+    /// Interpolated CIP coordinates X,Y and CIO locator s.
+    pub fn cip_xys(&self,tt:TT)->Result<(R,R,R),InterpolatorError> {
+	let (i,w) = self.stencil(tt)?;
+	let mut xys = (0.0,0.0,0.0);
+	for (wk,&(x,y,s)) in w.iter().zip(&self.nodes[i..i + 4]) {
+	    xys.0 += wk * x;
+	    xys.1 += wk * y;
+	    xys.2 += wk * s;
+	}
+	Ok(xys)
+    }
+
+    /// Interpolated celestial-to-intermediate matrix.
+    pub fn celestial_to_intermediate(&self,tt:TT)->Result<Mat3,InterpolatorError> {
+	let (x,y,s) = self.cip_xys(tt)?;
+	Ok(frames::celestial_to_intermediate_from_xys(x,y,s)) // c2ixys
+    }
+
+    /// Celestial to terrestrial matrix, with the Earth rotation angle
+    /// and polar motion computed exactly.
+    pub fn celestial_to_terrestrial(&self,tt:TT,ut1:UT1,xp:R,yp:R)
+				    ->Result<Mat3,InterpolatorError> {
+	let rc2i = self.celestial_to_intermediate(tt)?;
+	let era = earth::rotation_angle(ut1); // era00
+	let shift =
+	    if self.shifts.is_empty() {
+		0.0
+	    } else {
+		let (i,w) = self.stencil(tt)?;
+		let q = w.iter().zip(&self.shifts[i..i + 4]).map(|(wk,q)| wk * q).sum();
+		self.model.cio_origin_shift_from_tt_part(q,ut1)
+	    };
+	let rpom = earth::polar_motion_matrix(xp,yp,self.model.tio_locator(tt)); // pom00
+	Ok(frames::celestial_to_terrestrial_from_cio_components(&rc2i,era + shift,&rpom)) // c2tcio
+    }
+
+    /// Rotation from the GCRS to the ITRS, from the interpolated
+    /// celestial to terrestrial matrix.
+    pub fn gcrs_to_itrs(&self,tt:TT,ut1:UT1,xp:R,yp:R)
+			->Result<Rotation<Gcrs,Itrs>,InterpolatorError> {
+	Ok(Rotation::new(self.celestial_to_terrestrial(tt,ut1,xp,yp)?,tt))
+    }
+}
@@ -0,0 +1,472 @@
+// Reader for the JPL DE binary ephemerides (DE405, DE430, DE440...)
+// in the classic format written by asc2eph, e.g. linux_p1550p2650.440.
+// The file holds a header record, a record of constant values and
+// fixed-length data records, each covering a fixed time span with the
+// Chebyshev coefficients of every body over one or more sub-intervals.
+// Records are read from disk on demand and the last one is cached
+// behind a mutex, so that an ephemeris can be shared between threads;
+// concurrent evaluations are serialized.
+// The byte order of the file is detected from the header.
+//
+// Positions are with respect to the ICRF, in AU and AU/day, using the
+// astronomical unit of the file.  The time argument is TDB (strictly
+// Teph, which differs from TDB by a constant offset below 0.1 ms).
+
+use std::{
+    fs::File,
+    io::{BufReader,Read,Seek,SeekFrom},
+    path::Path,
+    sync::Mutex
+};
+
+use crate::{
+    common::*,
+    time::{TDB,D2S},
+    earth::{PosVel,EarthPosVel}
+};
+
+custom_error!{pub JplError
+	      Io{source:std::io::Error} = "I/O error: {source}",
+	      InvalidHeader             = "invalid ephemeris header",
+	      OutOfRange                = "date outside of the ephemeris range",
+	      MissingBody               = "body not present in the ephemeris",
+	      NoTimeEphemeris           = "no TT-TDB time ephemeris in the file"
+}
+
+/// Bodies and reference points of the ephemeris
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum Body {
+    Mercury,
+    Venus,
+    Earth,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+    Pluto,
+    Moon,
+    Sun,
+    SolarSystemBarycenter,
+    EarthMoonBarycenter
+}
+
+/// Number of constant names in the original header layout
+const OLD_MAX : usize = 400;
+
+/// Header offsets (bytes): titles, constant names, time span, number of
+/// constants, AU, Earth/Moon mass ratio, coefficient pointers, DE
+/// number, libration pointers, further constant names
+const TTL_LEN : usize = 3*84;
+const CNAM_LEN : usize = 6;
+const SS_OFFSET : usize = TTL_LEN + OLD_MAX*CNAM_LEN;
+const NCON_OFFSET : usize = SS_OFFSET + 3*8;
+const AU_OFFSET : usize = NCON_OFFSET + 4;
+const EMRAT_OFFSET : usize = AU_OFFSET + 8;
+const IPT_OFFSET : usize = EMRAT_OFFSET + 8;
+const NUMDE_OFFSET : usize = IPT_OFFSET + 12*3*4;
+const LPT_OFFSET : usize = NUMDE_OFFSET + 4;
+const CNAM2_OFFSET : usize = LPT_OFFSET + 3*4;
+
+/// Pointer groups: 11 bodies, nutations, librations, lunar mantle
+/// angular velocity, TT-TDB
+const NGROUPS : usize = 15;
+
+/// Group indices
+const G_EMB : usize = 2;
+const G_MOON : usize = 9;
+const G_SUN : usize = 10;
+const G_TT_TDB : usize = 14;
+
+/// Components per group
+const NCOMPONENTS : [usize;NGROUPS] = [3,3,3,3,3,3,3,3,3,3,3,2,3,3,1];
+
+/// Coefficient pointers of a group: first coefficient (1-based, within
+/// a record), number of coefficients per component and number of
+/// sub-intervals
+#[derive(Copy,Clone,Debug,Default)]
+struct Pointer {
+    start:usize,
+    ncoef:usize,
+    nsub:usize
+}
+
+struct Cache<F> {
+    reader:F,
+    record:Option<usize>,
+    coefficients:Vec<R>
+}
+
+/// JPL DE binary ephemeris.  It is `Sync` when the reader is `Send`.
+pub struct JplEphemeris<F:Read+Seek=BufReader<File>> {
+    titles:Vec<String>,
+    names:Vec<String>,
+    values:Vec<R>,
+    start:R,
+    end:R,
+    interval:R,
+    nrecords:usize,
+    au:R,
+    emrat:R,
+    numde:i32,
+    pointers:[Pointer;NGROUPS],
+    ncoeff:usize,
+    big_endian:bool,
+    cache:Mutex<Cache<F>>
+}
+
+/// Byte-order aware reading of header fields
+struct Bytes<'a> {
+    buf:&'a [u8],
+    big_endian:bool
+}
+
+impl<'a> Bytes<'a> {
+    fn i32(&self,offset:usize)->Result<i32,JplError> {
+	let b : [u8;4] = self.buf.get(offset..offset + 4)
+	    .ok_or(JplError::InvalidHeader)?
+	    .try_into().unwrap();
+	Ok(if self.big_endian { i32::from_be_bytes(b) } else { i32::from_le_bytes(b) })
+    }
+
+    fn f64(&self,offset:usize)->Result<R,JplError> {
+	let b : [u8;8] = self.buf.get(offset..offset + 8)
+	    .ok_or(JplError::InvalidHeader)?
+	    .try_into().unwrap();
+	Ok(if self.big_endian { R::from_be_bytes(b) } else { R::from_le_bytes(b) })
+    }
+
+    fn text(&self,offset:usize,len:usize)->Result<String,JplError> {
+	let b = self.buf.get(offset..offset + len).ok_or(JplError::InvalidHeader)?;
+	Ok(String::from_utf8_lossy(b).trim().to_string())
+    }
+
+    fn pointer(&self,offset:usize)->Result<Pointer,JplError> {
+	let start = self.i32(offset)?;
+	let ncoef = self.i32(offset + 4)?;
+	let nsub = self.i32(offset + 8)?;
+	if start < 0 || ncoef < 0 || nsub < 0 {
+	    return Err(JplError::InvalidHeader);
+	}
+	Ok(Pointer { start:start as usize,ncoef:ncoef as usize,nsub:nsub as usize })
+    }
+}
+
+/// Evaluate a Chebyshev series and its derivative with respect to the
+/// normalized time TC in [-1,1]
+fn chebyshev(c:&[R],tc:R)->(R,R) {
+    let (mut t0,mut t1) = (1.0,tc);
+    let (mut d0,mut d1) = (0.0,1.0);
+    let mut p = c[0];
+    let mut v = 0.0;
+    if c.len() > 1 {
+	p += c[1]*t1;
+	v += c[1]*d1;
+    }
+    for &ck in &c[2..] {
+	let t2 = 2.0*tc*t1 - t0;
+	let d2 = 2.0*t1 + 2.0*tc*d1 - d0;
+	p += ck*t2;
+	v += ck*d2;
+	(t0,t1) = (t1,t2);
+	(d0,d1) = (d1,d2);
+    }
+    (p,v)
+}
+
+impl JplEphemeris {
+    /// Open an ephemeris file
+    pub fn open<P:AsRef<Path>>(path:P)->Result<Self,JplError> {
+	Self::from_reader(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<F:Read+Seek> JplEphemeris<F> {
+    /// Read the header and constants of an ephemeris
+    pub fn from_reader(mut reader:F)->Result<Self,JplError> {
+	// The header record is at least as long as the fixed part plus
+	// the remaining pointers; constant names beyond 400 follow.
+	let mut buf = vec![0;CNAM2_OFFSET + 6*4];
+	reader.seek(SeekFrom::Start(0))?;
+	reader.read_exact(&mut buf)?;
+
+	// Byte order: the DE number and the time span must be sensible
+	let big_endian = [false,true].into_iter().find(|&big_endian| {
+	    let b = Bytes { buf:&buf,big_endian };
+	    let numde = b.i32(NUMDE_OFFSET).unwrap_or(0);
+	    let ncon = b.i32(NCON_OFFSET).unwrap_or(-1);
+	    let interval = b.f64(SS_OFFSET + 16).unwrap_or(0.0);
+	    (1..10000).contains(&numde) && (0..100000).contains(&ncon) &&
+		interval > 0.0 && interval < 1e4
+	}).ok_or(JplError::InvalidHeader)?;
+
+	let b = Bytes { buf:&buf,big_endian };
+	let titles = (0..3)
+	    .map(|i| b.text(i*84,84))
+	    .collect::<Result<Vec<_>,_>>()?;
+	let start = b.f64(SS_OFFSET)?;
+	let end = b.f64(SS_OFFSET + 8)?;
+	let interval = b.f64(SS_OFFSET + 16)?;
+	let ncon = b.i32(NCON_OFFSET)? as usize;
+	let au = b.f64(AU_OFFSET)?;
+	let emrat = b.f64(EMRAT_OFFSET)?;
+	let numde = b.i32(NUMDE_OFFSET)?;
+	let valid = end > start && au > 0.0 && emrat > 0.0;
+	if !valid {
+	    return Err(JplError::InvalidHeader);
+	}
+
+	// The time span must be a whole number of records
+	let nrecords = ((end - start) / interval).round();
+	if nrecords < 1.0 || abs(nrecords*interval - (end - start)) > 1e-6*interval {
+	    return Err(JplError::InvalidHeader);
+	}
+	let nrecords = nrecords as usize;
+
+	let mut pointers = [Pointer::default();NGROUPS];
+	for (j,pointer) in pointers.iter_mut().take(12).enumerate() {
+	    *pointer = b.pointer(IPT_OFFSET + 12*j)?;
+	}
+	pointers[12] = b.pointer(LPT_OFFSET)?;
+
+	// Names of the constants beyond the original 400, then the lunar
+	// mantle and TT-TDB pointers (absent, i.e. zero, in older files).
+	let nextra = ncon.saturating_sub(OLD_MAX);
+	let extra = CNAM2_OFFSET + nextra*CNAM_LEN;
+	if extra > CNAM2_OFFSET {
+	    buf.resize(extra + 6*4,0);
+	    reader.seek(SeekFrom::Start(0))?;
+	    reader.read_exact(&mut buf)?;
+	}
+	let b = Bytes { buf:&buf,big_endian };
+	pointers[13] = b.pointer(extra)?;
+	pointers[14] = b.pointer(extra + 12)?;
+
+	// Record length, in coefficients: the end of the last group
+	let mut ncoeff = 2;
+	for (j,p) in pointers.iter_mut().enumerate() {
+	    if p.ncoef == 0 || p.nsub == 0 {
+		*p = Pointer::default();
+		continue;
+	    }
+	    if p.start < 3 || p.ncoef < 2 {
+		return Err(JplError::InvalidHeader);
+	    }
+	    ncoeff = ncoeff.max(p.start - 1 + p.ncoef*NCOMPONENTS[j]*p.nsub);
+	}
+	if ncoeff*8 < extra + 6*4 {
+	    return Err(JplError::InvalidHeader);
+	}
+
+	let mut names = Vec::with_capacity(ncon);
+	for i in 0..ncon {
+	    let offset =
+		if i < OLD_MAX {
+		    TTL_LEN + i*CNAM_LEN
+		} else {
+		    CNAM2_OFFSET + (i - OLD_MAX)*CNAM_LEN
+		};
+	    names.push(b.text(offset,CNAM_LEN)?);
+	}
+
+	// Constant values, in the second record
+	let mut vbuf = vec![0;ncon*8];
+	reader.seek(SeekFrom::Start((ncoeff*8) as u64))?;
+	reader.read_exact(&mut vbuf)?;
+	let vb = Bytes { buf:&vbuf,big_endian };
+	let values = (0..ncon)
+	    .map(|i| vb.f64(i*8))
+	    .collect::<Result<Vec<_>,_>>()?;
+
+	Ok(Self {
+	    titles,
+	    names,
+	    values,
+	    start,
+	    end,
+	    interval,
+	    nrecords,
+	    au,
+	    emrat,
+	    numde,
+	    pointers,
+	    ncoeff,
+	    big_endian,
+	    cache:Mutex::new(Cache { reader,record:None,coefficients:vec![0.0;ncoeff] })
+	})
+    }
+
+    /// DE number of the ephemeris
+    pub fn de_number(&self)->i32 {
+	self.numde
+    }
+
+    /// Title lines of the header
+    pub fn titles(&self)->&[String] {
+	&self.titles
+    }
+
+    /// First and last Julian dates (TDB) covered by the ephemeris
+    pub fn range(&self)->(R,R) {
+	(self.start,self.end)
+    }
+
+    /// Astronomical unit of the ephemeris [km]
+    pub fn au(&self)->R {
+	self.au
+    }
+
+    /// Earth/Moon mass ratio
+    pub fn emrat(&self)->R {
+	self.emrat
+    }
+
+    /// Value of a named constant of the ephemeris
+    pub fn constant(&self,name:&str)->Option<R> {
+	self.names.iter().position(|n| n == name).map(|i| self.values[i])
+    }
+
+    /// Evaluate the components of a pointer group at a date.  Returns
+    /// the values and their rates per day.
+    fn interpolate(&self,group:usize,TDB((d1,d2)):TDB)->Result<([R;3],[R;3]),JplError> {
+	let Pointer { start,ncoef,nsub } = self.pointers[group];
+	if ncoef == 0 {
+	    return Err(JplError::MissingBody);
+	}
+
+	// Record covering the date; the end of the range belongs to the
+	// last record.
+	let t = (d1 - self.start) + d2;
+	let inside = t >= 0.0 && t <= self.end - self.start;
+	if !inside {
+	    return Err(JplError::OutOfRange);
+	}
+	let irec = ((t / self.interval).floor() as usize).min(self.nrecords - 1);
+
+	// The cache is consistent even if another thread panicked while
+	// holding the lock: the record is only set once it is loaded.
+	let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+	if cache.record != Some(irec) {
+	    let Cache { reader,coefficients,record } = &mut *cache;
+	    *record = None;
+	    let mut buf = vec![0;self.ncoeff*8];
+	    reader.seek(SeekFrom::Start(((irec + 2)*self.ncoeff*8) as u64))?;
+	    reader.read_exact(&mut buf)?;
+	    let b = Bytes { buf:&buf,big_endian:self.big_endian };
+	    for (i,c) in coefficients.iter_mut().enumerate() {
+		*c = b.f64(i*8)?;
+	    }
+	    *record = Some(irec);
+	}
+	let c = &cache.coefficients;
+
+	// Sub-interval and normalized time
+	let dt = t - ( c[0] - self.start );
+	let span = c[1] - c[0];
+	let sublen = span / nsub as R;
+	let isub = ((dt / sublen).floor().max(0.0) as usize).min(nsub - 1);
+	let tc = 2.0 * ( dt - isub as R * sublen ) / sublen - 1.0;
+
+	let ncomp = NCOMPONENTS[group];
+	let mut p = [0.0;3];
+	let mut v = [0.0;3];
+	for k in 0..ncomp {
+	    let i0 = start - 1 + ( isub*ncomp + k )*ncoef;
+	    let (pk,vk) = chebyshev(&c[i0..i0 + ncoef],tc);
+	    p[k] = pk;
+	    v[k] = vk * 2.0 / sublen;
+	}
+	Ok((p,v))
+    }
+
+    /// Barycentric position and velocity of a body [km, km/day]
+    fn barycentric_km(&self,body:Body,tdb:TDB)->Result<PosVel,JplError> {
+	let earth_moon = || -> Result<(PosVel,PosVel),JplError> {
+	    let (pb,vb) = self.interpolate(G_EMB,tdb)?;
+	    let (pm,vm) = self.interpolate(G_MOON,tdb)?;
+	    let f = 1.0 / ( 1.0 + self.emrat );
+	    let earth = PosVel { p:pb.sub(pm.scale(f)),v:vb.sub(vm.scale(f)) };
+	    Ok((earth,PosVel { p:pm,v:vm }))
+	};
+	let group = match body {
+	    Body::SolarSystemBarycenter => return Ok(PosVel { p:[0.0;3],v:[0.0;3] }),
+	    Body::Earth => return Ok(earth_moon()?.0),
+	    Body::Moon => {
+		let (earth,moon) = earth_moon()?;
+		return Ok(PosVel { p:earth.p.add(moon.p),v:earth.v.add(moon.v) });
+	    },
+	    Body::Mercury => 0,
+	    Body::Venus => 1,
+	    Body::EarthMoonBarycenter => G_EMB,
+	    Body::Mars => 3,
+	    Body::Jupiter => 4,
+	    Body::Saturn => 5,
+	    Body::Uranus => 6,
+	    Body::Neptune => 7,
+	    Body::Pluto => 8,
+	    Body::Sun => G_SUN
+	};
+	let (p,v) = self.interpolate(group,tdb)?;
+	Ok(PosVel { p,v })
+    }
+
+    /// Position and velocity of the body TARGET with respect to the body
+    /// CENTER, in the ICRF [AU, AU/day]
+    pub fn pos_vel(&self,target:Body,center:Body,tdb:TDB)->Result<PosVel,JplError> {
+	let pv =
+	    match (target,center) {
+		// Geocentric Moon directly, for accuracy
+		(Body::Moon,Body::Earth) => {
+		    let (p,v) = self.interpolate(G_MOON,tdb)?;
+		    PosVel { p,v }
+		},
+		(Body::Earth,Body::Moon) => {
+		    let (p,v) = self.interpolate(G_MOON,tdb)?;
+		    PosVel { p:p.neg(),v:v.neg() }
+		},
+		_ => {
+		    let t = self.barycentric_km(target,tdb)?;
+		    let c = self.barycentric_km(center,tdb)?;
+		    PosVel { p:t.p.sub(c.p),v:t.v.sub(c.v) }
+		}
+	    };
+	Ok(PosVel { p:pv.p.scale(1.0/self.au),v:pv.v.scale(1.0/self.au) })
+    }
+
+    /// Earth position and velocity, heliocentric and barycentric, with
+    /// respect to the BCRS [AU, AU/day], as a replacement for the epv00
+    /// series of EarthPosVel
+    pub fn earth_pos_vel(&self,tdb:TDB)->Result<EarthPosVel,JplError> {
+	let earth = self.barycentric_km(Body::Earth,tdb)?;
+	let sun = self.barycentric_km(Body::Sun,tdb)?;
+	let k = 1.0 / self.au;
+	Ok(EarthPosVel {
+	    heliocentric:PosVel { p:earth.p.sub(sun.p).scale(k),v:earth.v.sub(sun.v).scale(k) },
+	    barycentric:PosVel { p:earth.p.scale(k),v:earth.v.scale(k) },
+	    warning:None
+	})
+    }
+
+    /// Whether the file includes the TT-TDB time ephemeris
+    pub fn has_time_ephemeris(&self)->bool {
+	self.pointers[G_TT_TDB].ncoef > 0
+    }
+
+    /// TT-TDB at the geocentre [s]
+    pub fn tt_minus_tdb(&self,tdb:TDB)->Result<R,JplError> {
+	if !self.has_time_ephemeris() {
+	    return Err(JplError::NoTimeEphemeris);
+	}
+	let (p,_) = self.interpolate(G_TT_TDB,tdb)?;
+	Ok(p[0])
+    }
+
+    /// TT-TDB at the geocentre [s] and its rate [s/s]
+    pub fn tt_minus_tdb_rate(&self,tdb:TDB)->Result<(R,R),JplError> {
+	if !self.has_time_ephemeris() {
+	    return Err(JplError::NoTimeEphemeris);
+	}
+	let (p,v) = self.interpolate(G_TT_TDB,tdb)?;
+	Ok((p[0],v[0] / D2S))
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 
 pub mod common;
+pub mod linalg;
+pub mod quaternion;
 pub mod delta_at;
 pub mod earth;
 pub mod calendar;
@@ -9,11 +11,30 @@ pub mod time;
 pub mod frames;
 pub mod locator;
 pub mod fundargs;
+pub mod iers_tables;
+pub mod teme;
+pub mod typed;
+pub mod interpolator;
+mod majorant;
+pub mod longterm;
+pub mod ecliptic;
+pub mod galactic;
+pub mod stars;
+pub mod astrometry;
+pub mod horizontal;
+pub mod moon;
+pub mod planets;
+pub mod jpl;
 
+#[allow(clippy::large_const_arrays,clippy::excessive_precision)]
 mod epv00_data;
     
 #[cfg(test)]
+#[allow(clippy::single_element_loop,
+	 clippy::excessive_precision,
+	 clippy::needless_range_loop)]
 mod tests;
 
 #[cfg(test)]
+#[allow(clippy::type_complexity,clippy::excessive_precision)]
 mod test_data;
@@ -0,0 +1,295 @@
+// Vector, matrix and rotation types with arithmetic operators.  They
+// wrap the plain-array Vec3 and Mat3 used throughout the crate and
+// convert to and from them with From/Into.  Matrix-vector products are
+// the ordinary M·x (SOFA rxp), unlike Matrix::apply.
+
+use std::ops::{Add,AddAssign,Sub,SubAssign,Neg,Mul,Div,Index,IndexMut};
+
+use crate::common::*;
+
+/// A 3-vector
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+pub struct Vector3(pub Vec3);
+
+/// A 3x3 matrix, stored by rows
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+pub struct Matrix3(pub Mat3);
+
+/// A proper rotation, i.e. an orthonormal matrix of determinant +1
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct Rotation3(Matrix3);
+
+impl From<Vec3> for Vector3 {
+    fn from(x:Vec3)->Self { Self(x) }
+}
+
+impl From<Vector3> for Vec3 {
+    fn from(x:Vector3)->Self { x.0 }
+}
+
+impl From<Mat3> for Matrix3 {
+    fn from(m:Mat3)->Self { Self(m) }
+}
+
+impl From<Matrix3> for Mat3 {
+    fn from(m:Matrix3)->Self { m.0 }
+}
+
+impl From<Rotation3> for Matrix3 {
+    fn from(r:Rotation3)->Self { r.0 }
+}
+
+impl From<Rotation3> for Mat3 {
+    fn from(r:Rotation3)->Self { r.0.0 }
+}
+
+impl Vector3 {
+    pub fn new(x:R,y:R,z:R)->Self {
+	Self([x,y,z])
+    }
+
+    pub fn zero()->Self {
+	Self([0.0;3])
+    }
+
+    pub fn dot(self,b:Self)->R {
+	self.0.dot(b.0)
+    }
+
+    pub fn cross(self,b:Self)->Self {
+	Self(self.0.cross(b.0))
+    }
+
+    pub fn norm(self)->R {
+	self.0.norm()
+    }
+
+    pub fn normalize(self)->Self {
+	Self(self.0.normalize())
+    }
+
+    /// Angular separation with another vector
+    pub fn angle(self,b:Self)->R {
+	self.0.angle(b.0)
+    }
+}
+
+impl Index<usize> for Vector3 {
+    type Output = R;
+    fn index(&self,i:usize)->&R { &self.0[i] }
+}
+
+impl IndexMut<usize> for Vector3 {
+    fn index_mut(&mut self,i:usize)->&mut R { &mut self.0[i] }
+}
+
+impl Add for Vector3 {
+    type Output = Self;
+    fn add(self,b:Self)->Self { Self(self.0.add(b.0)) }
+}
+
+impl AddAssign for Vector3 {
+    fn add_assign(&mut self,b:Self) { *self = *self + b }
+}
+
+impl Sub for Vector3 {
+    type Output = Self;
+    fn sub(self,b:Self)->Self { Self(self.0.sub(b.0)) }
+}
+
+impl SubAssign for Vector3 {
+    fn sub_assign(&mut self,b:Self) { *self = *self - b }
+}
+
+impl Neg for Vector3 {
+    type Output = Self;
+    fn neg(self)->Self { Self(self.0.neg()) }
+}
+
+impl Mul<R> for Vector3 {
+    type Output = Self;
+    fn mul(self,c:R)->Self { Self(self.0.scale(c)) }
+}
+
+impl Mul<Vector3> for R {
+    type Output = Vector3;
+    fn mul(self,x:Vector3)->Vector3 { x * self }
+}
+
+impl Div<R> for Vector3 {
+    type Output = Self;
+    fn div(self,c:R)->Self { Self(self.0.scale(1.0 / c)) }
+}
+
+impl Matrix3 {
+    pub fn zero()->Self {
+	Self(Mat3::zero())
+    }
+
+    pub fn identity()->Self {
+	Self(Mat3::identity())
+    }
+
+    /// Matrix whose rows are the given vectors
+    pub fn from_rows(a:Vector3,b:Vector3,c:Vector3)->Self {
+	Self([a.0,b.0,c.0])
+    }
+
+    pub fn row(&self,i:usize)->Vector3 {
+	Vector3(self.0[i])
+    }
+
+    pub fn transpose(&self)->Self {
+	Self(self.0.transpose())
+    }
+
+    pub fn determinant(&self)->R {
+	self.row(0).dot(self.row(1).cross(self.row(2)))
+    }
+
+    /// Inverse matrix, or None if the matrix is singular
+    pub fn inverse(&self)->Option<Self> {
+	let det = self.determinant();
+	if det == 0.0 {
+	    return None;
+	}
+	let (a,b,c) = (self.row(0),self.row(1),self.row(2));
+
+	// The columns of the inverse are the cross products of the rows
+	Some(Self::from_rows(b.cross(c),c.cross(a),a.cross(b)).transpose() / det)
+    }
+}
+
+impl Index<usize> for Matrix3 {
+    type Output = Vec3;
+    fn index(&self,i:usize)->&Vec3 { &self.0[i] }
+}
+
+impl IndexMut<usize> for Matrix3 {
+    fn index_mut(&mut self,i:usize)->&mut Vec3 { &mut self.0[i] }
+}
+
+impl Add for Matrix3 {
+    type Output = Self;
+    fn add(self,b:Self)->Self { Self(self.0.add(&b.0)) }
+}
+
+impl Sub for Matrix3 {
+    type Output = Self;
+    fn sub(self,b:Self)->Self { Self(self.0.add(&b.0.scale(-1.0))) }
+}
+
+impl Neg for Matrix3 {
+    type Output = Self;
+    fn neg(self)->Self { Self(self.0.scale(-1.0)) }
+}
+
+impl Mul for Matrix3 {
+    type Output = Self;
+    fn mul(self,b:Self)->Self { Self(self.0.compose(&b.0)) }
+}
+
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+    fn mul(self,x:Vector3)->Vector3 { Vector3(self.0.mul_vec(x.0)) }
+}
+
+impl Mul<R> for Matrix3 {
+    type Output = Self;
+    fn mul(self,c:R)->Self { Self(self.0.scale(c)) }
+}
+
+impl Mul<Matrix3> for R {
+    type Output = Matrix3;
+    fn mul(self,m:Matrix3)->Matrix3 { m * self }
+}
+
+impl Div<R> for Matrix3 {
+    type Output = Self;
+    fn div(self,c:R)->Self { Self(self.0.scale(1.0 / c)) }
+}
+
+/// Largest departure from orthonormality accepted by
+/// Rotation3::from_matrix
+pub const ORTHONORMALITY_TOLERANCE : R = 1e-6;
+
+impl Rotation3 {
+    pub fn identity()->Self {
+	Self(Matrix3::identity())
+    }
+
+    /// Rotation of the reference frame about the given axis (0, 1 or 2
+    /// for x, y or z) by THETA, as SOFA rx, ry and rz.
+    pub fn axis(axis:usize,theta:R)->Self {
+	Self(Matrix3(Mat3::rotation(axis,theta)))
+    }
+
+    /// Rotation corresponding to a rotation vector.
+    pub fn from_rotation_vector(w:Vector3)->Self {
+	Self(Matrix3(rotation_vector_to_matrix(w.0))) // rv2m
+    }
+
+    /// Express the rotation as a rotation vector.
+    ///
+    /// Source: rm2v.for
+    pub fn to_rotation_vector(&self)->Vector3 {
+	let r = &self.0.0;
+	let w = [r[1][2] - r[2][1],
+		 r[2][0] - r[0][2],
+		 r[0][1] - r[1][0]];
+	let s2 = w.norm();
+	if s2 > 0.0 {
+	    let c2 = r[0][0] + r[1][1] + r[2][2] - 1.0;
+	    let phi = atan2(s2,c2);
+	    Vector3(w.scale(phi / s2))
+	} else {
+	    Vector3::zero()
+	}
+    }
+
+    /// The nearest rotation to a matrix that is orthonormal with
+    /// determinant +1 to within ORTHONORMALITY_TOLERANCE, or None.
+    pub fn from_matrix(m:Matrix3)->Option<Self> {
+	let e = m * m.transpose() - Matrix3::identity();
+	let err = e.0.iter().flatten().fold(0.0,|a:R,x| a.max(abs(*x)));
+	if err > ORTHONORMALITY_TOLERANCE || m.determinant() < 0.0 {
+	    None
+	} else {
+	    Some(Self(m).renormalize())
+	}
+    }
+
+    /// Remove the accumulated departure from orthonormality, using
+    /// one Newton step of the polar decomposition.
+    pub fn renormalize(&self)->Self {
+	let m = self.0;
+	match m.inverse() {
+	    Some(mi) => Self((m + mi.transpose()) * 0.5),
+	    None => *self
+	}
+    }
+
+    pub fn matrix(&self)->Matrix3 {
+	self.0
+    }
+
+    /// The inverse rotation, i.e. the transpose
+    pub fn inverse(&self)->Self {
+	Self(self.0.transpose())
+    }
+
+    /// Rotation angle [0,π]
+    pub fn angle(&self)->R {
+	self.to_rotation_vector().norm()
+    }
+}
+
+impl Mul for Rotation3 {
+    type Output = Self;
+    fn mul(self,b:Self)->Self { Self(self.0 * b.0) }
+}
+
+impl Mul<Vector3> for Rotation3 {
+    type Output = Vector3;
+    fn mul(self,x:Vector3)->Vector3 { self.0 * x }
+}
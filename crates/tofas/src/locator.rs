@@ -1,6 +1,7 @@
 use crate::{
     common::*,
     fundargs,
+    majorant::{self,Majorant},
     time::{TT,DJC,DJ00}
 };
 
@@ -202,9 +203,117 @@ const SSS : [&[[R;2]];N] = [
     &SS3,
     &SS4,
 ];
+
+// IAU 2006 coefficients, differing from the IAU 2000 ones in the
+// polynomial part and in a few of the T^1 to T^3 terms.
+
+const SP06 : [R;6] = [
+    94.00e-6,
+    3808.65e-6,
+    -122.68e-6,
+    -72574.11e-6,
+    27.98e-6,
+    15.62e-6
+];
+
+const SS1_06 : [[R;2];NS1] = [
+    [               -0.07e-6,           3.57e-6],
+    [                1.73e-6,          -0.03e-6],
+    [                0.00e-6,           0.48e-6]
+];
+
+const SS2_06 : [[R;2];NS2] = [
+    [              743.52e-6,          -0.17e-6],
+    [               56.91e-6,           0.06e-6],
+    [                9.84e-6,          -0.01e-6],
+    [               -8.85e-6,           0.01e-6],
+    [               -6.38e-6,          -0.05e-6],
+    [               -3.07e-6,           0.00e-6],
+    [                2.23e-6,           0.00e-6],
+    [                1.67e-6,           0.00e-6],
+    [                1.30e-6,           0.00e-6],
+    [                0.93e-6,           0.00e-6],
+    [                0.68e-6,           0.00e-6],
+    [               -0.55e-6,           0.00e-6],
+    [                0.53e-6,           0.00e-6],
+    [               -0.27e-6,           0.00e-6],
+    [               -0.27e-6,           0.00e-6],
+    [               -0.26e-6,           0.00e-6],
+    [               -0.25e-6,           0.00e-6],
+    [                0.22e-6,           0.00e-6],
+    [               -0.21e-6,           0.00e-6],
+    [                0.20e-6,           0.00e-6],
+    [                0.17e-6,           0.00e-6],
+    [                0.13e-6,           0.00e-6],
+    [               -0.13e-6,           0.00e-6],
+    [               -0.12e-6,           0.00e-6],
+    [               -0.11e-6,           0.00e-6]
+];
+
+const SS3_06 : [[R;2];NS3] = [
+    [                0.30e-6,         -23.42e-6],
+    [               -0.03e-6,          -1.46e-6],
+    [               -0.01e-6,          -0.25e-6],
+    [                0.00e-6,           0.23e-6]
+];
+
+const SSS06 : [&[[R;2]];N] = [
+    &SS0,
+    &SS1_06,
+    &SS2_06,
+    &SS3_06,
+    &SS4,
+];
     
+/// The CIO locator s, positioning the Celestial Intermediate Origin on
+/// the equator of the Celestial Intermediate Pole, given the CIP's X,Y
+/// coordinates.  Compatible with IAU 2000A precession-nutation.
+///
 /// Source: s00.for
-pub fn cio(TT((date1,date2)):TT,x:R,y:R)->R {
+pub fn cio(tt:TT,x:R,y:R)->R {
+    series(tt,&SP,&SSS) - x*y/2.0
+}
+
+/// The CIO locator s, given the CIP's X,Y coordinates.  Compatible
+/// with IAU 2006/2000A precession-nutation.
+///
+/// Source: s06.for
+pub fn cio_06(tt:TT,x:R,y:R)->R {
+    series(tt,&SP06,&SSS06) - x*y/2.0
+}
+
+/// Majorant of cio() for |t| <= TMAX centuries, given those of X and
+/// Y
+pub(crate) fn cio_majorant(tmax:R,x:&Majorant,y:&Majorant)->Majorant {
+    series_majorant(tmax,&SP,&SSS) + (*x * *y).scale(0.5)
+}
+
+/// Majorant of cio_06() for |t| <= TMAX centuries, given those of X
+/// and Y
+pub(crate) fn cio_06_majorant(tmax:R,x:&Majorant,y:&Majorant)->Majorant {
+    series_majorant(tmax,&SP06,&SSS06) + (*x * *y).scale(0.5)
+}
+
+/// Majorant of the series for s+XY/2
+fn series_majorant(tmax:R,sp:&[R;NSP],sss:&[&[[R;2]];N])->Majorant {
+    let p = fundargs::polynomials_03();
+    let fa = [p[0],p[1],p[2],p[3],p[4],p[6],p[7],p[13]];
+
+    let mut s = Majorant::polynomial(sp,tmax);
+
+    for k in 0..N {
+	let tk = Majorant::power(k,tmax);
+	for (ks,ss) in KSS[k].iter().zip(sss[k].iter()) {
+	    let a = Majorant::polynomial(&majorant::combination(ks.map(R::from),&fa),tmax);
+	    s = s + tk * (a.sin().scale(ss[0]) + a.cos().scale(ss[1]));
+	}
+    }
+
+    s.scale(AS2R)
+}
+
+/// Evaluate the series for s+XY/2
+fn series(TT((date1,date2)):TT,sp:&[R;NSP],sss:&[&[[R;2]];N])->R {
     let t = ((date1 - DJ00) + date2) / DJC;
 
     let fa = [
@@ -218,11 +327,11 @@ pub fn cio(TT((date1,date2)):TT,x:R,y:R)->R {
 	fundargs::pa03(t)
     ];
 
-    let mut s = SP;
+    let mut s = *sp;
 
     for k in 0..N {
 	let ksk = &KSS[k];
-	let ssk = &SSS[k];
+	let ssk = &sss[k];
 	let nsk = ksk.len();
 	for i in (0..nsk).rev() {
 	    let mut a = 0.0;
@@ -233,5 +342,5 @@ pub fn cio(TT((date1,date2)):TT,x:R,y:R)->R {
 	}
     }
 
-    ( s[0] + ( s[1] + ( s[2] + ( s[3] + ( s[4] + s[5] * t ) * t ) * t ) * t ) * t ) * AS2R - x*y/2.0
+    ( s[0] + ( s[1] + ( s[2] + ( s[3] + ( s[4] + s[5] * t ) * t ) * t ) * t ) * t ) * AS2R
 }
@@ -0,0 +1,139 @@
+// Long-term precession model of Vondrák, Capitaine and Wallace
+// (2011), valid for +/- 200 millennia around J2000.0.  Dates are Julian
+// epochs (TT).
+
+use crate::{
+    common::*,
+    frames
+};
+
+/// Obliquity at J2000.0 [rad]
+const EPS0 : R = 84381.406 * AS2R;
+
+/// Polynomial coefficients of the ecliptic pole (P,Q) [arcsec]
+const PQPOL : [[R;4];2] = [
+    [ 5851.607687, -0.1189000, -0.00028913,  0.000000101],
+    [-1600.886300,  1.1689818, -0.00000020, -0.000000437]
+];
+
+/// Periodic coefficients of the ecliptic pole: period [y], cosine and
+/// sine terms of P and Q [arcsec]
+const PQPER : [[R;5];8] = [
+    [ 708.15, -5486.751211, -684.661560,  667.666730, -5523.863691],
+    [2309.00,   -17.127623, 2446.283880,-2354.886252,  -549.747450],
+    [1620.00,  -617.517403,  399.671049, -428.152441,  -310.998056],
+    [ 492.20,   413.442940, -356.652376,  376.202861,   421.535876],
+    [1183.00,    78.614193, -186.387003,  184.778874,   -36.776172],
+    [ 622.00,  -180.732815, -316.800070,  335.321713,  -145.278396],
+    [ 882.00,   -87.676083,  198.296701, -185.138669,   -34.744450],
+    [ 547.00,    46.140315,  101.135679, -120.972830,    22.885731]
+];
+
+/// Polynomial coefficients of the equator pole (X,Y) [arcsec]
+const XYPOL : [[R;4];2] = [
+    [  5453.282155,  0.4252841, -0.00037173, -0.000000152],
+    [-73750.930350, -0.7675452, -0.00018725,  0.000000231]
+];
+
+/// Periodic coefficients of the equator pole: period [y], cosine and
+/// sine terms of X and Y [arcsec]
+const XYPER : [[R;5];14] = [
+    [ 256.75,  -819.940624, 75004.344875, 81491.287984,  1558.515853],
+    [ 708.15, -8444.676815,   624.033993,   787.163481,  7774.939698],
+    [ 274.20,  2600.009459,  1251.136893,  1251.296102, -2219.534038],
+    [ 241.45,  2755.175630, -1102.212834, -1257.950837, -2523.969396],
+    [2309.00,  -167.659835, -2660.664980, -2966.799730,   247.850422],
+    [ 492.20,   871.855056,   699.291817,   639.744522,  -846.485643],
+    [ 396.10,    44.769698,   153.167220,   131.600209, -1393.124055],
+    [ 288.90,  -512.313065,  -950.865637,  -445.040117,   368.526116],
+    [ 231.10,  -819.415595,   499.754645,   584.522874,   749.045012],
+    [1610.00,  -538.071099,  -145.188210,   -89.756563,   444.704518],
+    [ 620.00,  -189.793622,   558.116553,   524.429630,   235.934465],
+    [ 157.87,  -402.922932,   -23.923029,   -13.549067,   374.049623],
+    [ 220.30,   179.516345,  -165.405086,  -210.157124,  -171.330180],
+    [1200.00,    -9.814756,     9.344131,   -44.919798,   -22.899655]
+];
+
+/// Sum the periodic and polynomial parts of a pole series [rad]
+fn pole_series(t:R,per:&[[R;5]],pol:&[[R;4];2])->(R,R) {
+    let mut a = 0.0;
+    let mut b = 0.0;
+
+    // Periodic terms.
+    let w = TWO_PI * t;
+    for c in per {
+	let arg = w / c[0];
+	let (s,co) = (sin(arg),cos(arg));
+	a += co * c[1] + s * c[3];
+	b += co * c[2] + s * c[4];
+    }
+
+    // Polynomial terms.
+    let mut w = 1.0;
+    for (pa,pb) in pol[0].iter().zip(&pol[1]) {
+	a += pa * w;
+	b += pb * w;
+	w *= t;
+    }
+
+    (a * AS2R,b * AS2R)
+}
+
+/// Long-term precession of the ecliptic: unit vector of the ecliptic
+/// pole, with respect to the J2000.0 mean equator and equinox.
+///
+/// Source: ltpecl.for
+pub fn ecliptic_pole(epj:R)->Vec3 {
+    let t = ( epj - 2000.0 ) / 100.0;
+    let (p,q) = pole_series(t,&PQPER,&PQPOL);
+    let w = 1.0 - p*p - q*q;
+    let w = if w < 0.0 { 0.0 } else { sqrt(w) };
+    let (s,c) = (sin(EPS0),cos(EPS0));
+    [p,
+     -q*c - w*s,
+     -q*s + w*c]
+}
+
+/// Long-term precession of the equator: unit vector of the celestial
+/// pole, with respect to the J2000.0 mean equator and equinox.
+///
+/// Source: ltpequ.for
+pub fn equator_pole(epj:R)->Vec3 {
+    let t = ( epj - 2000.0 ) / 100.0;
+    let (x,y) = pole_series(t,&XYPER,&XYPOL);
+    let w = 1.0 - x*x - y*y;
+    [x,
+     y,
+     if w < 0.0 { 0.0 } else { sqrt(w) }]
+}
+
+/// Long-term precession matrix, from the J2000.0 mean equator and
+/// equinox to the mean equator and equinox of date.
+///
+/// Source: ltp.for
+pub fn precession_matrix(epj:R)->Mat3 {
+    // Equator pole (bottom row of matrix).
+    let peqr = equator_pole(epj); // ltpequ
+
+    // Ecliptic pole.
+    let pecl = ecliptic_pole(epj); // ltpecl
+
+    // Equinox (top row of matrix).
+    let eqx = peqr.cross(pecl).normalize(); // pxp, pn
+
+    // Middle row of matrix.
+    let v = peqr.cross(eqx);
+
+    [eqx,v,peqr]
+}
+
+/// Long-term precession matrix, including ICRS frame bias.
+///
+/// Source: ltpb.for
+pub fn bias_precession_matrix(epj:R)->Mat3 {
+    // Precession matrix.
+    let rp = precession_matrix(epj); // ltp
+
+    // Apply the bias.
+    frames::with_frame_bias_first_order(&rp)
+}
@@ -0,0 +1,184 @@
+// Majorants of the derivatives of a function of time: upper bounds on
+// the absolute value of the function and of its first four
+// derivatives over an interval of time, in Julian centuries.  They
+// are propagated through sums and products (Leibniz rule) and through
+// compositions with sin, cos, atan or the reciprocal (Faà di Bruno
+// formula), following the expressions that evaluate the function, and
+// give the bound on the fourth derivative of the Lagrange remainder
+// used by the CIP interpolator.
+
+use std::ops::{Add,Mul};
+
+use crate::common::*;
+
+/// Bounds on |f|, |f'|, |f''|, |f'''| and |f''''| over an interval
+#[derive(Copy,Clone,Debug,Default,PartialEq)]
+pub(crate) struct Majorant(pub [R;5]);
+
+impl Majorant {
+    pub fn constant(c:R)->Self {
+	Self([abs(c),0.0,0.0,0.0,0.0])
+    }
+
+    /// Polynomial with coefficients C, constant term first, for
+    /// |t| <= TMAX
+    pub fn polynomial(c:&[R],tmax:R)->Self {
+	let mut m = [0.0;5];
+	for (k,mk) in m.iter_mut().enumerate() {
+	    for (j,cj) in c.iter().enumerate().skip(k) {
+		let falling : R = ((j - k + 1)..=j).map(|i| i as R).product();
+		*mk += abs(*cj) * falling * tmax.powi((j - k) as i32);
+	    }
+	}
+	Self(m)
+    }
+
+    /// The power t^J for |t| <= TMAX
+    pub fn power(j:usize,tmax:R)->Self {
+	let mut c = vec![0.0;j + 1];
+	c[j] = 1.0;
+	Self::polynomial(&c,tmax)
+    }
+
+    pub fn scale(&self,c:R)->Self {
+	Self(self.0.map(|m| abs(c) * m))
+    }
+
+    /// Bound on the fourth derivative
+    pub fn fourth(&self)->R {
+	self.0[4]
+    }
+
+    /// Tighten the bound on |f| from its value at a point of the
+    /// interval, within H of any other point
+    pub fn with_value(self,f:R,h:R)->Self {
+	let Self(mut m) = self;
+	m[0] = m[0].min(abs(f) + m[1] * h);
+	Self(m)
+    }
+
+    /// g(f) given bounds G on the absolute values of g and its first
+    /// four derivatives over the range of f
+    fn compose(&self,g:[R;5])->Self {
+	let [_,u1,u2,u3,u4] = self.0;
+	Self([g[0],
+	      g[1] * u1,
+	      g[1] * u2 + g[2] * u1 * u1,
+	      g[1] * u3 + 3.0 * g[2] * u1 * u2 + g[3] * u1.powi(3),
+	      g[1] * u4 + g[2] * ( 4.0 * u1 * u3 + 3.0 * u2 * u2 ) +
+	      6.0 * g[3] * u1 * u1 * u2 + g[4] * u1.powi(4)])
+    }
+
+    pub fn sin(&self)->Self {
+	let s = self.0[0].min(1.0);
+	self.compose([s,1.0,s,1.0,s])
+    }
+
+    pub fn cos(&self)->Self {
+	let s = self.0[0].min(1.0);
+	self.compose([1.0,s,1.0,s,1.0])
+    }
+
+    /// 1/f where f >= LOWER; unbounded unless LOWER > 0
+    pub fn recip(&self,lower:R)->Self {
+	if lower > 0.0 {
+	    self.compose([1.0 / lower,
+			  1.0 / lower.powi(2),
+			  2.0 / lower.powi(3),
+			  6.0 / lower.powi(4),
+			  24.0 / lower.powi(5)])
+	} else {
+	    Self([R::INFINITY;5])
+	}
+    }
+
+    pub fn atan(&self)->Self {
+	let v = self.0[0];
+	self.compose([v,1.0,2.0 * v,2.0,24.0 * v])
+    }
+}
+
+impl Add for Majorant {
+    type Output = Self;
+    fn add(self,b:Self)->Self {
+	let mut m = self.0;
+	for (mk,bk) in m.iter_mut().zip(b.0) {
+	    *mk += bk;
+	}
+	Self(m)
+    }
+}
+
+impl Mul for Majorant {
+    type Output = Self;
+    fn mul(self,b:Self)->Self {
+	const BINOMIAL : [[R;5];5] = [
+	    [1.0,0.0,0.0,0.0,0.0],
+	    [1.0,1.0,0.0,0.0,0.0],
+	    [1.0,2.0,1.0,0.0,0.0],
+	    [1.0,3.0,3.0,1.0,0.0],
+	    [1.0,4.0,6.0,4.0,1.0]
+	];
+	let (a,b) = (self.0,b.0);
+	let mut m = [0.0;5];
+	for (k,mk) in m.iter_mut().enumerate() {
+	    for i in 0..=k {
+		*mk += BINOMIAL[k][i] * a[i] * b[k - i];
+	    }
+	}
+	Self(m)
+    }
+}
+
+impl std::iter::Sum for Majorant {
+    fn sum<I:Iterator<Item=Self>>(iter:I)->Self {
+	iter.fold(Self::default(),|a,b| a + b)
+    }
+}
+
+/// Polynomial of the linear combination with multipliers N of the
+/// polynomials P
+pub(crate) fn combination<const K:usize>(n:impl IntoIterator<Item=R>,p:&[[R;K]])->[R;K] {
+    let mut c = [0.0;K];
+    for (nj,pj) in n.into_iter().zip(p) {
+	for (ck,pjk) in c.iter_mut().zip(pj) {
+	    *ck += nj * pjk;
+	}
+    }
+    c
+}
+
+/// Majorants of the elements of a 3x3 matrix
+#[derive(Copy,Clone,Debug,Default)]
+pub(crate) struct MajorantMatrix(pub [[Majorant;3];3]);
+
+impl MajorantMatrix {
+    pub fn constant(r:&Mat3)->Self {
+	Self(r.map(|row| row.map(Majorant::constant)))
+    }
+
+    /// Mat3::rotation(AXIS,THETA)
+    pub fn rotation(axis:usize,theta:&Majorant)->Self {
+	let (c,s) = (theta.cos(),theta.sin());
+	let i0 = (axis + 1) % 3;
+	let i1 = (axis + 2) % 3;
+	let mut r = Self::default();
+	r.0[i0][i0] = c;
+	r.0[i0][i1] = s;
+	r.0[i1][i0] = s;
+	r.0[i1][i1] = c;
+	r.0[axis][axis] = Majorant::constant(1.0);
+	r
+    }
+
+    /// Majorants of the product of the matrices
+    pub fn compose(&self,b:&Self)->Self {
+	let mut c = Self::default();
+	for i in 0..3 {
+	    for j in 0..3 {
+		c.0[i][j] = (0..3).map(|k| self.0[i][k] * b.0[k][j]).sum();
+	    }
+	}
+	c
+    }
+}
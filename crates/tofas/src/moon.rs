@@ -0,0 +1,297 @@
+// Approximate geocentric position and velocity of the Moon, from the
+// simplified ELP2000-82 series of Meeus (Astronomical Algorithms, 1998,
+// chapter 47) with the IAU 2006 frame bias and precession.  The
+// accuracy is about 10 arcsec in direction and 1000 km in distance over
+// 1900-2100 AD.
+
+use crate::{
+    common::*,
+    time::{TT,DJ00,DJC},
+    earth::{AUM,PosVel},
+    frames
+};
+
+// Coefficients for fundamental arguments (degrees, Julian centuries)
+
+/// Moon's mean longitude (wrt mean equinox and ecliptic of date):
+/// constant term from Simon et al. (1994), the others from Meeus
+const ELP : [R;5] = [218.31665436,481267.88123421,-0.0015786,
+		     1.0/538841.0,-1.0/65194000.0];
+
+/// Moon's mean elongation
+const D : [R;5] = [297.8501921,445267.1114034,-0.0018819,
+		   1.0/545868.0,1.0/113065000.0];
+
+/// Sun's mean anomaly
+const EM : [R;5] = [357.5291092,35999.0502909,-0.0001536,
+		    1.0/24490000.0,0.0];
+
+/// Moon's mean anomaly
+const EMP : [R;5] = [134.9633964,477198.8675055,0.0087414,
+		     1.0/69699.0,-1.0/14712000.0];
+
+/// Mean distance of the Moon from its ascending node
+const F : [R;5] = [93.2720950,483202.0175233,-0.0036539,
+		   1.0/3526000.0,1.0/863310000.0];
+
+/// Meeus A_1, due to Venus (deg)
+const A1 : [R;2] = [119.75,131.849];
+
+/// Meeus A_2, due to Jupiter (deg)
+const A2 : [R;2] = [53.09,479264.290];
+
+/// Meeus A_3, due to sidereal motion of the Moon in longitude (deg)
+const A3 : [R;2] = [313.45,481266.484];
+
+// Coefficients for Meeus "additive terms" (deg)
+const AL : [R;3] = [0.003958,0.001962,0.000318];
+const AB : [R;6] = [-0.002235,0.000382,0.000175,0.000175,0.000127,-0.000115];
+
+/// Fixed term in distance (m)
+const R0 : R = 385000560.0;
+
+/// Coefficients for (dimensionless) E factor
+const E : [R;2] = [-0.002516,-0.0000074];
+
+/// Coefficients for Moon longitude and distance series: multiples of
+/// D, M, M', F, then the longitude (deg) and distance (m) coefficients
+const TLR : [(i8,i8,i8,i8,R,R);60] = [
+    (0, 0, 1, 0, 6.288774, -20905355.0),
+    (2, 0,-1, 0, 1.274027,  -3699111.0),
+    (2, 0, 0, 0, 0.658314,  -2955968.0),
+    (0, 0, 2, 0, 0.213618,   -569925.0),
+    (0, 1, 0, 0,-0.185116,     48888.0),
+    (0, 0, 0, 2,-0.114332,     -3149.0),
+    (2, 0,-2, 0, 0.058793,    246158.0),
+    (2,-1,-1, 0, 0.057066,   -152138.0),
+    (2, 0, 1, 0, 0.053322,   -170733.0),
+    (2,-1, 0, 0, 0.045758,   -204586.0),
+    (0, 1,-1, 0,-0.040923,   -129620.0),
+    (1, 0, 0, 0,-0.034720,    108743.0),
+    (0, 1, 1, 0,-0.030383,    104755.0),
+    (2, 0, 0,-2, 0.015327,     10321.0),
+    (0, 0, 1, 2,-0.012528,         0.0),
+    (0, 0, 1,-2, 0.010980,     79661.0),
+    (4, 0,-1, 0, 0.010675,    -34782.0),
+    (0, 0, 3, 0, 0.010034,    -23210.0),
+    (4, 0,-2, 0, 0.008548,    -21636.0),
+    (2, 1,-1, 0,-0.007888,     24208.0),
+    (2, 1, 0, 0,-0.006766,     30824.0),
+    (1, 0,-1, 0,-0.005163,     -8379.0),
+    (1, 1, 0, 0, 0.004987,    -16675.0),
+    (2,-1, 1, 0, 0.004036,    -12831.0),
+    (2, 0, 2, 0, 0.003994,    -10445.0),
+    (4, 0, 0, 0, 0.003861,    -11650.0),
+    (2, 0,-3, 0, 0.003665,     14403.0),
+    (0, 1,-2, 0,-0.002689,     -7003.0),
+    (2, 0,-1, 2,-0.002602,         0.0),
+    (2,-1,-2, 0, 0.002390,     10056.0),
+    (1, 0, 1, 0,-0.002348,      6322.0),
+    (2,-2, 0, 0, 0.002236,     -9884.0),
+    (0, 1, 2, 0,-0.002120,      5751.0),
+    (0, 2, 0, 0,-0.002069,         0.0),
+    (2,-2,-1, 0, 0.002048,     -4950.0),
+    (2, 0, 1,-2,-0.001773,      4130.0),
+    (2, 0, 0, 2,-0.001595,         0.0),
+    (4,-1,-1, 0, 0.001215,     -3958.0),
+    (0, 0, 2, 2,-0.001110,         0.0),
+    (3, 0,-1, 0,-0.000892,      3258.0),
+    (2, 1, 1, 0,-0.000810,      2616.0),
+    (4,-1,-2, 0, 0.000759,     -1897.0),
+    (0, 2,-1, 0,-0.000713,     -2117.0),
+    (2, 2,-1, 0,-0.000700,      2354.0),
+    (2, 1,-2, 0, 0.000691,         0.0),
+    (2,-1, 0,-2, 0.000596,         0.0),
+    (4, 0, 1, 0, 0.000549,     -1423.0),
+    (0, 0, 4, 0, 0.000537,     -1117.0),
+    (4,-1, 0, 0, 0.000520,     -1571.0),
+    (1, 0,-2, 0,-0.000487,     -1739.0),
+    (2, 1, 0,-2,-0.000399,         0.0),
+    (0, 0, 2,-2,-0.000381,     -4421.0),
+    (1, 1, 1, 0, 0.000351,         0.0),
+    (3, 0,-2, 0,-0.000340,         0.0),
+    (4, 0,-3, 0, 0.000330,         0.0),
+    (2,-1, 2, 0, 0.000327,         0.0),
+    (0, 2, 1, 0,-0.000323,      1165.0),
+    (1, 1,-1, 0, 0.000299,         0.0),
+    (2, 0, 3, 0, 0.000294,         0.0),
+    (2, 0,-1,-2, 0.000000,      8752.0)
+];
+
+/// Coefficients for Moon latitude series: multiples of D, M, M', F,
+/// then the latitude coefficient (deg)
+const TB : [(i8,i8,i8,i8,R);60] = [
+    (0, 0, 0, 1, 5.128122),
+    (0, 0, 1, 1, 0.280602),
+    (0, 0, 1,-1, 0.277693),
+    (2, 0, 0,-1, 0.173237),
+    (2, 0,-1, 1, 0.055413),
+    (2, 0,-1,-1, 0.046271),
+    (2, 0, 0, 1, 0.032573),
+    (0, 0, 2, 1, 0.017198),
+    (2, 0, 1,-1, 0.009266),
+    (0, 0, 2,-1, 0.008822),
+    (2,-1, 0,-1, 0.008216),
+    (2, 0,-2,-1, 0.004324),
+    (2, 0, 1, 1, 0.004200),
+    (2, 1, 0,-1,-0.003359),
+    (2,-1,-1, 1, 0.002463),
+    (2,-1, 0, 1, 0.002211),
+    (2,-1,-1,-1, 0.002065),
+    (0, 1,-1,-1,-0.001870),
+    (4, 0,-1,-1, 0.001828),
+    (0, 1, 0, 1,-0.001794),
+    (0, 0, 0, 3,-0.001749),
+    (0, 1,-1, 1,-0.001565),
+    (1, 0, 0, 1,-0.001491),
+    (0, 1, 1, 1,-0.001475),
+    (0, 1, 1,-1,-0.001410),
+    (0, 1, 0,-1,-0.001344),
+    (1, 0, 0,-1,-0.001335),
+    (0, 0, 3, 1, 0.001107),
+    (4, 0, 0,-1, 0.001021),
+    (4, 0,-1, 1, 0.000833),
+    (0, 0, 1,-3, 0.000777),
+    (4, 0,-2, 1, 0.000671),
+    (2, 0, 0,-3, 0.000607),
+    (2, 0, 2,-1, 0.000596),
+    (2,-1, 1,-1, 0.000491),
+    (2, 0,-2, 1,-0.000451),
+    (0, 0, 3,-1, 0.000439),
+    (2, 0, 2, 1, 0.000422),
+    (2, 0,-3,-1, 0.000421),
+    (2, 1,-1, 1,-0.000366),
+    (2, 1, 0, 1,-0.000351),
+    (4, 0, 0, 1, 0.000331),
+    (2,-1, 1, 1, 0.000315),
+    (2,-2, 0,-1, 0.000302),
+    (0, 0, 1, 3,-0.000283),
+    (2, 1, 1,-1,-0.000229),
+    (1, 1, 0,-1, 0.000223),
+    (1, 1, 0, 1, 0.000223),
+    (0, 1,-2,-1,-0.000220),
+    (2, 1,-1,-1,-0.000220),
+    (1, 0, 1, 1,-0.000185),
+    (2,-1,-2,-1, 0.000181),
+    (0, 1, 2, 1,-0.000177),
+    (4, 0,-2,-1, 0.000176),
+    (4,-1,-1,-1, 0.000166),
+    (1, 0, 1,-1,-0.000164),
+    (4, 0, 1,-1, 0.000132),
+    (1, 0,-1,-1,-0.000119),
+    (4,-1, 0,-1, 0.000115),
+    (2,-2, 0, 1, 0.000107)
+];
+
+/// Fundamental argument (radians) and its rate (radians per Julian
+/// century) from a quartic in T (degrees)
+fn quartic(c:&[R;5],t:R)->(R,R) {
+    (DEGREE * ( c[0] + ( c[1] + ( c[2] + ( c[3] + c[4]*t )*t )*t )*t ).rem_euclid(360.0),
+     DEGREE * ( c[1] + ( c[2]*2.0 + ( c[3]*3.0 + c[4]*4.0*t )*t )*t ))
+}
+
+/// E factor and its derivative for a multiple of M
+fn e_factor(nem:i8,e:R,de:R)->(R,R) {
+    match nem.abs() {
+	1 => (e,de),
+	2 => (e*e,2.0*e*de),
+	_ => (1.0,0.0)
+    }
+}
+
+/// Approximate geocentric position and velocity of the Moon, with
+/// respect to the GCRS (AU, AU/day).
+///
+/// Source: moon98.for
+pub fn moon_pv(tt@TT((date1,date2)):TT)->PosVel {
+    // Centuries since J2000.
+    let t = ( ( date1 - DJ00 ) + date2 ) / DJC;
+
+    // Fundamental arguments (radians) and derivatives (radians per
+    // Julian century) for the current epoch.
+    let (elp,delp) = quartic(&ELP,t);
+    let (d,dd) = quartic(&D,t);
+    let (em,dem) = quartic(&EM,t);
+    let (emp,demp) = quartic(&EMP,t);
+    let (f,df) = quartic(&F,t);
+
+    // Meeus further arguments.
+    let a1 = DEGREE * ( A1[0] + A1[1]*t );
+    let da1 = DEGREE * A1[1];
+    let a2 = DEGREE * ( A2[0] + A2[1]*t );
+    let da2 = DEGREE * A2[1];
+    let a3 = DEGREE * ( A3[0] + A3[1]*t );
+    let da3 = DEGREE * A3[1];
+
+    // E-factor.
+    let e = 1.0 + ( E[0] + E[1]*t )*t;
+    let de = E[0] + 2.0*E[1]*t;
+
+    // Use the Meeus additive terms (deg) to start off the summations.
+    let elpmf = elp - f;
+    let delpmf = delp - df;
+    let mut vel = AL[0]*sin(a1) + AL[1]*sin(elpmf) + AL[2]*sin(a2);
+    let mut vdel = AL[0]*cos(a1)*da1 + AL[1]*cos(elpmf)*delpmf + AL[2]*cos(a2)*da2;
+
+    let mut vr = 0.0;
+    let mut vdr = 0.0;
+
+    let a1mf = a1 - f;
+    let da1mf = da1 - df;
+    let a1pf = a1 + f;
+    let da1pf = da1 + df;
+    let dlpmp = elp - emp;
+    let slpmp = elp + emp;
+    let mut vb = AB[0]*sin(elp) + AB[1]*sin(a3) + AB[2]*sin(a1mf) +
+	AB[3]*sin(a1pf) + AB[4]*sin(dlpmp) + AB[5]*sin(slpmp);
+    let mut vdb = AB[0]*cos(elp)*delp + AB[1]*cos(a3)*da3 +
+	AB[2]*cos(a1mf)*da1mf + AB[3]*cos(a1pf)*da1pf +
+	AB[4]*cos(dlpmp)*(delp - demp) + AB[5]*cos(slpmp)*(delp + demp);
+
+    // Series expansions, smallest terms first.
+    let argument = |nd:i8,nem:i8,nemp:i8,nf:i8| {
+	let (nd,nem,nemp,nf) = (nd as R,nem as R,nemp as R,nf as R);
+	(nd*d + nem*em + nemp*emp + nf*f,
+	 nd*dd + nem*dem + nemp*demp + nf*df)
+    };
+
+    // Longitude and distance plus derivatives.
+    for &(nd,nem,nemp,nf,coefl,coefr) in TLR.iter().rev() {
+	let (en,den) = e_factor(nem,e,de);
+	let (arg,darg) = argument(nd,nem,nemp,nf);
+	let (farg,garg) = (sin(arg),cos(arg));
+	vel += coefl*en*farg;
+	vdel += coefl*( den*farg + en*garg*darg );
+	vr += coefr*en*garg;
+	vdr += coefr*( den*garg - en*farg*darg );
+    }
+    let el = elp + DEGREE*vel;
+    let del = ( delp + DEGREE*vdel ) / DJC;
+    let r = ( vr + R0 ) / AUM;
+    let dr = vdr / AUM / DJC;
+
+    // Latitude plus derivative.
+    for &(nd,nem,nemp,nf,coefb) in TB.iter().rev() {
+	let (en,den) = e_factor(nem,e,de);
+	let (arg,darg) = argument(nd,nem,nemp,nf);
+	let farg = sin(arg);
+	vb += coefb*en*farg;
+	vdb += coefb*( den*farg + en*cos(arg)*darg );
+    }
+    let b = vb*DEGREE;
+    let db = vdb*DEGREE / DJC;
+
+    // Longitude, latitude to x, y, z (AU).
+    let pv = PosVel::from_spherical(el,b,r,del,db,dr); // s2pv
+
+    // IAU 2006 Fukushima-Williams bias+precession angles.
+    let (gamb,phib,psib,_) = frames::fukushima_williams_angles(tt); // pfw06
+
+    // Mean ecliptic coordinates to GCRS rotation matrix.
+    let rm = Mat3::rotation(2,-gamb)
+	.compose(&Mat3::rotation(0,-phib))
+	.compose(&Mat3::rotation(2,psib));
+
+    // Rotate the Moon position and velocity into GCRS.
+    PosVel { p:rm.mul_vec(pv.p),v:rm.mul_vec(pv.v) } // rxpv
+}
@@ -0,0 +1,294 @@
+// Unit quaternions for attitude representation.  A quaternion
+// represents the same frame rotation as the matrices used in the rest
+// of the crate: Quaternion::axis(n,θ) corresponds to
+// Mat3::rotation(n,θ), and products follow the matrix order, that is
+// (a*b).to_matrix() = a.to_matrix()·b.to_matrix().  The scalar part is
+// w.
+
+use std::ops::Mul;
+
+use crate::{
+    common::*,
+    linalg::{Vector3,Rotation3}
+};
+
+#[derive(Copy,Clone,Debug,PartialEq)]
+pub struct Quaternion {
+    pub w:R,
+    pub x:R,
+    pub y:R,
+    pub z:R
+}
+
+/// Euler angle sequences.  The sequence IJK with angles (a,b,c) is the
+/// rotation by a about axis I, followed by b about the new axis J and c
+/// about the newest axis K, i.e. the matrix Rk(c)·Rj(b)·Ri(a).
+#[derive(Copy,Clone,Debug,PartialEq,Eq)]
+pub enum EulerSequence {
+    Xyz,Xzy,Yxz,Yzx,Zxy,Zyx,
+    Xyx,Xzx,Yxy,Yzy,Zxz,Zyz
+}
+
+impl EulerSequence {
+    pub const ALL : [Self;12] = [
+	Self::Xyz,Self::Xzy,Self::Yxz,Self::Yzx,Self::Zxy,Self::Zyx,
+	Self::Xyx,Self::Xzx,Self::Yxy,Self::Yzy,Self::Zxz,Self::Zyz
+    ];
+
+    /// The three rotation axes, 0, 1 or 2 for x, y or z
+    pub fn axes(self)->[usize;3] {
+	match self {
+	    Self::Xyz => [0,1,2],
+	    Self::Xzy => [0,2,1],
+	    Self::Yxz => [1,0,2],
+	    Self::Yzx => [1,2,0],
+	    Self::Zxy => [2,0,1],
+	    Self::Zyx => [2,1,0],
+	    Self::Xyx => [0,1,0],
+	    Self::Xzx => [0,2,0],
+	    Self::Yxy => [1,0,1],
+	    Self::Yzy => [1,2,1],
+	    Self::Zxz => [2,0,2],
+	    Self::Zyz => [2,1,2]
+	}
+    }
+
+    /// Whether the first and last axes are the same
+    pub fn is_proper(self)->bool {
+	let [i,_,k] = self.axes();
+	i == k
+    }
+}
+
+/// Below this value of the cosine (Tait-Bryan) or sine (proper Euler)
+/// of the middle angle, the first and last angles are not separable and
+/// the last one is set to zero.
+const GIMBAL_LOCK : R = 1e-12;
+
+impl Quaternion {
+    pub fn new(w:R,x:R,y:R,z:R)->Self {
+	Self { w,x,y,z }
+    }
+
+    pub fn identity()->Self {
+	Self::new(1.0,0.0,0.0,0.0)
+    }
+
+    /// Rotation of the reference frame about an axis (0, 1 or 2 for x,
+    /// y or z)
+    pub fn axis(axis:usize,theta:R)->Self {
+	let (s,c) = (sin(theta/2.0),cos(theta/2.0));
+	let mut v = [0.0;3];
+	v[axis] = s;
+	Self::new(c,v[0],v[1],v[2])
+    }
+
+    pub fn vector(&self)->Vec3 {
+	[self.x,self.y,self.z]
+    }
+
+    pub fn norm(&self)->R {
+	sqrt(self.dot(self))
+    }
+
+    pub fn dot(&self,b:&Self)->R {
+	self.w*b.w + self.x*b.x + self.y*b.y + self.z*b.z
+    }
+
+    pub fn normalize(&self)->Self {
+	let n = self.norm();
+	Self::new(self.w/n,self.x/n,self.y/n,self.z/n)
+    }
+
+    /// The inverse rotation
+    pub fn conjugate(&self)->Self {
+	Self::new(self.w,-self.x,-self.y,-self.z)
+    }
+
+    /// Hamilton product a⊗b
+    pub fn hamilton(a:&Self,b:&Self)->Self {
+	let av = a.vector();
+	let bv = b.vector();
+	let [x,y,z] = bv.scale(a.w).add(av.scale(b.w)).add(av.cross(bv));
+	Self::new(a.w*b.w - av.dot(bv),x,y,z)
+    }
+
+    /// Rotation matrix (frame rotation) of the quaternion
+    pub fn to_matrix(&self)->Mat3 {
+	let Self { w,x,y,z } = self.normalize();
+	[[w*w + x*x - y*y - z*z, 2.0*(x*y + w*z),       2.0*(x*z - w*y)],
+	 [2.0*(x*y - w*z),       w*w - x*x + y*y - z*z, 2.0*(y*z + w*x)],
+	 [2.0*(x*z + w*y),       2.0*(y*z - w*x),       w*w - x*x - y*y + z*z]]
+    }
+
+    /// Quaternion of a rotation matrix, with a non-negative scalar
+    /// part (Shepperd's method)
+    pub fn from_matrix(m:&Mat3)->Self {
+	let tr = m[0][0] + m[1][1] + m[2][2];
+	let q =
+	    if tr >= m[0][0] && tr >= m[1][1] && tr >= m[2][2] {
+		let w = 0.5 * sqrt(1.0 + tr);
+		let f = 0.25 / w;
+		Self::new(w,
+			  f * (m[1][2] - m[2][1]),
+			  f * (m[2][0] - m[0][2]),
+			  f * (m[0][1] - m[1][0]))
+	    } else if m[0][0] >= m[1][1] && m[0][0] >= m[2][2] {
+		let x = 0.5 * sqrt(1.0 + m[0][0] - m[1][1] - m[2][2]);
+		let f = 0.25 / x;
+		Self::new(f * (m[1][2] - m[2][1]),
+			  x,
+			  f * (m[0][1] + m[1][0]),
+			  f * (m[0][2] + m[2][0]))
+	    } else if m[1][1] >= m[2][2] {
+		let y = 0.5 * sqrt(1.0 - m[0][0] + m[1][1] - m[2][2]);
+		let f = 0.25 / y;
+		Self::new(f * (m[2][0] - m[0][2]),
+			  f * (m[0][1] + m[1][0]),
+			  y,
+			  f * (m[1][2] + m[2][1]))
+	    } else {
+		let z = 0.5 * sqrt(1.0 - m[0][0] - m[1][1] + m[2][2]);
+		let f = 0.25 / z;
+		Self::new(f * (m[0][1] - m[1][0]),
+			  f * (m[0][2] + m[2][0]),
+			  f * (m[1][2] + m[2][1]),
+			  z)
+	    };
+	let q = q.normalize();
+	if q.w < 0.0 { q * -1.0 } else { q }
+    }
+
+    /// Quaternion of a rotation vector, consistent with rv2m
+    pub fn from_rotation_vector(v:Vec3)->Self {
+	let phi = v.norm();
+	if phi == 0.0 {
+	    return Self::identity();
+	}
+	let [x,y,z] = v.scale(sin(phi/2.0) / phi);
+	Self::new(cos(phi/2.0),x,y,z)
+    }
+
+    /// Rotation vector of the quaternion, consistent with rm2v
+    pub fn to_rotation_vector(&self)->Vec3 {
+	let q = if self.w < 0.0 { *self * -1.0 } else { *self };
+	let s = q.vector().norm();
+	if s == 0.0 {
+	    return [0.0;3];
+	}
+	q.vector().scale(2.0 * atan2(s,q.w) / s)
+    }
+
+    /// Quaternion of the Euler sequence with angles (a,b,c)
+    pub fn from_euler(seq:EulerSequence,[a,b,c]:[R;3])->Self {
+	let [i,j,k] = seq.axes();
+	Self::axis(k,c) * Self::axis(j,b) * Self::axis(i,a)
+    }
+
+    /// Euler angles (a,b,c) of the rotation for the given sequence.
+    /// The first and last angles are in ]-π,π]; the middle angle is in
+    /// [-π/2,π/2] for Tait-Bryan sequences and in [0,π] for proper
+    /// Euler sequences.  At gimbal lock the last angle is set to zero.
+    pub fn to_euler(&self,seq:EulerSequence)->[R;3] {
+	let m = self.to_matrix();
+	let [i,j,_] = seq.axes();
+	let k = 3 - i - j;
+
+	// Parity of the permutation (i,j,k)
+	let e = if (j + 3 - i) % 3 == 1 { 1.0 } else { -1.0 };
+
+	let (b,locked) =
+	    if seq.is_proper() {
+		let sb = sqrt(sq(m[i][j]) + sq(m[i][k]));
+		(atan2(sb,m[i][i]),sb < GIMBAL_LOCK)
+	    } else {
+		let cb = sqrt(sq(m[k][j]) + sq(m[k][k]));
+		(atan2(e * m[k][i],cb),cb < GIMBAL_LOCK)
+	    };
+
+	if locked {
+	    return [atan2(e * m[j][k],m[j][j]),b,0.0];
+	}
+
+	if seq.is_proper() {
+	    [atan2(m[i][j],-e * m[i][k]),
+	     b,
+	     atan2(m[j][i],e * m[k][i])]
+	} else {
+	    [atan2(-e * m[k][j],m[k][k]),
+	     b,
+	     atan2(-e * m[j][i],m[i][i])]
+	}
+    }
+
+    /// Rotate a vector, i.e. to_matrix()·v
+    pub fn rotate(&self,v:Vec3)->Vec3 {
+	self.to_matrix().mul_vec(v)
+    }
+
+    /// Angle of the rotation between this attitude and another, in
+    /// [0,π]
+    pub fn angle_to(&self,b:&Self)->R {
+	let d = Self::hamilton(&self.normalize().conjugate(),&b.normalize());
+	2.0 * atan2(d.vector().norm(),abs(d.w))
+    }
+
+    /// Spherical linear interpolation from self (t=0) to b (t=1),
+    /// along the shortest path
+    pub fn slerp(&self,b:&Self,t:R)->Self {
+	let a = self.normalize();
+	let mut b = b.normalize();
+	let mut d = a.dot(&b);
+	if d < 0.0 {
+	    b = b * -1.0;
+	    d = -d;
+	}
+	let (ka,kb) =
+	    if d > 1.0 - 1e-12 {
+		// Nearly identical: linear interpolation
+		(1.0 - t,t)
+	    } else {
+		let theta = acos(d);
+		let s = sin(theta);
+		(sin((1.0 - t) * theta) / s,sin(t * theta) / s)
+	    };
+	Self::new(ka*a.w + kb*b.w,
+		  ka*a.x + kb*b.x,
+		  ka*a.y + kb*b.y,
+		  ka*a.z + kb*b.z).normalize()
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Self;
+
+    /// Composition in matrix order
+    fn mul(self,b:Self)->Self {
+	Self::hamilton(&b,&self)
+    }
+}
+
+impl Mul<R> for Quaternion {
+    type Output = Self;
+    fn mul(self,c:R)->Self {
+	Self::new(c*self.w,c*self.x,c*self.y,c*self.z)
+    }
+}
+
+impl From<Quaternion> for Mat3 {
+    fn from(q:Quaternion)->Self {
+	q.to_matrix()
+    }
+}
+
+impl From<Rotation3> for Quaternion {
+    fn from(r:Rotation3)->Self {
+	Self::from_matrix(&r.into())
+    }
+}
+
+impl From<Quaternion> for Rotation3 {
+    fn from(q:Quaternion)->Self {
+	Rotation3::from_rotation_vector(Vector3(q.to_rotation_vector()))
+    }
+}
@@ -0,0 +1,524 @@
+// Star catalogue conversions: space motion, FK5 to Hipparcos and FK4
+// to FK5.  Catalogue coordinates are (RA,Dec,dRA/dt,dDec/dt,px,rv) with
+// angles in radians, proper motions in radians per (Julian or
+// Besselian) year, parallax in arcseconds and radial velocity in km/s,
+// positive receding.
+
+use crate::{
+    common::*,
+    time::{TDB,D2S,DJY,DJ00},
+    earth::{AUM,PosVel},
+    astrometry::{self,Astrom}
+};
+
+custom_error!{pub StarError
+	      SuperluminalSpeed = "superluminal speed",
+	      NullPosition      = "null position vector"
+}
+
+/// Warnings issued when converting a catalogue entry to a space motion
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub struct StarPvWarnings {
+    /// The parallax was too small and a minimum value was used
+    pub distance_overridden:bool,
+
+    /// The velocity exceeded half the speed of light and was set to
+    /// zero
+    pub excessive_speed:bool,
+
+    /// The relativistic correction failed to converge
+    pub no_convergence:bool
+}
+
+/// Smallest parallax allowed [arcsec]
+const PXMIN : R = 1e-7;
+
+/// Largest speed allowed, as a fraction of c
+const VMAX : R = 0.5;
+
+/// Maximum number of iterations for the relativistic correction
+const IMAX : usize = 100;
+
+/// Speed of light [AU/d]
+const DC : R = D2S * CMPS / AUM;
+
+/// Convert star catalog coordinates to position+velocity vector.  The
+/// position is in AU and the velocity in AU/day.
+///
+/// Source: starpv.for
+pub fn star_pv(ra:R,dec:R,pmr:R,pmd:R,px:R,rv:R)->(PosVel,StarPvWarnings) {
+    let mut warnings = StarPvWarnings::default();
+
+    // Distance (AU).
+    let w =
+	if px >= PXMIN {
+	    px
+	} else {
+	    warnings.distance_overridden = true;
+	    PXMIN
+	};
+    let r = DR2AS / w;
+
+    // Radial speed (AU/day).
+    let rd = D2S * rv * 1e3 / AUM;
+
+    // Proper motion (radian/day).
+    let rad = pmr / DJY;
+    let decd = pmd / DJY;
+
+    // To pv-vector (AU,AU/day).
+    let mut pv = PosVel::from_spherical(ra,dec,r,rad,decd,rd); // s2pv
+
+    // If excessive velocity, arbitrarily set it to zero.
+    if pv.v.norm() / DC > VMAX {
+	pv.v = [0.0;3];
+	warnings.excessive_speed = true;
+    }
+
+    // Isolate the radial component of the velocity (AU/day).
+    let x = pv.p.normalize();
+    let vsr = x.dot(pv.v);
+    let usr = x.scale(vsr);
+
+    // Isolate the transverse component of the velocity (AU/day).
+    let ust = pv.v.sub(usr);
+    let vst = ust.norm();
+
+    // Special-relativity dimensionless parameters.
+    let betsr = vsr / DC;
+    let betst = vst / DC;
+
+    // Determine the inertial-to-observed relativistic correction terms.
+    let mut bett = betst;
+    let mut betr = betsr;
+    let (mut d,mut del) = (0.0,0.0);
+    let (mut od,mut odel,mut odd,mut oddel) = (0.0,0.0,0.0,0.0);
+    let mut converged = false;
+    for i in 0..IMAX {
+	d = 1.0 + betr;
+	let w = betr*betr + bett*bett;
+	del = - w / ( sqrt(1.0 - w) + 1.0 );
+	betr = d*betsr + del;
+	bett = d*betst;
+	if i > 0 {
+	    let dd = abs(d - od);
+	    let ddel = abs(del - odel);
+	    if i > 1 && dd >= odd && ddel >= oddel {
+		converged = true;
+		break;
+	    }
+	    odd = dd;
+	    oddel = ddel;
+	}
+	od = d;
+	odel = del;
+    }
+    if !converged {
+	warnings.no_convergence = true;
+    }
+
+    // Replace observed radial velocity with inertial value.
+    let w = if betsr != 0.0 { d + del / betsr } else { 1.0 };
+    let ur = usr.scale(w);
+
+    // Replace observed tangential velocity with inertial value.
+    let ut = ust.scale(d);
+
+    // Combine the two to obtain the inertial space velocity.
+    pv.v = ur.add(ut);
+
+    (pv,warnings)
+}
+
+/// Convert star position+velocity vector to catalog coordinates.
+/// Returns (RA,Dec,dRA/dt,dDec/dt,px,rv).
+///
+/// Source: pvstar.for
+pub fn pv_star(pv:&PosVel)->Result<(R,R,R,R,R,R),StarError> {
+    // Isolate the radial component of the velocity (AU/day, inertial).
+    let x = pv.p.normalize();
+    let vr = x.dot(pv.v);
+    let ur = x.scale(vr);
+
+    // Isolate the transverse component of the velocity (AU/day,
+    // inertial).
+    let ut = pv.v.sub(ur);
+    let vt = ut.norm();
+
+    // Special-relativity dimensionless parameters.
+    let bett = vt / DC;
+    let betr = vr / DC;
+
+    // The inertial-to-observed correction terms.
+    let d = 1.0 + betr;
+    let w = betr*betr + bett*bett;
+    if d == 0.0 || w >= 1.0 {
+	return Err(StarError::SuperluminalSpeed);
+    }
+    let del = - w / ( sqrt(1.0 - w) + 1.0 );
+
+    // Scale inertial radial velocity vector into observed.
+    let w = if betr != 0.0 { ( betr - del ) / ( betr * d ) } else { 1.0 };
+    let ur = ur.scale(w);
+
+    // Scale inertial tangential velocity vector into observed.
+    let ut = ut.scale(1.0 / d);
+
+    // Combine the two to obtain the observed velocity vector (AU/day).
+    let obs = PosVel { p:pv.p,v:ur.add(ut) };
+
+    // Cartesian to spherical.
+    let (a,dec,r,rad,decd,rd) = obs.to_spherical(); // pv2s
+    if r == 0.0 {
+	return Err(StarError::NullPosition);
+    }
+
+    Ok((anp(a),
+	dec,
+	rad * DJY,
+	decd * DJY,
+	DR2AS / r,
+	1e-3 * rd * AUM / D2S))
+}
+
+/// A star catalogue entry: position, proper motion, parallax and
+/// radial velocity at a given epoch, in the units of the module
+#[derive(Copy,Clone,Debug)]
+pub struct CatalogueEntry {
+    /// Right ascension [rad]
+    pub ra:R,
+
+    /// Declination [rad]
+    pub dec:R,
+
+    /// Proper motion in RA, dRA/dt [rad/Julian year]
+    pub pmr:R,
+
+    /// Proper motion in Dec, dDec/dt [rad/Julian year]
+    pub pmd:R,
+
+    /// Parallax [arcsec]
+    pub px:R,
+
+    /// Radial velocity [km/s, positive receding]
+    pub rv:R,
+
+    /// Epoch of the position (TDB)
+    pub epoch:TDB
+}
+
+/// Smallest parallax used by propagate_safe() [arcsec]
+const PXMIN_SAFE : R = 5e-7;
+
+/// Factor giving a maximum transverse speed of about 1% of c, used by
+/// propagate_safe()
+const F_SAFE : R = 326.0;
+
+impl CatalogueEntry {
+    /// Space motion of the star (AU, AU/day), see star_pv().
+    pub fn to_pv(&self)->(PosVel,StarPvWarnings) {
+	star_pv(self.ra,self.dec,self.pmr,self.pmd,self.px,self.rv) // starpv
+    }
+
+    /// Catalogue entry at EPOCH of a space motion (AU, AU/day), see
+    /// pv_star().
+    pub fn from_pv(pv:&PosVel,epoch:TDB)->Result<Self,StarError> {
+	let (ra,dec,pmr,pmd,px,rv) = pv_star(pv)?; // pvstar
+	Ok(Self { ra,dec,pmr,pmd,px,rv,epoch })
+    }
+
+    /// Star proper motion: update the entry for space motion to the
+    /// given epoch, taking into account light time.
+    ///
+    /// Source: starpm.for
+    pub fn propagate(&self,epoch:TDB)->Result<(Self,StarPvWarnings),StarError> {
+	let TDB((ep1a,ep1b)) = self.epoch;
+	let TDB((ep2a,ep2b)) = epoch;
+
+	// RA,Dec etc. at the "before" epoch to space motion pv-vector.
+	let (pv1,warnings) = self.to_pv(); // starpv
+
+	// Light time when observed (days).
+	let tl1 = pv1.p.norm() / DC;
+
+	// Time interval, "before" to "after" (days).
+	let dt = ( ep2a - ep1a ) + ( ep2b - ep1b );
+
+	// Move star along track from the "before" observed position to the
+	// "after" geometric position.
+	let p = pv1.p.add(pv1.v.scale(dt + tl1)); // pvu
+
+	// From this geometric position, deduce the observed light time
+	// (days) at the "after" epoch (with theoretically unneccessary
+	// error check).
+	let r2 = p.dot(p);
+	let rdv = p.dot(pv1.v);
+	let v2 = pv1.v.dot(pv1.v);
+	let c2mv2 = DC*DC - v2;
+	if c2mv2 <= 0.0 {
+	    return Err(StarError::SuperluminalSpeed);
+	}
+	let tl2 = ( - rdv + sqrt(rdv*rdv + c2mv2*r2) ) / c2mv2;
+
+	// Move the position along track from the observed place at the
+	// "before" epoch to the observed place at the "after" epoch.
+	let pv2 = PosVel { p:pv1.p.add(pv1.v.scale(dt + ( tl1 - tl2 ))),v:pv1.v }; // pvu
+
+	// Space motion pv-vector to RA,Dec etc. at the "after" epoch.
+	Ok((Self::from_pv(&pv2,epoch)?,warnings)) // pvstar
+    }
+
+    /// Star proper motion: update the entry for space motion to the
+    /// given epoch, with special handling to handle the zero parallax
+    /// case.  The parallax is increased if needed to keep the
+    /// transverse speed below about 1% of c, which is then reported
+    /// with the distance_overridden warning.
+    ///
+    /// Source: pmsafe.for
+    pub fn propagate_safe(&self,epoch:TDB)->Result<(Self,StarPvWarnings),StarError> {
+	// Proper motion in one year (radians).
+	let pm = separation_spherical(self.ra,self.dec,
+				      self.ra + self.pmr,self.dec + self.pmd); // seps
+
+	// Override the parallax to reduce the chances of a warning status.
+	let px = self.px.max(pm * F_SAFE).max(PXMIN_SAFE);
+	let overridden = px != self.px;
+
+	// Carry out the transformation using the modified parallax.
+	let (entry,mut warnings) = Self { px,..*self }.propagate(epoch)?; // starpm
+
+	// Revise the status.
+	warnings.distance_overridden |= overridden;
+	Ok((entry,warnings))
+    }
+
+    /// Coordinate direction of the star (BCRS unit vector) at the TDB
+    /// date for an observer at POB (SSB to observer, AU), applying the
+    /// linear space motion and the parallax.
+    ///
+    /// Source: pmpx.for
+    pub fn direction(&self,TDB((d1,d2)):TDB,pob:Vec3)->Vec3 {
+	let TDB((e1,e2)) = self.epoch;
+	let pmt = ( ( d1 - e1 ) + ( d2 - e2 ) ) / DJY;
+	astrometry::proper_motion_parallax(self.ra,self.dec,self.pmr,self.pmd,
+					   self.px,self.rv,pmt,pob) // pmpx
+    }
+
+    /// CIRS right ascension and declination (RI,DI) of the star, given
+    /// the star-independent astrometry parameters for the date.  The
+    /// proper motion time interval is counted from the epoch of the
+    /// entry.
+    ///
+    /// Source: atciq.for
+    pub fn cirs_quick(&self,astrom:&Astrom)->(R,R) {
+	let TDB((e1,e2)) = self.epoch;
+	let astrom = Astrom {
+	    pmt:astrom.pmt - ( ( e1 - DJ00 ) + e2 ) / DJY,
+	    ..astrom.clone()
+	};
+	astrometry::icrs_to_cirs_quick(self.ra,self.dec,self.pmr,self.pmd,
+				       self.px,self.rv,&astrom) // atciq
+    }
+}
+
+/// FK5 to Hipparcos rotation and spin.  Returns the orientation R5H of
+/// the Hipparcos frame with respect to FK5 and the spin S5H of
+/// Hipparcos with respect to FK5 [rad/year].
+///
+/// Source: fk5hip.for
+pub fn fk5_hipparcos()->(Mat3,Vec3) {
+    // FK5 wrt Hipparcos orientation and spin (radians, radians/year)
+    const EPX : R = -19.9e-3 * AS2R;
+    const EPY : R =  -9.1e-3 * AS2R;
+    const EPZ : R =  22.9e-3 * AS2R;
+
+    const OMX : R = -0.30e-3 * AS2R;
+    const OMY : R =  0.60e-3 * AS2R;
+    const OMZ : R =  0.70e-3 * AS2R;
+
+    (rotation_vector_to_matrix([EPX,EPY,EPZ]), // rv2m
+     [OMX,OMY,OMZ])
+}
+
+/// Transform FK5 (J2000.0) star data into the Hipparcos system.
+///
+/// Source: fk52h.for
+pub fn fk5_to_hipparcos(r5:R,d5:R,dr5:R,dd5:R,px5:R,rv5:R)
+			->Result<(R,R,R,R,R,R),StarError> {
+    // FK5 barycentric position/velocity pv-vector (normalized).
+    let (pv5,_) = star_pv(r5,d5,dr5,dd5,px5,rv5); // starpv
+
+    // FK5 to Hipparcos orientation matrix and spin vector.
+    let (r5h,s5h) = fk5_hipparcos(); // fk5hip
+
+    // Make spin units per day instead of per year.
+    let s5h = s5h.scale(1.0 / DJY);
+
+    // Orient the FK5 position into the Hipparcos system.
+    let p = r5h.mul_vec(pv5.p);
+
+    // Apply spin to the position giving an extra space motion
+    // component, add this to the FK5 space motion and orient into the
+    // Hipparcos system.
+    let v = r5h.mul_vec(pv5.p.cross(s5h).add(pv5.v));
+
+    // Hipparcos pv-vector to spherical.
+    pv_star(&PosVel { p,v }) // pvstar
+}
+
+/// Transform Hipparcos star data into the FK5 (J2000.0) system.
+///
+/// Source: h52fk.for
+pub fn hipparcos_to_fk5(rh:R,dh:R,drh:R,ddh:R,pxh:R,rvh:R)
+			->Result<(R,R,R,R,R,R),StarError> {
+    // Hipparcos barycentric position/velocity pv-vector (normalized).
+    let (pvh,_) = star_pv(rh,dh,drh,ddh,pxh,rvh); // starpv
+
+    // FK5 to Hipparcos orientation matrix and spin vector.
+    let (r5h,s5h) = fk5_hipparcos(); // fk5hip
+
+    // Make spin units per day instead of per year.
+    let s5h = s5h.scale(1.0 / DJY);
+
+    // Orient the spin into the Hipparcos system.
+    let sh = r5h.mul_vec(s5h);
+
+    // De-orient the Hipparcos position into the FK5 system.
+    let p = r5h.transpose().mul_vec(pvh.p);
+
+    // Apply spin to the position giving a space motion, subtract it
+    // from the Hipparcos space motion and de-orient into the FK5
+    // system.
+    let v = r5h.transpose().mul_vec(pvh.v.sub(pvh.p.cross(sh)));
+
+    // FK5 pv-vector to spherical.
+    pv_star(&PosVel { p,v }) // pvstar
+}
+
+/// Radians per year to arcsec per century
+const PMF : R = 100.0 * DR2AS;
+
+/// Km per sec to AU per tropical century, 86400 * 36524.2198782 /
+/// 149597870.7
+const VF : R = 21.095;
+
+/// E-terms of aberration (position and rate) [rad, rad per century]
+const A : [Vec3;2] = [
+    [-1.62557e-6,-0.31919e-6,-0.13843e-6],
+    [ 1.245e-3,  -1.580e-3,  -0.659e-3]
+];
+
+/// FK4 to FK5 6x6 matrix, in blocks (output pos/vel, input pos/vel)
+const EM : [[Mat3;2];2] = [
+    [[[ 0.9999256782,-0.0111820611,-0.0048579477],
+      [ 0.0111820610, 0.9999374784,-0.0000271765],
+      [ 0.0048579479,-0.0000271474, 0.9999881997]],
+     [[ 0.00000242395018,-0.00000002710663,-0.00000001177656],
+      [ 0.00000002710663, 0.00000242397878,-0.00000000006587],
+      [ 0.00000001177656,-0.00000000006582, 0.00000242410173]]],
+    [[[-0.000551,-0.238565, 0.435739],
+      [ 0.238514,-0.002667,-0.008541],
+      [-0.435623, 0.012254, 0.002117]],
+     [[ 0.99994704,-0.01118251,-0.00485767],
+      [ 0.01118251, 0.99995883,-0.00002718],
+      [ 0.00485767,-0.00002714, 1.00000956]]]
+];
+
+/// Convert B1950.0 FK4 star catalog data to J2000.0 FK5.  Proper
+/// motions are per tropical year on input and per Julian year on
+/// output.
+///
+/// Source: fk425.for
+pub fn fk4_to_fk5(r1950:R,d1950:R,dr1950:R,dd1950:R,p1950:R,v1950:R)->(R,R,R,R,R,R) {
+    const TINY : R = 1e-30;
+
+    // The FK4 data (units radians and arcsec per tropical century).
+    let (sr,cr) = (sin(r1950),cos(r1950));
+    let (sd,cd) = (sin(d1950),cos(d1950));
+    let ur = dr1950 * PMF;
+    let ud = dd1950 * PMF;
+    let px = p1950;
+    let rv = v1950;
+
+    // Express as a pv-vector.
+    let pxvf = px * VF;
+    let w = rv * pxvf;
+    let r0 = [cr*cd,sr*cd,sd];
+    let v0 = [-sr*cd*ur - cr*sd*ud + w*r0[0],
+	       cr*cd*ur - sr*sd*ud + w*r0[1],
+	                     cd*ud + w*r0[2]];
+
+    // Allow for e-terms and express as position+velocity 6-vector.
+    let w = r0.dot(A[0]);
+    let wd = r0.dot(A[1]);
+    let p1 = r0.sub(A[0]).add(r0.scale(w));
+    let v1 = v0.sub(A[1]).add(r0.scale(wd));
+
+    // Convert pv-vector to Fricke system.
+    let [x,y,z] = EM[0][0].mul_vec(p1).add(EM[0][1].mul_vec(v1));
+    let [xd,yd,zd] = EM[1][0].mul_vec(p1).add(EM[1][1].mul_vec(v1));
+
+    // Revert to catalog form.
+    let rxysq = x*x + y*y;
+    let rxyzsq = rxysq + z*z;
+    let rxy = sqrt(rxysq);
+    let rxyz = sqrt(rxyzsq);
+
+    let spxy = x*xd + y*yd;
+    let spxyz = spxy + z*zd;
+
+    let r = if x == 0.0 && y == 0.0 { 0.0 } else { anp(atan2(y,x)) };
+    let d = atan2(z,rxy);
+
+    let (ur,ud) =
+	if rxy > TINY {
+	    (( x*yd - y*xd ) / rxysq,
+	     ( zd*rxysq - z*spxy ) / ( rxyzsq*rxy ))
+	} else {
+	    (0.0,0.0)
+	};
+
+    let (px,rv) =
+	if px > TINY {
+	    (px / rxyz,spxyz / ( pxvf*rxyz ))
+	} else {
+	    (px,rv)
+	};
+
+    (r,d,ur / PMF,ud / PMF,px,rv)
+}
+
+/// Convert a B1950.0 FK4 star position to J2000.0 FK5, assuming zero
+/// proper motion in the FK5 system.  BEPOCH is the Besselian epoch of
+/// the FK4 position.
+///
+/// Source: fk45z.for
+pub fn fk4_to_fk5_no_motion(r1950:R,d1950:R,bepoch:R)->(R,R) {
+    // Tropical year (days)
+    const DTY : R = 365.242198781;
+
+    // Spherical to Cartesian.
+    let r0 = spherical_to_cartesian(r1950,d1950); // s2c
+
+    // Adjust p-vector A to give zero proper motion in FK5.
+    let w = ( bepoch - 1950.0 ) / PMF;
+    let a1 = A[0].add(A[1].scale(w)); // ppsp
+
+    // Remove E-terms.
+    let w = r0.dot(a1);
+    let p1 = r0.sub(a1).add(r0.scale(w));
+
+    // Convert to Fricke system pv-vector (cf. Seidelmann 3.591-3).
+    let p = EM[0][0].mul_vec(p1);
+    let v = EM[1][0].mul_vec(p1);
+
+    // Allow for fictitious proper motion.
+    let djm = 15019.81352 + ( bepoch - 1900.0 ) * DTY; // epb2jd, MJD
+    let epj = 2000.0 + ( djm - 51544.5 ) / DJY; // epj
+    let w = ( epj - 2000.0 ) / PMF;
+    let p = p.add(v.scale(w)); // pvu
+
+    // Revert to spherical coordinates.
+    let (w,d2000) = cartesian_to_spherical(p); // c2s
+    (anp(w),d2000)
+}
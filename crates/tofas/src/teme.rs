@@ -0,0 +1,97 @@
+// True Equator Mean Equinox (TEME) frame used by SGP4, realized with
+// the IAU 1976/1980 models.  The celestial side of that chain is the
+// J2000.0 mean equator and equinox; the GCRS transformations add the
+// IAU 2000 frame bias (about 20 mas) to it.  Velocities are in units
+// of position per second.
+
+use crate::{
+    common::*,
+    time::{TT,UT1,D2S},
+    earth::{self,PosVel},
+    frames
+};
+
+/// Form the matrix taking J2000.0 mean equator and equinox coordinates
+/// to TEME.
+pub fn celestial_to_teme(tt:TT)->Mat3 {
+    let rnpb = frames::precession_nutation_80(tt); // pnm80
+    let ee = frames::equation_of_equinoxes_94(tt); // eqeq94
+    Mat3::rotation(2,ee).compose(&rnpb)
+}
+
+/// Form the matrix taking TEME coordinates to PEF.
+pub fn teme_to_pef_matrix(ut1:UT1)->Mat3 {
+    Mat3::rotation(2,earth::mean_sidereal_time_82(ut1)) // gmst82
+}
+
+/// Earth angular velocity vector, given the excess length of day
+/// [lod] in seconds.
+fn omega(lod:R)->Vec3 {
+    [0.0,0.0,earth::OMEGA * (1.0 - lod / D2S)]
+}
+
+/// Form the matrix taking GCRS coordinates to TEME, including the
+/// frame bias.
+pub fn gcrs_to_teme_matrix(tt:TT)->Mat3 {
+    celestial_to_teme(tt).compose(&frames::frame_bias_matrix())
+}
+
+/// Transform a state vector from GCRS to TEME.
+pub fn gcrs_to_teme(tt:TT,pv:&PosVel)->PosVel {
+    let r = gcrs_to_teme_matrix(tt);
+    PosVel {
+	p:r.mul_vec(pv.p),
+	v:r.mul_vec(pv.v)
+    }
+}
+
+/// Transform a state vector from TEME to GCRS.
+pub fn teme_to_gcrs(tt:TT,pv:&PosVel)->PosVel {
+    let rt = gcrs_to_teme_matrix(tt).transpose();
+    PosVel {
+	p:rt.mul_vec(pv.p),
+	v:rt.mul_vec(pv.v)
+    }
+}
+
+/// Transform a state vector from TEME to the pseudo Earth-fixed
+/// frame, given the excess length of day [lod] in seconds.
+pub fn teme_to_pef(ut1:UT1,lod:R,pv:&PosVel)->PosVel {
+    let r = teme_to_pef_matrix(ut1);
+    let p = r.mul_vec(pv.p);
+    let v = r.mul_vec(pv.v).sub(omega(lod).cross(p));
+    PosVel { p,v }
+}
+
+/// Transform a state vector from the pseudo Earth-fixed frame to TEME,
+/// given the excess length of day [lod] in seconds.
+pub fn pef_to_teme(ut1:UT1,lod:R,pv:&PosVel)->PosVel {
+    let rt = teme_to_pef_matrix(ut1).transpose();
+    let v = pv.v.add(omega(lod).cross(pv.p));
+    PosVel {
+	p:rt.mul_vec(pv.p),
+	v:rt.mul_vec(v)
+    }
+}
+
+/// Transform a state vector from TEME to ITRS, given the polar motion
+/// [xp],[yp] in radians and the excess length of day [lod] in seconds.
+pub fn teme_to_itrs(ut1:UT1,xp:R,yp:R,lod:R,pv:&PosVel)->PosVel {
+    let pef = teme_to_pef(ut1,lod,pv);
+    let w = earth::polar_motion_matrix(xp,yp,0.0);
+    PosVel {
+	p:w.mul_vec(pef.p),
+	v:w.mul_vec(pef.v)
+    }
+}
+
+/// Transform a state vector from ITRS to TEME, given the polar motion
+/// [xp],[yp] in radians and the excess length of day [lod] in seconds.
+pub fn itrs_to_teme(ut1:UT1,xp:R,yp:R,lod:R,pv:&PosVel)->PosVel {
+    let wt = earth::polar_motion_matrix(xp,yp,0.0).transpose();
+    let pef = PosVel {
+	p:wt.mul_vec(pv.p),
+	v:wt.mul_vec(pv.v)
+    };
+    pef_to_teme(ut1,lod,&pef)
+}
@@ -4,11 +4,27 @@ use crate::{
     common::*,
     delta_at::DeltaAt,
     earth::{self,EarthPosVel},
-    time::{TT,UT1,TDB,DJ00},
+    time::{TT,TAI,UT1,UTC,TDB,D2S,DJ00,DJC},
     ellipsoid::*,
     calendar::*,
-    frames,
+    frames::{self,PrecessionNutationModel,FramesError},
     locator,
+    fundargs,
+    iers_tables::{self,PoissonSeries,Nutation2000A,CipSeries,SeriesError},
+    teme,
+    typed::{Position,Gcrs,Itrs,FrameError},
+    interpolator::{CipInterpolator,InterpolatorError},
+    longterm,
+    ecliptic,
+    galactic,
+    stars,
+    horizontal,
+    moon,
+    planets::{Planet,PlanetPosVelWarning},
+    jpl::{JplEphemeris,JplError,Body},
+    astrometry::{self,Astrom,Atmosphere,EarthOrientation,Observer,ObservedCoordinates},
+    linalg::{Vector3,Matrix3,Rotation3},
+    quaternion::{Quaternion,EulerSequence},
     test_data::*
 };
 
@@ -341,4 +357,1584 @@ fn test_delta_at() {
     let dat = gd.delta_at(fd).unwrap().unwrap();
     let dat_exp = 33.0;
     compare_numbers("DAT",dat,dat_exp,EPSILON);
+
+    // A new value applies from the first day of its month, and the
+    // last day of the previous month keeps the old one
+    for (year,month,day,fd,dat_exp) in [(2016,12,31,1.0,36.0),
+					(2017,1,1,0.0,37.0),
+					(2005,12,31,0.5,32.0),
+					(2006,1,1,0.0,33.0),
+					(1971,12,31,0.0,4.213170 + (41316.0 - 39126.0)*0.002592),
+					(1972,1,1,0.0,10.0),
+					(1961,12,31,0.0,1.372818 + (37664.0 - 37300.0)*0.001296),
+					(1962,1,1,0.0,1.845858)] {
+	let dat = GregorianDate::new(year,month,day).unwrap().delta_at(fd).unwrap().unwrap();
+	compare_numbers("DAT boundary",dat,dat_exp,1e-12);
+    }
+}
+
+// Reference values for the IAU 2006 tests are from the SOFA test suite
+// (t_sofa_f.for).
+
+#[test]
+fn test_mean_obliquity_06() {
+    let eps = frames::mean_obliquity_06(TT((2400000.5,54388.0)));
+    compare_numbers("OBL06",eps,0.4090749229387258204,1e-14);
+}
+
+#[test]
+fn test_fukushima_williams_angles() {
+    let (gamb,phib,psib,epsa) =
+	frames::fukushima_williams_angles(TT((2400000.5,50123.9999)));
+    compare_numbers("GAMB",gamb,-0.2243387670997995690e-5,1e-16);
+    compare_numbers("PHIB",phib,0.4091014602391312808,1e-12);
+    compare_numbers("PSIB",psib,-0.9501954178013031895e-3,1e-14);
+    compare_numbers("EPSA",epsa,0.4091014316587367491,1e-12);
+}
+
+#[test]
+fn test_bias_and_precession_06() {
+    let rbp = frames::bias_and_precession_06(TT((2400000.5,50123.9999)));
+    compare_numbers("RBP11",rbp[0][0],0.9999995505176007047,1e-12);
+    compare_numbers("RBP12",rbp[0][1],0.8695404617348208406e-3,1e-14);
+    compare_numbers("RBP13",rbp[0][2],0.3779735201865589104e-3,1e-14);
+    compare_numbers("RBP21",rbp[1][0],-0.8695404723772031414e-3,1e-14);
+    compare_numbers("RBP22",rbp[1][1],0.9999996219496027161,1e-12);
+    compare_numbers("RBP31",rbp[2][0],-0.3779734957034089490e-3,1e-14);
+}
+
+#[test]
+fn test_locator_06() {
+    let s = locator::cio_06(TT((2400000.5,53736.0)),
+			    0.5791308486706011000e-3,
+			    0.4020579816732961219e-4);
+    compare_numbers("S06",s,-0.1220032213076463117e-7,1e-18);
+}
+
+/// Tolerance for comparisons with the SOFA IAU 2006/2000A values: the
+/// IAU 2000B series stand in for IAU 2000A, within about 1 mas, unless
+/// the IERS tables are given by TOFAS_IERS_TABLES
+fn tolerance_06(strict:R,loose:R)->R {
+    if iers_tables::nutation_2000a().is_some() {
+	strict
+    } else {
+	eprintln!("{} not set, using the IAU 2000B series",iers_tables::TABLES_VAR);
+	loose
+    }
+}
+
+#[test]
+fn test_nutation_06() {
+    let tt = TT((2400000.5,53736.0));
+    let tol = tolerance_06(1e-13,MAS2R);
+    let (dpsi,deps) = frames::nutation_06(tt);
+    compare_numbers("DPSI",dpsi,-0.9630912025820308797e-5,tol);
+    compare_numbers("DEPS",deps,0.4063238496887249798e-4,tol);
+    if let Some(nutation) = iers_tables::nutation_2000a() {
+	let (dpsi,deps) = nutation.nutation_00a(tt);
+	compare_numbers("DPSI",dpsi,-0.9630909107115518431e-5,1e-13);
+	compare_numbers("DEPS",deps,0.4063239174001678710e-4,1e-13);
+    }
+    let (dpsi,deps) = frames::nutation_06_from_00(TT((2400000.5,53736.0)),
+						  -0.9630909107115518431e-5,
+						  0.4063239174001678710e-4);
+    compare_numbers("DPSI",dpsi,-0.9630912025820308797e-5,1e-13);
+    compare_numbers("DEPS",deps,0.4063238496887249798e-4,1e-13);
+}
+
+#[test]
+fn test_iers_tables() {
+    let longitude = "\
+IAU 2000A nutation in longitude, j = 0 and 1 (header lines are skipped)
+----------------------------------------------------------------------
+ j = 0  Number of terms = 3
+    1   -17206424.18        3338.60    0    0    0    0    1    0    0    0    0    0    0    0    0    0
+    2    -1317091.22       -1369.60    0    0    2   -2    2    0    0    0    0    0    0    0    0    0
+    3        1440.00           0.00    0    0    0    0    0    0    0    8  -16    4    5    0    0    0
+
+ j = 1  Number of terms = 1
+    1      -17418.82           2.88    0    0    0    0    1    0    0    0    0    0    0    0    0    0
+";
+    let obliquity = "\
+ j = 0  Number of terms = 2
+    1        1537.70     9205233.10    0    0    0    0    1    0    0    0    0    0    0    0    0    0
+    2          10.00          20.00    0    0    0    0    1    0    0    0    0    0    0    0    1    0
+";
+    let longitude = PoissonSeries::from_reader(longitude.as_bytes()).unwrap();
+    let obliquity = PoissonSeries::from_reader(obliquity.as_bytes()).unwrap();
+    assert_eq!(longitude.counts(),vec![3,1]);
+    assert_eq!(obliquity.counts(),vec![2]);
+    let nutation = Nutation2000A::new(longitude,obliquity);
+
+    // The luni-solar terms use the IERS 2003 arguments, the planetary
+    // ones those of nut00a
+    let uas = AS2R / 1e6;
+    let tt = TT((2400000.5,53736.0));
+    let t = ((2400000.5 - DJ00) + 53736.0) / DJC;
+    let om = fundargs::om03(t);
+    let arg2 = 2.0*fundargs::f03(t) - 2.0*fundargs::d03(t) + 2.0*om;
+    let arg3 = 8.0*fundargs::e03(t) - 16.0*fundargs::ma03(t)
+	+ 4.0*fundargs::ju03(t) + 5.0*fundargs::sa03(t);
+    let dpsi = (-17206424.18*sin(om) + 3338.60*cos(om)
+		- 1317091.22*sin(arg2) - 1369.60*cos(arg2)
+		+ 1440.0*sin(arg3)
+		+ t*(-17418.82*sin(om) + 2.88*cos(om))) * uas;
+    let arg4 = (2.18243920 - 33.757045*t) + (5.321159000 + 3.8127774000*t);
+    let deps = (1537.70*sin(om) + 9205233.10*cos(om)
+		+ 10.0*sin(arg4) + 20.0*cos(arg4)) * uas;
+    let (dpsi1,deps1) = nutation.nutation_06a(tt);
+    compare_numbers("DPSI",dpsi1,dpsi,1e-18);
+    compare_numbers("DEPS",deps1,deps,1e-18);
+
+    let (dpsi0,deps0) = nutation.nutation_00a(tt);
+    let (dpsi2,deps2) = frames::nutation_06_from_00(tt,dpsi0,deps0);
+    compare_numbers("DPSI",dpsi2,dpsi1,1e-20);
+    compare_numbers("DEPS",deps2,deps1,1e-20);
+
+    // X and Y add a polynomial part
+    let x = " j = 1\n    1 -6844318.44 1328.67 0 0 0 0 1 0 0 0 0 0 0 0 0 0\n";
+    let y = " j = 0\n    1 1538.18 9205236.26 0 0 0 0 1 0 0 0 0 0 0 0 0 0\n";
+    let series = CipSeries::new(PoissonSeries::from_reader(x.as_bytes()).unwrap(),
+				PoissonSeries::from_reader(y.as_bytes()).unwrap());
+    let (x,y) = series.xy_06(tt);
+    let xpol = -16617.0 + t*(2004191898.0 + t*(-429782.9 + t*(-198618.34 + t*(7.578 + t*5.9285))));
+    let ypol = -6951.0 + t*(-25896.0 + t*(-22407274.7 + t*(1900.59 + t*(1112.526 + t*0.1358))));
+    compare_numbers("X",x,(xpol + t*(-6844318.44*sin(om) + 1328.67*cos(om))) * uas,1e-18);
+    compare_numbers("Y",y,(ypol + 1538.18*sin(om) + 9205236.26*cos(om)) * uas,1e-18);
+
+    assert!(matches!(PoissonSeries::from_reader("    1 1.0 2.0 0 0 0 0 1 0 0 0 0 0 0 0 0 0".as_bytes()),
+		     Err(SeriesError::MissingPower { line:1 })));
+    assert!(matches!(PoissonSeries::from_reader("j = 0\n".as_bytes()),
+		     Err(SeriesError::Empty)));
+}
+
+#[test]
+fn test_precession_nutation_06() {
+    let tt = TT((2400000.5,50123.9999));
+    let rbpn1 = [
+	[ 0.9999995832794205484,   0.8372382772630962111e-3, 0.3639684771140623099e-3],
+	[-0.8372533744743683605e-3,0.9999996486492861646,    0.4132905944611019498e-4],
+	[-0.3639337469629464969e-3,-0.4163377605910663999e-4,0.9999999329094260057]
+    ];
+    let rbpn2 = frames::precession_nutation_06(tt);
+    compare_matrices("RBPN",&rbpn1,&rbpn2,tolerance_06(1e-12,5.0*MAS2R));
+    let (dpsi,deps) = frames::nutation_06(tt);
+    let pn = frames::PrecessionNutation::from_tt_with_nutation_06(tt,dpsi,deps);
+    compare_matrices("RBPN",&rbpn2,&pn.rbpn,1e-15);
+    compare_matrices("RBP",&frames::bias_and_precession_06(tt),&pn.rbp,1e-15);
+    compare_matrices("RBP",&pn.rp.compose(&pn.rb),&pn.rbp,1e-15);
+}
+
+#[test]
+fn test_celestial_to_terrestrial_06() {
+    let rc2t1 = [
+	[-0.1810332128305897282,  0.9834769806938592296,  0.6555550962998436505e-4],
+	[-0.9834768134135984552, -0.1810332203649130832,  0.5749801116141056317e-3],
+	[ 0.5773474014081406921e-3,0.3961832391770163647e-4,0.9999998325501692289]
+    ];
+    let rc2t2 = frames::celestial_to_terrestrial_06(TT((2400000.5,53736.0)),
+						    UT1((2400000.5,53736.0)),
+						    2.55060238e-7,
+						    1.860359247e-6);
+    compare_matrices("RC2T",&rc2t1,&rc2t2,tolerance_06(1e-12,5.0*MAS2R));
+}
+
+#[test]
+fn test_cip_xys() {
+    let tt = TT((2400000.5,53736.0));
+    let (x,y,s) = frames::cip_xys_00b(tt);
+    compare_numbers("X",x,0.5791301929950208873e-3,1e-14);
+    compare_numbers("Y",y,0.4020553681373720832e-4,1e-15);
+    compare_numbers("S",s,-0.1220027377285083189e-7,1e-18);
+
+    let (x,y,s) = frames::cip_xys_06(tt);
+    compare_numbers("X",x,0.5791308482835292617e-3,tolerance_06(1e-14,MAS2R));
+    compare_numbers("Y",y,0.4020580099454020310e-4,tolerance_06(1e-15,MAS2R));
+    compare_numbers("S",s,-0.1220032294164579896e-7,tolerance_06(1e-18,1e-12));
+
+    if iers_tables::nutation_2000a().is_some() {
+	let (x,y,s) = frames::cip_xys_00a(tt);
+	compare_numbers("X",x,0.5791308472168152904e-3,1e-14);
+	compare_numbers("Y",y,0.4020595661591500259e-4,1e-15);
+	compare_numbers("S",s,-0.1220040848471549623e-7,1e-18);
+    }
+
+    // The series agree with the matrix to a few microarcseconds
+    let series = iers_tables::cip_series().is_some();
+    if series {
+	let (x,y) = frames::cip_xy_06(tt);
+	compare_numbers("X",x,0.5791308486706010975e-3,1e-15);
+	compare_numbers("Y",y,0.4020579816732958141e-4,1e-15);
+    }
+    for _ in 0..100 {
+	let tt = TT((DJ00,(fastrand::f64() - 0.5)*DJC));
+	let (x1,y1) = frames::xy_from_bpn(&frames::precession_nutation_06(tt));
+	let (x2,y2) = frames::cip_xy_06(tt);
+	let (x3,y3,_) = frames::cip_xys_06(tt);
+	let tol = if series { 1e-10 } else { 1e-15 };
+	compare_numbers("X",x1,x2,tol);
+	compare_numbers("Y",y1,y2,tol);
+	compare_numbers("X",x1,x3,1e-15);
+	compare_numbers("Y",y1,y3,1e-15);
+    }
+}
+
+/// The precession-nutation models, IAU 2000A when its series are
+/// installed
+fn models()->Vec<PrecessionNutationModel> {
+    let mut models = vec![PrecessionNutationModel::Iau1980,
+			  PrecessionNutationModel::Iau2000B,
+			  PrecessionNutationModel::Iau2006];
+    if iers_tables::nutation_2000a().is_some() {
+	models.push(PrecessionNutationModel::Iau2000A);
+    }
+    models
+}
+
+#[test]
+fn test_precession_nutation_model() {
+    let tt = TT((2400000.5,53736.0));
+    let ut1 = UT1((2400000.5,53736.0));
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+
+    // Each model follows its SOFA counterpart
+    for (name,c2t) in [
+	("2000B",frames::celestial_to_terrestrial as fn(TT,UT1,R,R)->Mat3),
+	("2006",frames::celestial_to_terrestrial_06)] {
+	let m : PrecessionNutationModel = name.parse().unwrap();
+	compare_matrices(name,
+			 &m.celestial_to_terrestrial(tt,ut1,xp,yp),
+			 &c2t(tt,ut1,xp,yp),
+			 0.0);
+    }
+
+    // The IAU 1980 model starts from the J2000.0 mean equator and
+    // equinox: from the GCRS, the frame bias comes first, as for TEME
+    let m = PrecessionNutationModel::Iau1980;
+    let rb = frames::frame_bias_matrix();
+    compare_matrices("1980",&m.celestial_to_terrestrial(tt,ut1,xp,yp),
+		     &frames::celestial_to_terrestrial_80(tt,ut1,xp,yp).compose(&rb),0.0);
+    compare_matrices("1980 MOD",&m.equinox_chain(tt,ut1,xp,yp).gcrs_to_mod.matrix,
+		     &frames::precession_76(tt).compose(&rb),0.0);
+    compare_matrices("1980 TEME",
+		     &Mat3::rotation(2,frames::equation_of_equinoxes_94(tt))
+		     .compose(&m.gcrs_to_tod(tt).matrix),
+		     &teme::gcrs_to_teme_matrix(tt),1e-15);
+
+    // SOFA c2t00a.  IAU 2000B is within 1 mas of IAU 2000A, and the
+    // IAU 1980 model within 0.1 arcsecond.
+    let rc2t00a = [
+	[-0.1810332128307182668,  0.9834769806938457836,  0.6555535638688341725e-4],
+	[-0.9834768134135984552, -0.1810332203649520727,  0.5749801116141056317e-3],
+	[ 0.5773474014081406921e-3,0.3961816546911624260e-4,0.9999998325501746670]
+    ];
+    compare_matrices("2000B",&frames::celestial_to_terrestrial(tt,ut1,xp,yp),&rc2t00a,MAS2R);
+    compare_matrices("1980",&frames::celestial_to_terrestrial_80(tt,ut1,xp,yp),&rc2t00a,
+		     100.0*MAS2R);
+
+    // The IAU 2000A model needs the IERS tables
+    match "2000A".parse::<PrecessionNutationModel>() {
+	Ok(m) => {
+	    let rc2t = m.celestial_to_terrestrial(tt,ut1,xp,yp);
+	    compare_matrices("2000A",&rc2t,&frames::celestial_to_terrestrial_00a(tt,ut1,xp,yp),0.0);
+	    compare_matrices("2000A",&rc2t,&rc2t00a,1e-12);
+	    let (rbp,rn) = m.bias_precession_and_nutation(tt);
+	    compare_matrices("RBPN",&rn.compose(&rbp),&frames::precession_nutation_00a(tt),1e-15);
+	    let (x,y,s) = m.cip_xys(tt);
+	    compare_matrices("C2I",&frames::celestial_to_intermediate_from_xys(x,y,s),
+			     &frames::celestial_to_intermediate_00a(tt),1e-15);
+	},
+	Err(e) => {
+	    assert!(matches!(e,FramesError::MissingNutation2000A));
+	    assert!(iers_tables::nutation_2000a().is_none());
+	}
+    }
+    assert!("1066".parse::<PrecessionNutationModel>().is_err());
+}
+
+#[test]
+fn test_nutation_80() {
+    let (dpsi,deps) = frames::nutation_80(TT((2400000.5,53736.0)));
+    compare_numbers("DPSI",dpsi,-0.9643658353226563966e-5,1e-13);
+    compare_numbers("DEPS",deps,0.4060051006879713322e-4,1e-13);
+}
+
+#[test]
+fn test_nutation_matrix_80() {
+    let rmatn1 = [
+	[ 0.9999999999534999268,   0.8847935789636432161e-5, 0.3835906502164019142e-5],
+	[-0.8847780042583435924e-5,0.9999999991366569963,   -0.4060052702727130809e-4],
+	[-0.3836265729708478796e-5,0.4060049308612638555e-4, 0.9999999991684415129]
+    ];
+    let rmatn2 = frames::nutation_matrix_80(TT((2400000.5,53736.0)));
+    compare_matrices("RMATN",&rmatn1,&rmatn2,1e-12);
+}
+
+#[test]
+fn test_precession_76() {
+    let (zeta,z,theta) = frames::precession_angles_76(TT((2400000.5,33282.0)),
+						      TT((2400000.5,51544.0)));
+    compare_numbers("ZETA",zeta,0.5588961642000161243e-2,1e-12);
+    compare_numbers("Z",z,0.5589922365870680624e-2,1e-12);
+    compare_numbers("THETA",theta,0.4858945471687296760e-2,1e-12);
+
+    let rmatp1 = [
+	[ 0.9999995504328350733,   0.8696632209480960785e-3, 0.3779153474959888345e-3],
+	[-0.8696632209485112192e-3,0.9999996218428560614,   -0.1643284776111886407e-6],
+	[-0.3779153474950335077e-3,-0.1643306746147366896e-6,0.9999999285899790119]
+    ];
+    let rmatp2 = frames::precession_76(TT((2400000.5,50123.9999)));
+    compare_matrices("RMATP",&rmatp1,&rmatp2,1e-12);
+}
+
+#[test]
+fn test_precession_nutation_80() {
+    let rmatpn1 = [
+	[ 0.9999995831934611169,   0.8373654045728124011e-3, 0.3639121916933106191e-3],
+	[-0.8373804896118301316e-3,0.9999996485439674092,    0.4130202510421549752e-4],
+	[-0.3638774789072144473e-3,-0.4160674085851722359e-4,0.9999999329310274805]
+    ];
+    let rmatpn2 = frames::precession_nutation_80(TT((2400000.5,50123.9999)));
+    compare_matrices("RMATPN",&rmatpn1,&rmatpn2,1e-12);
+}
+
+#[test]
+fn test_sidereal_time_82() {
+    let ee = frames::equation_of_equinoxes_94(TT((2400000.5,41234.0)));
+    compare_numbers("EQEQ94",ee,0.5357758254609256894e-4,1e-17);
+    let gmst = earth::mean_sidereal_time_82(UT1((2400000.5,53736.0)));
+    compare_numbers("GMST82",gmst,1.754174981860675096,1e-12);
+    let gst = earth::apparent_sidereal_time_94(UT1((2400000.5,53736.0)));
+    compare_numbers("GST94",gst,1.754166136020645203,1e-12);
+}
+
+#[test]
+fn test_teme() {
+    // Vallado et al., "Revisiting Spacetrack Report #3", AIAA 2006-6753,
+    // example at 2004-04-06 07:51:28.386009 UTC (km, km/s)
+    let gd = GregorianDate::new(2004,4,6).unwrap();
+    let fd = HMS::new(7,51,28.386009).to_fraction_of_day();
+    let (jd1,jd2) = gd.to_julian();
+    let dat = 32.0;
+    let dut1 = -0.4399619;
+    let lod = 0.0015563;
+    let xp = -0.140682*AS2R;
+    let yp = 0.333309*AS2R;
+    let tt = TT((jd1,jd2 + fd + (dat + 32.184)/86400.0));
+    let ut1 = UT1((jd1,jd2 + fd + dut1/86400.0));
+    let pv_teme = earth::PosVel {
+	p:[5094.18016210,6127.64465950,6380.34453270],
+	v:[-4.746131487,0.785818041,5.531931288]
+    };
+
+    let pv_pef = teme::teme_to_pef(ut1,lod,&pv_teme);
+    let p_pef = [-1033.47503130,7901.30558560,6380.34453270];
+    let v_pef = [-3.225632747,-2.872442511,5.531931288];
+    for i in 0..3 {
+	compare_numbers("P_PEF",pv_pef.p[i],p_pef[i],2e-5);
+	compare_numbers("V_PEF",pv_pef.v[i],v_pef[i],1e-8);
+    }
+
+    let pv_itrs = teme::teme_to_itrs(ut1,xp,yp,lod,&pv_teme);
+    let p_itrs = [-1033.4793830,7901.2952754,6380.3565958];
+    let v_itrs = [-3.225636520,-2.872451450,5.531924446];
+    for i in 0..3 {
+	compare_numbers("P_ITRS",pv_itrs.p[i],p_itrs[i],2e-5);
+	compare_numbers("V_ITRS",pv_itrs.v[i],v_itrs[i],1e-8);
+    }
+
+    // The GCRS differs from the J2000.0 mean equator and equinox of the
+    // TEME chain by the frame bias, here about 0.7 m
+    let (rb,_,_) = frames::bias_and_precession(tt);
+    let pv_gcrs = teme::teme_to_gcrs(tt,&pv_teme);
+    let p_j2000 = teme::celestial_to_teme(tt).transpose().mul_vec(pv_teme.p);
+    for i in 0..3 {
+	compare_numbers("P_GCRS",pv_gcrs.p[i],rb.transpose().mul_vec(p_j2000)[i],1e-9);
+    }
+    assert!(pv_gcrs.p.sub(p_j2000).norm() > 5e-4);
+
+    let pv_teme2 = teme::itrs_to_teme(ut1,xp,yp,lod,&pv_itrs);
+    let pv_teme3 = teme::gcrs_to_teme(tt,&pv_gcrs);
+    for i in 0..3 {
+	compare_numbers("P_TEME",pv_teme2.p[i],pv_teme.p[i],1e-9);
+	compare_numbers("V_TEME",pv_teme2.v[i],pv_teme.v[i],1e-12);
+	compare_numbers("P_TEME",pv_teme3.p[i],pv_teme.p[i],1e-9);
+	compare_numbers("V_TEME",pv_teme3.v[i],pv_teme.v[i],1e-12);
+    }
+
+    // J2000 position without the EOP nutation corrections; these
+    // amount to about 1 m here
+    let p_j2000 = [5102.508958,6123.011401,6378.136928];
+    for i in 0..3 {
+	compare_numbers("P_J2000",pv_gcrs.p[i],p_j2000[i],1e-3);
+    }
+}
+
+#[test]
+fn test_typed_frames() {
+    let tt = TT((2400000.5,53736.0));
+    let ut1 = UT1((2400000.5,53736.0));
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+    let c2t = frames::gcrs_to_itrs(tt,ut1,xp,yp);
+    let c2t_steps = frames::gcrs_to_cirs(tt)
+	.then(&frames::cirs_to_tirs(tt,ut1)).unwrap()
+	.then(&frames::tirs_to_itrs(tt,xp,yp)).unwrap();
+    // c2t00b neglects the TIO locator s'
+    compare_matrices("RC2T",&c2t.matrix,&c2t_steps.matrix,1e-10);
+
+    let p = Position::<Gcrs>::new([1.0,2.0,3.0],tt);
+    let q : Position<Itrs> = c2t.apply(&p).unwrap();
+    let p2 = c2t.inverse().apply(&q).unwrap();
+    for i in 0..3 {
+	compare_numbers("P",p.xyz[i],p2.xyz[i],1e-15);
+    }
+
+    let p_later = Position::<Gcrs>::new([1.0,2.0,3.0],TT((2400000.5,53736.5)));
+    assert!(matches!(c2t.apply(&p_later),Err(FrameError::EpochMismatch)));
+    assert!(matches!(p.angle(&p_later),Err(FrameError::EpochMismatch)));
+
+    // Each typed entry point wraps its SOFA matrix at the given epoch
+    let rb = frames::frame_bias_matrix();
+    for (name,r,m) in [
+	("C2T00B",frames::gcrs_to_itrs(tt,ut1,xp,yp),frames::celestial_to_terrestrial(tt,ut1,xp,yp)),
+	("C2T06A",frames::gcrs_to_itrs_06(tt,ut1,xp,yp),frames::celestial_to_terrestrial_06(tt,ut1,xp,yp)),
+	("C2T80",frames::gcrs_to_itrs_80(tt,ut1,xp,yp),
+	 frames::celestial_to_terrestrial_80(tt,ut1,xp,yp).compose(&rb))] {
+	compare_matrices(name,&r.matrix,&m,0.0);
+	assert_eq!(r.epoch.total(),tt.total());
+    }
+    for (name,r,m) in [
+	("C2I00B",frames::gcrs_to_cirs(tt),frames::celestial_to_intermediate(tt)),
+	("C2I06A",frames::gcrs_to_cirs_06(tt),frames::celestial_to_intermediate_06(tt))] {
+	compare_matrices(name,&r.matrix,&m,0.0);
+	assert_eq!(r.epoch.total(),tt.total());
+    }
+    for (name,r,m) in [
+	("PNM00B",frames::gcrs_to_tod(tt),frames::precession_nutation(tt)),
+	("PNM06A",frames::gcrs_to_tod_06(tt),frames::precession_nutation_06(tt)),
+	("PNM80",frames::gcrs_to_tod_80(tt),frames::precession_nutation_80(tt).compose(&rb))] {
+	compare_matrices(name,&r.matrix,&m,0.0);
+	assert_eq!(r.epoch.total(),tt.total());
+    }
+
+    // The C2I constructors from their components agree with c2i06a
+    let rbpn = frames::gcrs_to_tod_06(tt);
+    let (x,y,s) = frames::cip_xys_06(tt);
+    let c2i = frames::gcrs_to_cirs_06(tt);
+    compare_matrices("C2IBPN",&frames::gcrs_to_cirs_from_tod(&rbpn).matrix,&c2i.matrix,1e-12);
+    compare_matrices("C2IXY",&frames::gcrs_to_cirs_from_xy(tt,x,y).matrix,&c2i.matrix,1e-12);
+    compare_matrices("C2IXYS",&frames::gcrs_to_cirs_from_xys(tt,x,y,s).matrix,&c2i.matrix,1e-15);
+
+    // c2tcio, from the typed stages; their epochs must match
+    let era = earth::rotation_angle(ut1);
+    let rpom = frames::tirs_to_itrs(tt,xp,yp);
+    compare_matrices("C2TCIO",&frames::gcrs_to_itrs_from_cio_components(&c2i,era,&rpom).unwrap().matrix,
+		     &frames::gcrs_to_itrs_06(tt,ut1,xp,yp).matrix,1e-15);
+    let rpom_later = frames::tirs_to_itrs(TT((2400000.5,53736.5)),xp,yp);
+    assert!(matches!(frames::gcrs_to_itrs_from_cio_components(&c2i,era,&rpom_later),
+		     Err(FrameError::EpochMismatch)));
+
+    // The model rotations
+    for m in models() {
+	compare_matrices("GCRS2CIRS",&m.gcrs_to_cirs(tt).matrix,
+			 &m.celestial_to_intermediate(tt),0.0);
+    }
+}
+
+#[test]
+fn test_equation_of_origins() {
+    let rnpb = [
+	[ 0.9999989440476103608,  -0.1332881761240011518e-2,-0.5790767434730085097e-3],
+	[ 0.1332858254308954453e-2,0.9999991109044505944,   -0.4097782710401555759e-4],
+	[ 0.5791308472168153320e-3,0.4020595661593994396e-4, 0.9999998314954572365]
+    ];
+    let eo = frames::equation_of_origins(&rnpb,-0.1220040848472271978e-7);
+    compare_numbers("EORS",eo,-0.1332882715130744606e-2,1e-14);
+}
+
+#[test]
+fn test_frame_chains() {
+    let tt = TT((2400000.5,53736.0));
+    let ut1 = UT1((2400000.5,53736.0));
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+    for m in models() {
+	let c2t = m.celestial_to_terrestrial(tt,ut1,xp,yp);
+	let cio = m.cio_chain(tt,ut1,xp,yp);
+	let eqx = m.equinox_chain(tt,ut1,xp,yp);
+	compare_matrices("EQX",&eqx.gcrs_to_itrs().matrix,&c2t,1e-14);
+	compare_matrices("RBPN",&eqx.gcrs_to_tod().matrix,&m.gcrs_to_tod(tt).matrix,1e-15);
+	compare_matrices("CIO",&cio.gcrs_to_itrs().matrix,&c2t,1e-15);
+	compare_matrices("TIRS",&cio.gcrs_to_tirs().matrix,&eqx.gcrs_to_pef().matrix,1e-14);
+	compare_matrices("C2I",&cio.gcrs_to_cirs.matrix,
+			 &Mat3::rotation(2,m.cio_origin_shift(tt,ut1))
+			 .compose(&m.celestial_to_intermediate(tt)),1e-15);
+    }
+}
+
+#[test]
+fn test_gcrs_itrs_state() {
+    let (jd,dut) = (2453736.5,-4.2e-6);
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+    let rates = frames::EarthRotationRates {
+	lod:0.002,
+	xp_rate:1e-12,
+	yp_rate:-2e-12,
+	precession_nutation:true
+    };
+    let pv = earth::PosVel { p:[6000.0,-2500.0,1500.0],v:[1.0,5.0,-3.0] };
+
+    for m in models() {
+	// Position and central difference of the full matrix of the
+	// model over +/- dt seconds
+	let dt = 0.1;
+	let at = |s:R| {
+	    let c2t = m.celestial_to_terrestrial(
+		TT((jd,s/D2S)),
+		UT1((jd,dut + s*(1.0 - rates.lod/D2S)/D2S)),
+		xp + s*rates.xp_rate,
+		yp + s*rates.yp_rate);
+	    c2t.mul_vec(pv.p.add(pv.v.scale(s)))
+	};
+	let v_fd = at(dt).sub(at(-dt)).scale(0.5/dt);
+
+	let tt = TT((jd,0.0));
+	let ut1 = UT1((jd,dut));
+	let pvt = m.gcrs_to_itrs_state(tt,ut1,xp,yp,&rates,&pv);
+	let pvc = m.itrs_to_gcrs_state(tt,ut1,xp,yp,&rates,&pvt);
+	for i in 0..3 {
+	    compare_numbers("P",pvt.p[i],at(0.0)[i],1e-9);
+	    compare_numbers("V",pvt.v[i],v_fd[i],1e-9);
+	    compare_numbers("PC",pvc.p[i],pv.p[i],1e-9);
+	    compare_numbers("VC",pvc.v[i],pv.v[i],1e-12);
+	}
+    }
+}
+
+#[test]
+fn test_c2t_partials() {
+    let (jd,dut) = (2453736.5,-4.2e-6);
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+    let rates = frames::EarthRotationRates {
+	lod:0.002,
+	xp_rate:1e-12,
+	yp_rate:-2e-12,
+	precession_nutation:true
+    };
+    let diff = |a:Mat3,b:Mat3,h:R| a.add(&b.scale(-1.0)).scale(0.5/h);
+
+    for m in models() {
+	let c2t = |s:R,dut1:R,dxp:R,dyp:R|
+	    m.celestial_to_terrestrial(
+		TT((jd,s/D2S)),
+		UT1((jd,dut + (dut1 + s*(1.0 - rates.lod/D2S))/D2S)),
+		xp + dxp + s*rates.xp_rate,
+		yp + dyp + s*rates.yp_rate);
+
+	let pt = m.celestial_to_terrestrial_partials(TT((jd,0.0)),UT1((jd,dut)),xp,yp,&rates);
+	compare_matrices("C2T",&pt.matrix,&c2t(0.0,0.0,0.0,0.0),1e-15);
+	compare_matrices("D/DT",&pt.d_dt,
+			 &diff(c2t(0.1,0.0,0.0,0.0),c2t(-0.1,0.0,0.0,0.0),0.1),1e-12);
+	compare_matrices("D/DUT1",&pt.d_ddut1,
+			 &diff(c2t(0.0,0.1,0.0,0.0),c2t(0.0,-0.1,0.0,0.0),0.1),1e-12);
+	compare_matrices("D/DXP",&pt.d_dxp,
+			 &diff(c2t(0.0,0.0,1e-6,0.0),c2t(0.0,0.0,-1e-6,0.0),1e-6),1e-9);
+	compare_matrices("D/DYP",&pt.d_dyp,
+			 &diff(c2t(0.0,0.0,0.0,1e-6),c2t(0.0,0.0,0.0,-1e-6),1e-6),1e-9);
+    }
+}
+
+#[test]
+fn test_cip_interpolator() {
+    let (jd,dut) = (2453736.5,-4.2e-6);
+    let (xp,yp) = (2.55060238e-7,1.860359247e-6);
+    for m in models() {
+	let ci = CipInterpolator::new(m,TT((jd,0.0)),TT((jd,2.0)),0.125).unwrap();
+	let (bx,by,bs) = ci.error_bounds();
+	assert!(bx.max(by).max(bs) < 1e-11);
+	for k in 0..=40 {
+	    let f = k as R / 20.0;
+	    let tt = TT((jd,f));
+	    let ut1 = UT1((jd,f + dut));
+	    let (x,y,s) = ci.cip_xys(tt).unwrap();
+	    let (x0,y0,s0) = m.cip_xys(tt);
+	    compare_numbers("X",x,x0,bx);
+	    compare_numbers("Y",y,y0,by);
+	    compare_numbers("S",s,s0,bs);
+	    compare_matrices("C2T",&ci.gcrs_to_itrs(tt,ut1,xp,yp).unwrap().matrix,
+			     &m.cio_chain(tt,ut1,xp,yp).gcrs_to_itrs().matrix,
+			     ci.rotation_error_bound());
+	}
+	assert!(matches!(ci.cip_xys(TT((jd,2.5))),Err(InterpolatorError::OutOfRange)));
+	assert!(matches!(ci.cip_xys(TT((jd,-0.1))),Err(InterpolatorError::OutOfRange)));
+    }
+    assert!(matches!(CipInterpolator::new(PrecessionNutationModel::Iau2006,
+					  TT((jd,1.0)),TT((jd,0.0)),0.1),
+		     Err(InterpolatorError::BadRange)));
+}
+
+#[test]
+fn test_cip_interpolator_error() {
+    // The actual errors, on dense grids over ranges from 2000 to 2100,
+    // stay below the bounds without being much smaller
+    let jd = 2453736.5;
+    for (start,days,step) in [(0.0,30.0,1.0),(-3000.0,400.0,4.0),
+			      (9000.0,20.0,0.5),(34000.0,10.0,1.0)] {
+	for m in models() {
+	    let ci = CipInterpolator::new(m,TT((jd,start)),TT((jd,start + days)),step).unwrap();
+	    let (bx,by,bs) = ci.error_bounds();
+	    let br = ci.rotation_error_bound();
+	    let (mut ex,mut ey,mut es,mut er) : (R,R,R,R) = (0.0,0.0,0.0,0.0);
+	    let n = (days / step * 50.0) as usize;
+	    for k in 0..=n {
+		let f = start + k as R * days / n as R;
+		let (tt,ut1) = (TT((jd,f)),UT1((jd,f)));
+		let (x,y,s) = ci.cip_xys(tt).unwrap();
+		let (x0,y0,s0) = m.cip_xys(tt);
+		ex = ex.max(abs(x - x0));
+		ey = ey.max(abs(y - y0));
+		es = es.max(abs(s - s0));
+		let d = ci.celestial_to_terrestrial(tt,ut1,0.0,0.0).unwrap()
+		    .compose(&m.cio_chain(tt,ut1,0.0,0.0).gcrs_to_itrs().matrix.transpose());
+		let w = [d[2][1] - d[1][2],d[0][2] - d[2][0],d[1][0] - d[0][1]];
+		er = er.max(w.norm() / 2.0);
+	    }
+	    assert!(ex <= bx && ey <= by && es <= bs && er <= br,
+		    "{m:?} {start} {ex} {bx} {ey} {by} {es} {bs} {er} {br}");
+	    assert!(ex > bx / 10.0 && ey > by / 10.0 && er > br / 25.0,
+		    "{m:?} {start} {ex} {bx} {ey} {by} {er} {br}");
+	}
+    }
+}
+
+#[test]
+fn test_cip_xys_majorants() {
+    // The fourth differences of X, Y, s and of the origin shift over
+    // one day, h^4 times a fourth derivative, and their values stay
+    // below the majorants, from 1910 to 2100
+    let h = 1.0;
+    for m in models() {
+	for tc in [-0.9,-0.3,0.06,0.5,1.0] {
+	    let majorants = m.cip_xys_majorants(tc,0.01);
+	    let v : Vec<[R;4]> = (-2..=2).map(|k| {
+		let tt = TT((DJ00,tc * DJC + k as R * h));
+		let (x,y,s) = m.cip_xys(tt);
+		[x,y,s,m.cio_origin_shift_tt(tt)]
+	    }).collect();
+	    for (i,mi) in majorants.iter().enumerate() {
+		let d4 = v[0][i] - 4.0 * v[1][i] + 6.0 * v[2][i] - 4.0 * v[3][i] + v[4][i];
+		let m4 = mi.fourth() * (h / DJC).powi(4);
+		assert!(abs(d4) <= m4 + 1e-14,"{m:?} {tc} {i} {d4} {m4}");
+		assert!(abs(v[2][i]) <= mi.0[0],"{m:?} {tc} {i} {} {}",v[2][i],mi.0[0]);
+	    }
+	}
+    }
+}
+
+// Reference values from the SOFA test suite (t_sofa_f.for)
+
+#[test]
+fn test_long_term_poles() {
+    let pecl = longterm::ecliptic_pole(-1500.0);
+    compare_numbers("PECL1",pecl[0],0.4768625676477096525e-3,1e-14);
+    compare_numbers("PECL2",pecl[1],-0.4052259533091875112,1e-14);
+    compare_numbers("PECL3",pecl[2],0.9142164401096448012,1e-14);
+
+    let peqr = longterm::equator_pole(-2500.0);
+    compare_numbers("PEQR1",peqr[0],-0.3586652560237326659,1e-14);
+    compare_numbers("PEQR2",peqr[1],-0.1996978910771128475,1e-14);
+    compare_numbers("PEQR3",peqr[2],0.9118552442250819624,1e-14);
+
+    compare_matrices("LTP",&longterm::precession_matrix(1666.666),&[
+	[0.9967044141159213819,0.7437801893193210840e-1,0.3237624409345603401e-1],
+	[-0.7437802731819618167e-1,0.9972293894454533070,-0.1205768842723593346e-2],
+	[-0.3237622482766575399e-1,-0.1206286039697609008e-2,0.9994750246704010914]],1e-14);
+
+    let rpb = longterm::bias_precession_matrix(1666.666);
+    for (j,x) in [0.9967044167723271851,0.7437794731203340345e-1,0.3237632684841625547e-1]
+	.iter().enumerate() {
+	compare_numbers("LTPB",rpb[0][j],*x,1e-14);
+    }
+    // ltpb applies the frame bias to first order, with DX rounded to
+    // 1 µas
+    let (rb,_,_) = frames::bias_and_precession(TT((DJ00,0.0)));
+    compare_matrices("LTPB/LTP",&rpb,&longterm::precession_matrix(1666.666).compose(&rb),1e-12);
+
+    // Close to IAU 2006 precession near J2000.0
+    let tt = TT((DJ00,3652.5));
+    compare_matrices("LTPB/PMAT06",&longterm::bias_precession_matrix(tt.julian_epoch()),
+		     &frames::bias_and_precession_06(tt),1e-9);
+}
+
+#[test]
+fn test_ecliptic() {
+    let rm = ecliptic::ecliptic_matrix_06(TT((2456165.5,0.401182685)));
+    compare_matrices("ECM06",&rm,&[
+	[0.9999952427708701137,-0.2829062057663042347e-2,-0.1229163741100017629e-2],
+	[0.3084546876908653562e-2,0.9174891871550392514,0.3977487611849338124],
+	[0.2488512951527405928e-5,-0.3977506604161195467,0.9174935488232863071]],1e-14);
+
+    let (dl,db) = ecliptic::equatorial_to_ecliptic_06(TT((1234.5,2440000.5)),1.234,0.987);
+    compare_numbers("EQEC06 DL",dl,1.342509918994654619,1e-14);
+    compare_numbers("EQEC06 DB",db,0.5926215259704608132,1e-14);
+
+    let (dr,dd) = ecliptic::ecliptic_to_equatorial_06(TT((2456165.5,0.401182685)),5.1,-0.9);
+    compare_numbers("ECEQ06 DR",dr,5.533459733613627767,1e-14);
+    compare_numbers("ECEQ06 DD",dd,-1.246542932554480576,1e-14);
+
+    let rm = ecliptic::ecliptic_matrix_long_term(-3000.0);
+    compare_matrices("LTECM",&rm,&[
+	[0.3564105644859788825,0.8530575738617682284,0.3811355207795060435],
+	[-0.9343283469640709942,0.3247830597681745976,0.1467872751535940865],
+	[0.1431636191201167793e-2,-0.4084222566960599342,0.9127919865189030899]],1e-14);
+
+    let (dl,db) = ecliptic::equatorial_to_ecliptic_long_term(-1500.0,1.234,0.987);
+    compare_numbers("LTEQEC DL",dl,0.5039483649047114859,1e-14);
+    compare_numbers("LTEQEC DB",db,0.5848534459726224882,1e-14);
+
+    let (dr,dd) = ecliptic::ecliptic_to_equatorial_long_term(2500.0,1.5,0.6);
+    compare_numbers("LTECEQ DR",dr,1.275156021861921167,1e-14);
+    compare_numbers("LTECEQ DD",dd,0.9966573543519204791,1e-14);
+
+    // Typed helpers agree with eqec06
+    let tt = TT((2456165.5,0.401182685));
+    let p = Position::<Gcrs>::new(spherical_to_cartesian(1.234,0.987).scale(2.0),tt);
+    let (l,b) = ecliptic::longitude_latitude(&ecliptic::gcrs_to_ecliptic(tt).apply(&p).unwrap());
+    let (dl,db) = ecliptic::equatorial_to_ecliptic_06(tt,1.234,0.987);
+    compare_numbers("L",l,dl,1e-15);
+    compare_numbers("B",b,db,1e-15);
+    compare_numbers("EPJ",TT((2451545.0,-365.25)).julian_epoch(),1999.0,1e-12);
+}
+
+#[test]
+#[allow(clippy::approx_constant)]
+fn test_galactic() {
+    let (dl,db) = galactic::icrs_to_galactic(5.9338074302227188048671087,-1.1784870613579944551540570);
+    compare_numbers("ICRS2G DL",dl,5.5850536063818546461558,1e-14);
+    compare_numbers("ICRS2G DB",db,-0.7853981633974483096157,1e-14);
+
+    let (dr,dd) = galactic::galactic_to_icrs(5.5850536063818546461558105,-0.7853981633974483096156608);
+    compare_numbers("G2ICRS DR",dr,5.9338074302227188048671,1e-14);
+    compare_numbers("G2ICRS DD",dd,-1.1784870613579944551541,1e-14);
+}
+
+#[test]
+fn test_star_pv() {
+    let (pv,warnings) = stars::star_pv(0.01686756,-1.093989828,-1.78323516e-5,
+				       2.336024047e-6,0.74723,-21.6);
+    assert_eq!(warnings,stars::StarPvWarnings::default());
+    compare_numbers("P1",pv.p[0],126668.5912743160601,1e-10);
+    compare_numbers("P2",pv.p[1],2136.792716839935195,1e-12);
+    compare_numbers("P3",pv.p[2],-245251.2339876830091,1e-10);
+    compare_numbers("V1",pv.v[0],-0.4051854008955659551e-2,1e-13);
+    compare_numbers("V2",pv.v[1],-0.6253919754414777970e-2,1e-15);
+    compare_numbers("V3",pv.v[2],0.1189353714588109341e-1,1e-13);
+
+    let pv = earth::PosVel {
+	p:[126668.5912743160601,2136.792716839935195,-245251.2339876830091],
+	v:[-0.4051854035740712739e-2,-0.6253919754866173866e-2,0.1189353719774107189e-1]
+    };
+    let (ra,dec,pmr,pmd,px,rv) = stars::pv_star(&pv).unwrap();
+    compare_numbers("RA",ra,0.1686756e-1,1e-12);
+    compare_numbers("DEC",dec,-1.093989828,1e-12);
+    compare_numbers("PMR",pmr,-0.1783235160000472788e-4,1e-16);
+    compare_numbers("PMD",pmd,0.2336024047000619347e-5,1e-16);
+    compare_numbers("PX",px,0.74723,1e-12);
+    compare_numbers("RV",rv,-21.60000010107306010,1e-11);
+}
+
+#[test]
+fn test_fk5_hipparcos() {
+    let (r5h,s5h) = stars::fk5_hipparcos();
+    compare_matrices("R5H",&r5h,&[
+	[0.9999999999999928638,0.1110223351022919694e-6,0.4411803962536558154e-7],
+	[-0.1110223308458746430e-6,0.9999999999999891830,-0.9647792498984142358e-7],
+	[-0.4411805033656962252e-7,0.9647792009175314354e-7,0.9999999999999943728]],1e-14);
+    compare_numbers("S5H1",s5h[0],-0.1454441043328607981e-8,1e-17);
+    compare_numbers("S5H2",s5h[1],0.2908882086657215962e-8,1e-17);
+    compare_numbers("S5H3",s5h[2],0.3393695767766752955e-8,1e-17);
+
+    let (rh,dh,drh,ddh,pxh,rvh) = stars::fk5_to_hipparcos(
+	1.76779433,-0.2917517103,-1.91851572e-7,-5.8468475e-6,0.379210,-7.6).unwrap();
+    compare_numbers("RH",rh,1.767794226299947632,1e-14);
+    compare_numbers("DH",dh,-0.2917516070530391757,1e-14);
+    compare_numbers("DRH",drh,-0.1961874125605721270e-6,1e-19);
+    compare_numbers("DDH",ddh,-0.58459905176693911e-5,1e-19);
+    compare_numbers("PXH",pxh,0.37921,1e-14);
+    compare_numbers("RVH",rvh,-7.6000000940000254,1e-11);
+
+    let (r5,d5,dr5,dd5,px5,rv5) = stars::hipparcos_to_fk5(
+	1.767794352,-0.2917512594,-2.76413026e-6,-5.92994449e-6,0.379210,-7.6).unwrap();
+    compare_numbers("R5",r5,1.767794455700065506,1e-13);
+    compare_numbers("D5",d5,-0.2917513626469638890,1e-13);
+    compare_numbers("DR5",dr5,-0.27597945024511204e-5,1e-18);
+    compare_numbers("DD5",dd5,-0.59308014093262838e-5,1e-18);
+    compare_numbers("PX5",px5,0.37921,1e-13);
+    compare_numbers("RV5",rv5,-7.6000001309071126,1e-11);
+}
+
+#[test]
+fn test_fk4_to_fk5() {
+    let (r2000,d2000,dr2000,dd2000,p2000,v2000) = stars::fk4_to_fk5(
+	0.07626899753879587532,-1.137405378399605780,
+	0.1973749217849087460e-4,0.5659714913272723189e-5,0.134,8.7);
+    compare_numbers("R2000",r2000,0.08757989933556446040,1e-14);
+    compare_numbers("D2000",d2000,-1.132279113042091895,1e-12);
+    compare_numbers("DR2000",dr2000,0.1953670614474396139e-4,1e-17);
+    compare_numbers("DD2000",dd2000,0.5637686678659640164e-5,1e-18);
+    compare_numbers("P2000",p2000,0.1339919950582767871,1e-13);
+    compare_numbers("V2000",v2000,8.736999669183529069,1e-12);
+
+    let (r2000,d2000) = stars::fk4_to_fk5_no_motion(
+	0.01602284975382960982,-0.1164347929099906024,1954.677617625256806);
+    compare_numbers("R2000",r2000,0.02719295911606862303,1e-15);
+    compare_numbers("D2000",d2000,-0.1115766001565926892,1e-12);
+}
+
+#[test]
+fn test_spherical() {
+    let p = spherical_to_cartesian(3.0123,-0.999);
+    compare_numbers("S2C1",p[0],-0.5366267667260523906,1e-12);
+    compare_numbers("S2C2",p[1],0.0697711109765145365,1e-12);
+    compare_numbers("S2C3",p[2],-0.8409302618566214041,1e-12);
+
+    let (theta,phi) = cartesian_to_spherical([100.0,-50.0,25.0]);
+    compare_numbers("C2S THETA",theta,-0.4636476090008061162,1e-14);
+    compare_numbers("C2S PHI",phi,0.2199879773954594463,1e-14);
+
+    let p = spherical_to_position(-3.21,0.123,0.456);
+    compare_numbers("S2P1",p[0],-0.4514964673880165228,1e-12);
+    compare_numbers("S2P2",p[1],0.0309339427734258688,1e-12);
+    compare_numbers("S2P3",p[2],0.0559466810510877933,1e-12);
+
+    let (theta,phi,r) = position_to_spherical([100.0,-50.0,25.0]);
+    compare_numbers("P2S THETA",theta,-0.4636476090008061162,1e-12);
+    compare_numbers("P2S PHI",phi,0.2199879773954594463,1e-12);
+    compare_numbers("P2S R",r,114.5643923738960002,1e-9);
+
+    let pv = earth::PosVel::from_spherical(-3.21,0.123,0.456,-7.8e-6,9.01e-6,-1.23e-5);
+    compare_numbers("S2PV P1",pv.p[0],-0.4514964673880165228,1e-12);
+    compare_numbers("S2PV P2",pv.p[1],0.0309339427734258688,1e-12);
+    compare_numbers("S2PV P3",pv.p[2],0.0559466810510877933,1e-12);
+    compare_numbers("S2PV V1",pv.v[0],0.1292270850663260170e-4,1e-16);
+    compare_numbers("S2PV V2",pv.v[1],0.2652814182060691422e-5,1e-16);
+    compare_numbers("S2PV V3",pv.v[2],0.2568431853930292259e-5,1e-16);
+
+    let pv = earth::PosVel {
+	p:[-0.4514964673880165,0.03093394277342585,0.05594668105108779],
+	v:[1.292270850663260e-5,2.652814182060692e-6,2.568431853930293e-6]
+    };
+    let (theta,phi,r,td,pd,rd) = pv.to_spherical();
+    compare_numbers("PV2S THETA",theta,3.073185307179586515,1e-12);
+    compare_numbers("PV2S PHI",phi,0.1229999999999999992,1e-12);
+    compare_numbers("PV2S R",r,0.4559999999999999757,1e-12);
+    compare_numbers("PV2S TD",td,-0.7800000000000000364e-5,1e-16);
+    compare_numbers("PV2S PD",pd,0.9010000000000001639e-5,1e-16);
+    compare_numbers("PV2S RD",rd,-0.1229999999999999832e-4,1e-16);
+}
+
+#[test]
+fn test_separation_and_position_angle() {
+    let a = [1.0,0.1,0.2];
+    let b = [-3.0,1e-3,0.2];
+    compare_numbers("SEPP",a.angle(b),2.860391919024660768,1e-12);
+    compare_numbers("SEPS",separation_spherical(1.0,0.1,0.2,-3.0),2.346722016996998842,1e-14);
+    compare_numbers("PAP",position_angle(a,b),0.3671514267841113674,1e-12);
+    compare_numbers("PAS",position_angle_spherical(1.0,0.1,0.2,-1.0),-2.724544922932270424,1e-12);
+
+    // Small separations keep full relative precision
+    let a = spherical_to_cartesian(1.0,0.5);
+    let b = spherical_to_cartesian(1.0,0.5 + 1e-12);
+    compare_numbers("SEPP SMALL",a.angle(b),1e-12,1e-15);
+}
+
+#[test]
+fn test_linalg() {
+    let a = Vector3::new(1.0,0.1,0.2);
+    let b = Vector3::from([-3.0,1e-3,0.2]);
+    let c : Vec3 = (a + b * 2.0 - -a).into();
+    compare_numbers("ADD",c[0],-4.0,1e-15);
+    compare_numbers("DOT",a.dot(b),-2.9599,1e-15);
+    compare_numbers("CROSS",a.cross(b)[2],0.001 + 0.3,1e-15);
+
+    let m = Matrix3::from([[2.0,3.0,2.0],[3.0,2.0,3.0],[3.0,4.0,5.0]]);
+    compare_numbers("DET",m.determinant(),-10.0,1e-14);
+    let mi = m.inverse().unwrap();
+    compare_matrices("INV",&(m * mi).into(),&Mat3::identity(),1e-15);
+    assert!(Matrix3::from([[1.0,2.0,3.0],[2.0,4.0,6.0],[0.0,0.0,1.0]]).inverse().is_none());
+    let x = m * Vector3::new(1.0,2.0,3.0);
+    compare_numbers("MXV",x[2],26.0,1e-15);
+
+    // rv2m and rm2v
+    let r = Rotation3::from_rotation_vector(Vector3::new(0.0,0.0,0.7));
+    compare_matrices("RV2M Z",&r.into(),&Mat3::rotation(2,0.7),1e-15);
+    let w = Vector3::new(0.0,1.41371669,-1.88495559);
+    let r = Rotation3::from_rotation_vector(w);
+    let wr = r * w;
+    let w2 = r.to_rotation_vector();
+    for i in 0..3 {
+	compare_numbers("AXIS",wr[i],w[i],1e-14);
+	compare_numbers("RV2M/RM2V",w2[i],w[i],1e-14);
+    }
+    compare_numbers("ANGLE",r.angle(),w.norm(),1e-15);
+    assert!(Rotation3::from_matrix(Matrix3([[0.00,-0.80,-0.60],
+					    [0.80,-0.36,0.48],
+					    [0.60,0.48,-0.64]])).is_none());
+
+    // Composition and inversion agree with the frame rotations
+    let r = Rotation3::axis(2,0.3) * Rotation3::axis(0,-0.2);
+    compare_matrices("COMPOSE",&r.into(),
+		     &Mat3::rotation(2,0.3).compose(&Mat3::rotation(0,-0.2)),1e-15);
+    compare_matrices("INVERSE",&(r * r.inverse()).into(),&Mat3::identity(),1e-15);
+
+    // Renormalization
+    let mut m = Matrix3::from(Mat3::rotation(1,0.7));
+    m[0][1] += 1e-8;
+    let r = Rotation3::from_matrix(m).unwrap();
+    let e : Mat3 = (r.matrix() * r.matrix().transpose()).into();
+    compare_matrices("ORTHO",&e,&Mat3::identity(),1e-15);
+    assert!(Rotation3::from_matrix(Matrix3::identity() * 2.0).is_none());
+    assert!(Rotation3::from_matrix(-Matrix3::identity()).is_none());
+}
+
+#[test]
+fn test_quaternion() {
+    for axis in 0..3 {
+	compare_matrices("AXIS",&Quaternion::axis(axis,0.4).to_matrix(),
+			 &Mat3::rotation(axis,0.4),1e-15);
+    }
+
+    // Composition follows the matrix order
+    let a = Quaternion::new(0.9,0.1,-0.3,0.2).normalize();
+    let b = Quaternion::new(-0.2,0.5,0.4,0.7).normalize();
+    compare_matrices("MUL",&(a * b).to_matrix(),&a.to_matrix().compose(&b.to_matrix()),1e-15);
+    compare_matrices("CONJ",&(a * a.conjugate()).to_matrix(),&Mat3::identity(),1e-15);
+
+    // Matrix round trip, including the frame rotations
+    let pom = earth::polar_motion_matrix(0.2,-0.3,0.1);
+    compare_matrices("POM",&Quaternion::from_matrix(&pom).to_matrix(),&pom,1e-15);
+    let c2t = frames::celestial_to_terrestrial(TT((2400000.5,53736.0)),
+					       UT1((2400000.5,53736.0)),2.55e-7,1.86e-6);
+    compare_matrices("C2T",&Quaternion::from_matrix(&c2t).to_matrix(),&c2t,1e-15);
+    for q in [a,b,Quaternion::axis(0,3.1),Quaternion::axis(1,-3.1),Quaternion::axis(2,3.0)] {
+	let q2 = Quaternion::from_matrix(&q.to_matrix());
+	compare_numbers("FROM MATRIX",q2.angle_to(&q),0.0,1e-15);
+	compare_matrices("FROM MATRIX",&q2.to_matrix(),&q.to_matrix(),1e-15);
+    }
+
+    // Rotation vectors agree with rv2m
+    let w = [0.3,-1.2,0.5];
+    compare_matrices("RV",&Quaternion::from_rotation_vector(w).to_matrix(),
+		     &rotation_vector_to_matrix(w),1e-15);
+    let w2 = Quaternion::from_rotation_vector(w).to_rotation_vector();
+    for i in 0..3 {
+	compare_numbers("RV",w2[i],w[i],1e-15);
+    }
+    let r : Rotation3 = a.into();
+    compare_matrices("ROTATION3",&r.into(),&a.to_matrix(),1e-15);
+
+    // Euler sequences
+    let angles = [0.3,-0.7,1.9];
+    for seq in EulerSequence::ALL {
+	let [i,j,k] = seq.axes();
+	let q = Quaternion::from_euler(seq,angles);
+	compare_matrices("EULER",&q.to_matrix(),
+			 &Mat3::rotation(k,angles[2])
+			 .compose(&Mat3::rotation(j,angles[1]))
+			 .compose(&Mat3::rotation(i,angles[0])),1e-15);
+	let e = q.to_euler(seq);
+	let q2 = Quaternion::from_euler(seq,e);
+	compare_numbers("EULER ROUND TRIP",q2.angle_to(&q),0.0,1e-15);
+	if seq.is_proper() {
+	    assert!(e[1] >= 0.0);
+	} else {
+	    for n in 0..3 {
+		compare_numbers("EULER ANGLES",e[n],angles[n],1e-14);
+	    }
+	}
+
+	// Gimbal lock
+	let mid = if seq.is_proper() { 0.0 } else { PI/2.0 };
+	let q = Quaternion::from_euler(seq,[0.3,mid,0.2]);
+	let e = q.to_euler(seq);
+	assert_eq!(e[2],0.0);
+	compare_matrices("GIMBAL",&Quaternion::from_euler(seq,e).to_matrix(),&q.to_matrix(),1e-12);
+    }
+
+    // SLERP
+    let q0 = Quaternion::axis(2,0.2);
+    let q1 = Quaternion::axis(2,1.0);
+    compare_matrices("SLERP",&q0.slerp(&q1,0.25).to_matrix(),&Mat3::rotation(2,0.4),1e-15);
+    compare_matrices("SLERP SIGN",&q0.slerp(&(q1 * -1.0),0.5).to_matrix(),
+		     &Mat3::rotation(2,0.6),1e-15);
+    compare_numbers("SLERP END",q0.slerp(&q1,1.0).angle_to(&q1),0.0,1e-15);
+}
+
+#[test]
+fn test_astrometry() {
+    // Reference values from the SOFA test suite
+    let p1 = astrometry::aberration(
+	[-0.76321968546737951,-0.60869453983060384,-0.21676408580639883],
+	[2.1044018893653786e-5,-8.9108923304429319e-5,-3.8633714797716569e-5],
+	0.99980921395708788,0.99999999506209258);
+    compare_numbers("AB1",p1[0],-0.7631631094219556269,1e-12);
+    compare_numbers("AB2",p1[1],-0.6087553082505590832,1e-12);
+    compare_numbers("AB3",p1[2],-0.2167926269368471279,1e-12);
+
+    let p = [-0.763276255,-0.608633767,-0.216735543];
+    let p1 = astrometry::light_deflection(0.00028574,p,p,
+					  [0.76700421,0.605629598,0.211937094],
+					  8.91276983,3e-10);
+    compare_numbers("LD1",p1[0],-0.7632762548968159627,1e-12);
+    compare_numbers("LD2",p1[1],-0.6086337670823762701,1e-12);
+    compare_numbers("LD3",p1[2],-0.2167355431320546947,1e-12);
+
+    let p1 = astrometry::light_deflection_sun(p,[-0.973644023,-0.20925523,-0.0907169552],
+					      0.999809214);
+    compare_numbers("LDSUN1",p1[0],-0.7632762580731413169,1e-12);
+    compare_numbers("LDSUN2",p1[1],-0.6086337635262647900,1e-12);
+    compare_numbers("LDSUN3",p1[2],-0.2167355419322321302,1e-12);
+
+    let pco = astrometry::proper_motion_parallax(1.234,0.789,1e-5,-2e-5,1e-2,10.0,8.75,
+						 [0.9,0.4,0.1]);
+    compare_numbers("PMPX1",pco[0],0.2328137623960308438,1e-12);
+    compare_numbers("PMPX2",pco[1],0.6651097085397855328,1e-12);
+    compare_numbers("PMPX3",pco[2],0.7095257765896359837,1e-12);
+
+    let site = Geodetic { elong:2.0,phi:0.5,height:3000.0 };
+    let pv = astrometry::observer_pv(&site,1e-6,-0.5e-6,1e-8,5.0).unwrap();
+    compare_numbers("PVTOB P1",pv.p[0],4225081.367071159207,1e-5);
+    compare_numbers("PVTOB P2",pv.p[1],3681943.215856198144,1e-5);
+    compare_numbers("PVTOB P3",pv.p[2],3041149.399241260785,1e-5);
+    compare_numbers("PVTOB V1",pv.v[0],-268.4915389365998787,1e-9);
+    compare_numbers("PVTOB V2",pv.v[1],308.0977983288903123,1e-9);
+    compare_numbers("PVTOB V3",pv.v[2],0.0,0.0);
+
+    let tdb = TDB((2456165.5,0.401182685));
+    let epv = EarthPosVel {
+	heliocentric:earth::PosVel {
+	    p:[0.903358544,-0.415395237,-0.180084014],
+	    v:[0.0;3]
+	},
+	barycentric:earth::PosVel {
+	    p:[0.901310875,-0.417402664,-0.180982288],
+	    v:[0.00742727954,0.0140507459,0.00609045792]
+	},
+	warning:None
+    };
+    let astrom = Astrom::geocentric(tdb,&epv);
+    compare_numbers("APCG PMT",astrom.pmt,12.65133794027378508,1e-11);
+    compare_numbers("APCG EB1",astrom.eb[0],0.901310875,1e-12);
+    let ehp = epv.heliocentric.p;
+    compare_numbers("APCG EM",astrom.em,ehp.norm(),1e-15);
+    for i in 0..3 {
+	compare_numbers("APCG EH",astrom.eh[i],ehp[i] / ehp.norm(),1e-15);
+    }
+    let v = epv.barycentric.v.scale(earth::AUM / CMPS / D2S);
+    for i in 0..3 {
+	compare_numbers("APCG V",astrom.v[i],v[i],1e-18);
+    }
+    compare_numbers("APCG BM1",astrom.bm1,sqrt(1.0 - v.dot(v)),1e-15);
+    compare_matrices("APCG BPN",&astrom.bpn,&Mat3::identity(),0.0);
+
+    let (x,y,s) = (0.0013122272,-2.92808623e-5,3.05749468e-8);
+    let astrom = Astrom::intermediate(tdb,&epv,(x,y,s));
+    compare_matrices("APCI BPN",&astrom.bpn,
+		     &frames::celestial_to_intermediate_from_xys(x,y,s),0.0);
+    compare_numbers("APCI EM",astrom.em,ehp.norm(),1e-15);
+
+    // Terrestrial observer
+    let tdb = TDB((2456384.5,0.970031644));
+    let epv = EarthPosVel {
+	heliocentric:earth::PosVel {
+	    p:[-0.973458265,-0.209215307,-0.0906996477],
+	    v:[0.0;3]
+	},
+	barycentric:earth::PosVel {
+	    p:[-0.974170438,-0.211520082,-0.0917583024],
+	    v:[0.00364365824,-0.0154287319,-0.00668922024]
+	},
+	warning:None
+    };
+    let site = Geodetic { elong:-0.527800806,phi:-1.2345856,height:2738.0 };
+    let astrom = Astrom::terrestrial(tdb,&epv,(x,y,s),3.14540971,&site,
+				     (2.47230737e-7,1.82640464e-6,-3.01974337e-11),
+				     (0.000201418779,-2.36140831e-7)).unwrap();
+    compare_numbers("APCO PMT",astrom.pmt,13.25248468622587269,1e-11);
+    compare_numbers("APCO EB1",astrom.eb[0],-0.9741827110630322720,1e-12);
+    compare_numbers("APCO EB2",astrom.eb[1],-0.2115130190135344832,1e-12);
+    compare_numbers("APCO EB3",astrom.eb[2],-0.09179840186949532298,1e-12);
+    compare_numbers("APCO EH1",astrom.eh[0],-0.9736425571689739035,1e-12);
+    compare_numbers("APCO EH2",astrom.eh[1],-0.2092452125849330936,1e-12);
+    compare_numbers("APCO EH3",astrom.eh[2],-0.09075578152243272599,1e-12);
+    compare_numbers("APCO EM",astrom.em,0.9998233241709957653,1e-12);
+    compare_numbers("APCO V1",astrom.v[0],0.2078704992916728762e-4,1e-16);
+    compare_numbers("APCO V2",astrom.v[1],-0.8955360107151952319e-4,1e-16);
+    compare_numbers("APCO V3",astrom.v[2],-0.3863338994288951082e-4,1e-16);
+    compare_numbers("APCO BM1",astrom.bm1,0.9999999950277561236,1e-12);
+    compare_numbers("APCO ALONG",astrom.along,-0.5278008060295995734,1e-12);
+    compare_numbers("APCO XPL",astrom.xpl,0.1133427418130752958e-5,1e-17);
+    compare_numbers("APCO YPL",astrom.ypl,0.1453347595780646207e-5,1e-17);
+    compare_numbers("APCO SPHI",astrom.sphi,-0.9440115679003211329,1e-12);
+    compare_numbers("APCO CPHI",astrom.cphi,0.3299123514971474711,1e-12);
+    compare_numbers("APCO DIURAB",astrom.diurab,0.0,0.0);
+    compare_numbers("APCO ERAL",astrom.eral,2.617608903970400427,1e-12);
+
+    // Star to CIRS and back
+    let tol = tolerance_06(1e-12,1e-8);
+    let tdb = TDB((2456165.5,0.401182685));
+    let (rc,dc,pr,pd,px,rv) = (2.71,0.174,1e-5,5e-6,0.1,55.0);
+    let (ri,di,eo) = astrometry::icrs_to_cirs(rc,dc,pr,pd,px,rv,tdb);
+    compare_numbers("ATCI13 RI",ri,2.710121572968696744,tol);
+    compare_numbers("ATCI13 DI",di,0.1729371367219539137,tol);
+    compare_numbers("ATCI13 EO",eo,-0.002900618712657375647,tol);
+
+    let (rc1,dc1,eo1) = astrometry::cirs_to_icrs(2.710121572969038991,0.1729371367218230438,tdb);
+    compare_numbers("ATIC13 RC",rc1,2.710126504531716819,tol);
+    compare_numbers("ATIC13 DC",dc1,0.1740632537627034482,tol);
+    compare_numbers("ATIC13 EO",eo1,eo,0.0);
+
+    // The quick inverse undoes light deflection and aberration
+    let (astrom,_) = Astrom::intermediate_for_date(tdb);
+    let (ri,di) = astrometry::icrs_to_cirs_quick(rc,dc,0.0,0.0,0.0,0.0,&astrom);
+    let (rc1,dc1) = astrometry::cirs_to_icrs_quick(ri,di,&astrom);
+    compare_numbers("ATICQ RC",rc1,rc,1e-12);
+    compare_numbers("ATICQ DC",dc1,dc,1e-12);
+}
+
+#[test]
+fn test_observed_place() {
+    // Reference values from the SOFA test suite
+    let TAI((tai1,tai2)) = TAI::from_utc(UTC((2453750.5,0.892100694))).unwrap();
+    compare_numbers("UTCTAI 1",tai1,2453750.5,1e-6);
+    compare_numbers("UTCTAI 2",tai2,0.8924826384444444444,1e-12);
+    let UT1((ut11,ut12)) = UT1::from_utc(UTC((2453750.5,0.892104561)),0.3341).unwrap();
+    compare_numbers("UTCUT1 1",ut11,2453750.5,1e-6);
+    compare_numbers("UTCUT1 2",ut12,0.892104561 + 0.3341 / D2S,1e-12);
+
+    let (refa,refb) = Atmosphere { phpa:800.0,tc:10.0,rh:0.9,wl:0.4 }.refraction_constants();
+    compare_numbers("REFA",refa,0.2264949956241415009e-3,1e-15);
+    compare_numbers("REFB",refb,-0.2598658261729343970e-6,1e-18);
+
+    let utc = UTC((2456384.5,0.969254051));
+    let observer = Observer {
+	position:Geodetic { elong:-0.527800806,phi:-1.2345856,height:2738.0 }.into(),
+	eop:EarthOrientation { dut1:0.1550675,xp:2.47230737e-7,yp:1.82640464e-6 },
+	atmosphere:Atmosphere { phpa:731.0,tc:12.8,rh:0.59,wl:0.55 }
+    };
+    let astrom = Astrom::observer_for_date(utc,&observer).unwrap();
+    compare_numbers("APIO13 ALONG",astrom.along,-0.5278008060295995733,1e-12);
+    compare_numbers("APIO13 XPL",astrom.xpl,0.1133427418130752958e-5,1e-17);
+    compare_numbers("APIO13 YPL",astrom.ypl,0.1453347595780646207e-5,1e-17);
+    compare_numbers("APIO13 SPHI",astrom.sphi,-0.9440115679003211329,1e-12);
+    compare_numbers("APIO13 CPHI",astrom.cphi,0.3299123514971474711,1e-12);
+    compare_numbers("APIO13 DIURAB",astrom.diurab,0.5135843661699913529e-6,1e-12);
+    compare_numbers("APIO13 ERAL",astrom.eral,2.617608909189066140,1e-12);
+    compare_numbers("APIO13 REFA",astrom.refa,0.2014187785940396921e-3,1e-15);
+    compare_numbers("APIO13 REFB",astrom.refb,-0.2361408314943696227e-6,1e-18);
+
+    let ob = astrometry::cirs_to_observed(2.710121572969038991,0.1729371367218230438,
+					  utc,&observer).unwrap();
+    compare_numbers("ATIO13 AOB",ob.azimuth,0.9233952224895122499e-1,1e-12);
+    compare_numbers("ATIO13 ZOB",ob.zenith_distance,1.407758704513549991,1e-12);
+    compare_numbers("ATIO13 HOB",ob.hour_angle,-0.9247619879881698140e-1,1e-12);
+    compare_numbers("ATIO13 DOB",ob.declination,0.1717653435756234676,1e-12);
+    compare_numbers("ATIO13 ROB",ob.right_ascension,2.710085107988480746,1e-12);
+
+    for (name,obs,ri,di) in [
+	("R",ObservedCoordinates::RaDec(2.710085107986886201,0.1717653435758265198),
+	 2.710121574447540810,0.1729371839116608778),
+	("H",ObservedCoordinates::HaDec(-0.09247619879782006106,0.1717653435758265198),
+	 2.710121574448138676,0.1729371839116608781),
+	("A",ObservedCoordinates::AzZd(0.09233952224794989993,1.407758704513722461),
+	 2.710121574448033895,0.1729371839116608778)] {
+	let (ri1,di1) = astrometry::observed_to_cirs(obs,utc,&observer).unwrap();
+	compare_numbers(&format!("ATOI13 {name} RI"),ri1,ri,1e-12);
+	compare_numbers(&format!("ATOI13 {name} DI"),di1,di,1e-12);
+    }
+
+    let tol = tolerance_06(1e-12,1e-8);
+    let (ob,eo) = astrometry::icrs_to_observed(2.71,0.174,1e-5,5e-6,0.1,55.0,
+					       utc,&observer).unwrap();
+    compare_numbers("ATCO13 AOB",ob.azimuth,0.9251774485485515207e-1,tol);
+    compare_numbers("ATCO13 ZOB",ob.zenith_distance,1.407661405256499357,tol);
+    compare_numbers("ATCO13 HOB",ob.hour_angle,-0.9265154431529724692e-1,tol);
+    compare_numbers("ATCO13 DOB",ob.declination,0.1716626560072526200,tol);
+    compare_numbers("ATCO13 ROB",ob.right_ascension,2.710260453504961012,tol);
+    compare_numbers("ATCO13 EO",eo,-0.003020548354802412839,tol);
+
+    let (rc,dc) = astrometry::observed_to_icrs(
+	ObservedCoordinates::RaDec(2.710085107986886201,0.1717653435758265198),
+	utc,&observer).unwrap();
+    compare_numbers("ATOC13 RC",rc,2.709956744659136129,tol);
+    compare_numbers("ATOC13 DC",dc,0.1741696500898471362,tol);
+
+    // Observed to CIRS undoes CIRS to observed, to within the accuracy
+    // of the approximate inverse refraction (at ZD = 81 degrees here)
+    let (ri,di) = astrometry::observed_to_cirs(
+	ObservedCoordinates::AzZd(ob.azimuth,ob.zenith_distance),utc,&observer).unwrap();
+    let ob1 = astrometry::cirs_to_observed(ri,di,utc,&observer).unwrap();
+    compare_numbers("ROUND TRIP AOB",ob1.azimuth,ob.azimuth,1e-10);
+    compare_numbers("ROUND TRIP ZOB",ob1.zenith_distance,ob.zenith_distance,1e-7);
+
+    // UTC before 1960
+    assert!(Astrom::observer_for_date(UTC((2400000.5,0.0)),&observer).is_err());
+}
+
+#[test]
+fn test_star_propagation() {
+    // Reference values from the SOFA test suite
+    let TDB((d1,d2)) = TDB::from_julian_epoch(1996.8);
+    compare_numbers("EPJ2JD 1",d1,2400000.5,1e-9);
+    compare_numbers("EPJ2JD 2",d2,50375.7,1e-9);
+
+    let entry = stars::CatalogueEntry {
+	ra:0.01686756,dec:-1.093989828,pmr:-1.78323516e-5,pmd:2.336024047e-6,
+	px:0.74723,rv:-21.6,epoch:TDB((2400000.5,50083.0))
+    };
+    let (e2,warnings) = entry.propagate(TDB((2400000.5,53736.0))).unwrap();
+    assert_eq!(warnings,stars::StarPvWarnings::default());
+    compare_numbers("STARPM RA",e2.ra,0.01668919069414256149,1e-13);
+    compare_numbers("STARPM DEC",e2.dec,-1.093966454217127897,1e-13);
+    compare_numbers("STARPM PMR",e2.pmr,-0.1783662682153176524e-4,1e-17);
+    compare_numbers("STARPM PMD",e2.pmd,0.2338092915983989595e-5,1e-17);
+    compare_numbers("STARPM PX",e2.px,0.7473533835317719243,1e-13);
+    compare_numbers("STARPM RV",e2.rv,-21.59905170476417175,1e-11);
+
+    let entry = stars::CatalogueEntry {
+	ra:1.234,dec:0.789,pmr:1e-5,pmd:-2e-5,px:1e-2,rv:10.0,
+	epoch:TDB((2400000.5,48348.5625))
+    };
+    let (e2,warnings) = entry.propagate_safe(TDB((2400000.5,51544.5))).unwrap();
+    assert_eq!(warnings,stars::StarPvWarnings::default());
+    compare_numbers("PMSAFE RA",e2.ra,1.234087484501017061,1e-12);
+    compare_numbers("PMSAFE DEC",e2.dec,0.7888249982450468567,1e-12);
+    compare_numbers("PMSAFE PMR",e2.pmr,0.9996457663586073988e-5,1e-12);
+    compare_numbers("PMSAFE PMD",e2.pmd,-0.2000040085106754565e-4,1e-16);
+    compare_numbers("PMSAFE PX",e2.px,0.9999997295356830666e-2,1e-12);
+    compare_numbers("PMSAFE RV",e2.rv,10.38468380293920069,1e-10);
+
+    // Zero parallax is overridden
+    let (_,warnings) = stars::CatalogueEntry { px:0.0,..entry }
+	.propagate_safe(TDB((2400000.5,51544.5))).unwrap();
+    assert!(warnings.distance_overridden);
+
+    // An entry at J2000.0 gives the same apparent place as atciq, and
+    // moving it to another epoch changes the place very little
+    let tdb = TDB((2456165.5,0.401182685));
+    let (astrom,_) = Astrom::intermediate_for_date(tdb);
+    let entry = stars::CatalogueEntry {
+	ra:2.71,dec:0.174,pmr:1e-5,pmd:5e-6,px:0.1,rv:55.0,epoch:TDB((DJ00,0.0))
+    };
+    let (ri,di) = entry.cirs_quick(&astrom);
+    let (ri1,di1) = astrometry::icrs_to_cirs_quick(2.71,0.174,1e-5,5e-6,0.1,55.0,&astrom);
+    compare_numbers("CIRS RI",ri,ri1,0.0);
+    compare_numbers("CIRS DI",di,di1,0.0);
+    let (entry2,_) = entry.propagate(TDB::from_julian_epoch(2016.0)).unwrap();
+    let (ri2,di2) = entry2.cirs_quick(&astrom);
+    compare_numbers("CIRS RI 2016",ri2,ri,1e-10);
+    compare_numbers("CIRS DI 2016",di2,di,1e-10);
+
+    let d = entry.direction(tdb,astrom.eb);
+    let d1 = astrometry::proper_motion_parallax(2.71,0.174,1e-5,5e-6,0.1,55.0,
+						astrom.pmt,astrom.eb);
+    compare_numbers("PMPX",d.sub(d1).norm(),0.0,1e-15);
+}
+
+#[test]
+fn test_horizontal() {
+    // Reference values from the SOFA test suite
+    let site = Geodetic { elong:0.0,phi:0.3,height:0.0 };
+    let (az,el) = horizontal::hour_angle_dec_to_az_el(1.1,1.2,&site);
+    compare_numbers("HD2AE A",az,5.916889243730066194,1e-13);
+    compare_numbers("HD2AE E",el,0.4472186304990486228,1e-14);
+    compare_numbers("HD2PA",horizontal::parallactic_angle(1.1,1.2,&site),
+		    1.906227428001995580,1e-13);
+
+    let site = Geodetic { elong:0.0,phi:0.7,height:0.0 };
+    let (ha,dec) = horizontal::az_el_to_hour_angle_dec(5.5,1.1,&site);
+    compare_numbers("AE2HD H",ha,0.5933291115507309663,1e-14);
+    compare_numbers("AE2HD D",dec,0.9613934761647817620,1e-14);
+    let (az,el) = horizontal::hour_angle_dec_to_az_el(ha,dec,&site);
+    compare_numbers("AE2HD A",az,5.5,1e-14);
+    compare_numbers("AE2HD E",el,1.1,1e-14);
+
+    // The CIO-based and equinox-based hour angles agree, since the
+    // right ascensions differ by the equation of the origins
+    let tt = TT((2456384.5,0.969254051));
+    let ut1 = UT1((2456384.5,0.969254051 - 67.0 / D2S));
+    let site = Geodetic { elong:-0.527800806,phi:-1.2345856,height:2738.0 };
+    let model = PrecessionNutationModel::Iau2006;
+    let (_,eo) = Astrom::intermediate_for_date(TDB(tt.0));
+    let ha_era = horizontal::local_hour_angle_era(ut1,2.7,&site);
+    let ha_gast = horizontal::local_hour_angle_gast(model,tt,ut1,2.7 - eo,&site);
+    compare_numbers("LHA",ha_gast,ha_era,1e-12);
+}
+
+#[test]
+fn test_moon() {
+    // Reference values from the SOFA test suite
+    let tt = TT((2400000.5,43999.9));
+    let pv = moon::moon_pv(tt);
+    compare_numbers("MOON98 P1",pv.p[0],-0.2601295959971044180e-2,1e-11);
+    compare_numbers("MOON98 P2",pv.p[1],0.6139750944302742096e-3,1e-11);
+    compare_numbers("MOON98 P3",pv.p[2],0.2640794528229828909e-3,1e-11);
+    compare_numbers("MOON98 V1",pv.v[0],-0.1244321506649895021e-3,1e-11);
+    compare_numbers("MOON98 V2",pv.v[1],-0.5219076942678119398e-3,1e-11);
+    compare_numbers("MOON98 V3",pv.v[2],-0.1716132214378462047e-3,1e-11);
+
+    // The velocity is the derivative of the position, apart from the
+    // neglected rate of the precession matrix
+    let h = 1e-3;
+    let pm = moon::moon_pv(TT((2400000.5,43999.9 - h)));
+    let pp = moon::moon_pv(TT((2400000.5,43999.9 + h)));
+    for i in 0..3 {
+	compare_numbers("MOON98 dP/dt",(pp.p[i] - pm.p[i])/(2.0*h),pv.v[i],3e-9);
+    }
+}
+
+#[test]
+fn test_planets() {
+    // Reference values from the SOFA test suite
+    let pv = Planet::EarthMoonBarycenter.pos_vel(TDB((2400000.5,-320000.0)));
+    compare_numbers("PLAN94 P1",pv.heliocentric.p[0],0.9308038666832975759,1e-11);
+    compare_numbers("PLAN94 P2",pv.heliocentric.p[1],0.3258319040261346000,1e-11);
+    compare_numbers("PLAN94 P3",pv.heliocentric.p[2],0.1422794544481140560,1e-11);
+    compare_numbers("PLAN94 V1",pv.heliocentric.v[0],-0.6429458958255170006e-2,1e-11);
+    compare_numbers("PLAN94 V2",pv.heliocentric.v[1],0.1468570657704237764e-1,1e-11);
+    compare_numbers("PLAN94 V3",pv.heliocentric.v[2],0.6406996426270981189e-2,1e-11);
+    assert_eq!(pv.warning,Some(PlanetPosVelWarning::DateOutOfRange));
+
+    let pv = Planet::Mercury.pos_vel(TDB((2400000.5,43999.9)));
+    compare_numbers("PLAN94 P4",pv.heliocentric.p[0],0.2945293959257430832,1e-11);
+    compare_numbers("PLAN94 P5",pv.heliocentric.p[1],-0.2452204176601049596,1e-11);
+    compare_numbers("PLAN94 P6",pv.heliocentric.p[2],-0.1615427700571978153,1e-11);
+    compare_numbers("PLAN94 V4",pv.heliocentric.v[0],0.1413867871404614441e-1,1e-11);
+    compare_numbers("PLAN94 V5",pv.heliocentric.v[1],0.1946548301104706582e-1,1e-11);
+    compare_numbers("PLAN94 V6",pv.heliocentric.v[2],0.8929809783898904786e-2,1e-11);
+    assert_eq!(pv.warning,None);
+
+    // The Earth-Moon barycentre is within 5000 km of the Earth
+    let tdb = TDB((DJ00,8765.4));
+    let epv : EarthPosVel = tdb.into();
+    let pv = Planet::EarthMoonBarycenter.pos_vel(tdb);
+    assert!(pv.heliocentric.p.sub(epv.heliocentric.p).norm() < 5e6/earth::AUM);
+
+    // Apparent places differ from the geometric directions by the
+    // aberration and the motion of the planet during the light time,
+    // both well below one arcminute
+    let tt = TT((DJ00,8765.4));
+    for planet in [Planet::Mercury,Planet::Venus,Planet::Mars,Planet::Jupiter,
+		   Planet::Saturn,Planet::Uranus,Planet::Neptune] {
+	let place = planet.apparent_place(PrecessionNutationModel::Iau2006,tt,tdb);
+	let pg = planet.pos_vel(tdb).heliocentric.p.sub(epv.heliocentric.p);
+	compare_numbers("Apparent distance",place.distance,pg.norm(),1e-2);
+	assert!(place.direction.angle(pg) < 60.0*AS2R);
+	let p = frames::precession_nutation_06(tt).mul_vec(place.direction);
+	let (ra,dec) = cartesian_to_spherical(p);
+	compare_numbers("Apparent RA",anp(ra),place.ra,1e-12);
+	compare_numbers("Apparent Dec",dec,place.dec,1e-12);
+    }
+}
+
+/// Synthetic JPL DE ephemeris in memory: two 32-day records, eleven
+/// bodies with twelve coefficients per component and one sub-interval,
+/// and TT-TDB over two sub-intervals.  Each component is the
+/// polynomial c0 + c1*T1(tc) + T2(tc) in the normalized time tc.
+fn synthetic_jpl(big_endian:bool)->Vec<u8> {
+    const NCOEF : usize = 12;
+    const NCOEFF : usize = 2 + 11*3*NCOEF + 2*NCOEF;
+    let recl = NCOEFF*8;
+    let mut buf = vec![0u8;4*recl];
+    let i32b = |v:i32| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+    let f64b = |v:f64| if big_endian { v.to_be_bytes() } else { v.to_le_bytes() };
+    let put = |buf:&mut Vec<u8>,offset:usize,bytes:&[u8]| {
+	buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    };
+
+    put(&mut buf,0,b"JPL Planetary Ephemeris DE440/LE440");
+    put(&mut buf,252,b"AU    EMRAT ");
+    put(&mut buf,2652,&f64b(2451536.5));
+    put(&mut buf,2660,&f64b(2451600.5));
+    put(&mut buf,2668,&f64b(32.0));
+    put(&mut buf,2676,&i32b(2));
+    put(&mut buf,2680,&f64b(149597870.7));
+    put(&mut buf,2688,&f64b(81.3));
+    for j in 0..11 {
+	for (k,v) in [3 + j*3*NCOEF,NCOEF,1].into_iter().enumerate() {
+	    put(&mut buf,2696 + 12*j + 4*k,&i32b(v as i32));
+	}
+    }
+    put(&mut buf,2840,&i32b(440));
+    for (k,v) in [3 + 11*3*NCOEF,NCOEF,2].into_iter().enumerate() {
+	put(&mut buf,2868 + 4*k,&i32b(v as i32));
+    }
+
+    // Constants
+    put(&mut buf,recl,&f64b(149597870.7));
+    put(&mut buf,recl + 8,&f64b(81.3));
+
+    // Data records
+    for irec in 0..2 {
+	let r0 = (2 + irec)*recl;
+	let jd0 = 2451536.5 + 32.0*irec as f64;
+	put(&mut buf,r0,&f64b(jd0));
+	put(&mut buf,r0 + 8,&f64b(jd0 + 32.0));
+	for j in 0..11 {
+	    for k in 0..3 {
+		let i0 = r0 + 8*(2 + (j*3 + k)*NCOEF);
+		put(&mut buf,i0,&f64b(1e6*(j + 1) as f64 + 1e3*k as f64 + 1e5*irec as f64));
+		put(&mut buf,i0 + 8,&f64b(1e3*(k + 1) as f64));
+		put(&mut buf,i0 + 16,&f64b(1.0));
+	    }
+	}
+	for isub in 0..2 {
+	    let i0 = r0 + 8*(2 + 11*3*NCOEF + isub*NCOEF);
+	    put(&mut buf,i0,&f64b(1e-3*(1 + 2*irec + isub) as f64));
+	    put(&mut buf,i0 + 8,&f64b(1e-4));
+	}
+    }
+    buf
+}
+
+#[test]
+fn test_jpl() {
+    let au = 149597870.7;
+    for big_endian in [false,true] {
+	let eph = JplEphemeris::from_reader(std::io::Cursor::new(synthetic_jpl(big_endian)))
+	    .unwrap();
+	assert_eq!(eph.de_number(),440);
+	assert_eq!(eph.range(),(2451536.5,2451600.5));
+	assert_eq!(eph.constant("EMRAT"),Some(81.3));
+	assert!(eph.has_time_ephemeris());
+
+	// Sun (group 11) in the second record, at tc = 0.5: T1 = 0.5,
+	// T2 = -0.5, dtc/dt = 2/32 per day
+	let tdb = TDB((2451568.5,24.0));
+	let pv = eph.pos_vel(Body::Sun,Body::SolarSystemBarycenter,tdb).unwrap();
+	for k in 0..3 {
+	    let c0 = 11e6 + 1e3*k as f64 + 1e5;
+	    let c1 = 1e3*(k + 1) as f64;
+	    compare_numbers("JPL Sun P",pv.p[k]*au,c0 + 0.5*c1 - 0.5,1e-6);
+	    compare_numbers("JPL Sun V",pv.v[k]*au,(c1 + 4.0*0.5)*2.0/32.0,1e-9);
+	}
+
+	// Earth and Moon from the Earth-Moon barycentre and the
+	// geocentric Moon; the velocities are the derivatives of the
+	// positions
+	let moon = eph.pos_vel(Body::Moon,Body::Earth,tdb).unwrap();
+	let earth = eph.pos_vel(Body::Earth,Body::SolarSystemBarycenter,tdb).unwrap();
+	let emb = eph.pos_vel(Body::EarthMoonBarycenter,Body::SolarSystemBarycenter,tdb).unwrap();
+	let m = eph.pos_vel(Body::Moon,Body::SolarSystemBarycenter,tdb).unwrap();
+	for k in 0..3 {
+	    compare_numbers("JPL EMB",emb.p[k],earth.p[k] + moon.p[k]/82.3,1e-12);
+	    compare_numbers("JPL Moon",m.p[k],earth.p[k] + moon.p[k],1e-12);
+	}
+	let h = 1e-3;
+	let pm = eph.pos_vel(Body::Mars,Body::Earth,TDB((2451545.0,-h))).unwrap();
+	let pp = eph.pos_vel(Body::Mars,Body::Earth,TDB((2451545.0,h))).unwrap();
+	let pv = eph.pos_vel(Body::Mars,Body::Earth,TDB((2451545.0,0.0))).unwrap();
+	for k in 0..3 {
+	    compare_numbers("JPL dP/dt",(pp.p[k] - pm.p[k])/(2.0*h),pv.v[k],1e-12);
+	}
+
+	// Drop-in replacement for EarthPosVel
+	let epv = eph.earth_pos_vel(tdb).unwrap();
+	let sun = eph.pos_vel(Body::Earth,Body::Sun,tdb).unwrap();
+	compare_matrices("JPL EPV",&[epv.heliocentric.p,epv.heliocentric.v,epv.barycentric.p],
+			 &[sun.p,sun.v,earth.p],1e-12);
+
+	// TT-TDB over two sub-intervals of 16 days
+	compare_numbers("JPL TT-TDB",eph.tt_minus_tdb(TDB((2451568.5,4.0))).unwrap(),
+			3e-3 - 0.5e-4,1e-15);
+	compare_numbers("JPL TT-TDB",eph.tt_minus_tdb(TDB((2451568.5,28.0))).unwrap(),
+			4e-3 + 0.5e-4,1e-15);
+	let (_,rate) = eph.tt_minus_tdb_rate(tdb).unwrap();
+	compare_numbers("JPL TT-TDB rate",rate,1e-4*2.0/16.0/D2S,1e-18);
+
+	assert!(matches!(eph.pos_vel(Body::Sun,Body::Earth,TDB((2451600.5,0.1))),
+			 Err(JplError::OutOfRange)));
+	assert!(eph.pos_vel(Body::Sun,Body::Earth,TDB((2451600.5,0.0))).is_ok());
+    }
+
+    // Threads sharing one ephemeris, each alternating between the two
+    // records, get the same results as serial evaluations
+    let eph = JplEphemeris::from_reader(std::io::Cursor::new(synthetic_jpl(false))).unwrap();
+    let dates = [TDB((2451536.5,3.0)),TDB((2451568.5,24.0))];
+    let serial : Vec<_> = dates.iter()
+	.map(|&tdb| eph.pos_vel(Body::Moon,Body::Earth,tdb).unwrap().p)
+	.collect();
+    std::thread::scope(|scope| {
+	for _ in 0..4 {
+	    scope.spawn(|| {
+		for _ in 0..100 {
+		    for (&tdb,p) in dates.iter().zip(&serial) {
+			assert_eq!(&eph.pos_vel(Body::Moon,Body::Earth,tdb).unwrap().p,p);
+		    }
+		}
+	    });
+	}
+    });
+    assert!(matches!(JplEphemeris::from_reader(std::io::Cursor::new(vec![0u8;4000])),
+		     Err(JplError::InvalidHeader)));
+
+    // Malformed headers: a time span shorter than one record, and a
+    // single Chebyshev coefficient per component
+    let mut buf = synthetic_jpl(false);
+    buf[2660..2668].copy_from_slice(&2451550.5f64.to_le_bytes());
+    assert!(matches!(JplEphemeris::from_reader(std::io::Cursor::new(buf)),
+		     Err(JplError::InvalidHeader)));
+    let mut buf = synthetic_jpl(false);
+    buf[2660..2668].copy_from_slice(&2451546.5f64.to_le_bytes());
+    assert!(matches!(JplEphemeris::from_reader(std::io::Cursor::new(buf)),
+		     Err(JplError::InvalidHeader)));
+    let mut buf = synthetic_jpl(false);
+    buf[2696 + 12*10 + 4..2696 + 12*10 + 8].copy_from_slice(&1i32.to_le_bytes());
+    assert!(matches!(JplEphemeris::from_reader(std::io::Cursor::new(buf)),
+		     Err(JplError::InvalidHeader)));
+}
+
+/// Check a real JPL DE file against the published SOFA epv00, moon98
+/// and dtdb values.  Run it with
+///
+///   TOFAS_JPL_EPHEMERIS=/path/to/linux_p1550p2650.440 \
+///     cargo test -p tofas test_jpl_real_file -- --ignored
+#[test]
+#[ignore = "needs a JPL DE file in TOFAS_JPL_EPHEMERIS"]
+fn test_jpl_real_file() {
+    let path = std::env::var("TOFAS_JPL_EPHEMERIS")
+	.expect("TOFAS_JPL_EPHEMERIS must give the path of a JPL DE binary file");
+    let eph = JplEphemeris::open(&path).unwrap();
+    assert!(eph.de_number() >= 405);
+    assert_eq!(eph.constant("AU"),Some(eph.au()));
+    assert_eq!(eph.constant("EMRAT"),Some(eph.emrat()));
+    compare_numbers("JPL AU",eph.au(),149597870.7,0.01);
+    compare_numbers("JPL EMRAT",eph.emrat(),81.3005,1e-3);
+
+    // t_epv00 date; epv00 fits DE405 to 4.6 km and 1.4 mm/s
+    let tdb = TDB((2400000.5,53411.52501161));
+    let epv = eph.earth_pos_vel(tdb).unwrap();
+    let ph = [-0.7757238809297706813,0.5598052241363340596,0.2426998466481686993];
+    let sofa = EarthPosVel::from(tdb);
+    for k in 0..3 {
+	compare_numbers("JPL EPV00 PH",epv.heliocentric.p[k],ph[k],1e-7);
+	compare_numbers("JPL EPV00 PB",epv.barycentric.p[k],sofa.barycentric.p[k],1e-7);
+	compare_numbers("JPL EPV00 VH",epv.heliocentric.v[k],sofa.heliocentric.v[k],2e-9);
+	compare_numbers("JPL EPV00 VB",epv.barycentric.v[k],sofa.barycentric.v[k],2e-9);
+    }
+
+    // moon98 is good to about 10" and 1000 km
+    let tt = TT((2400000.5,43999.9));
+    let moon = eph.pos_vel(Body::Moon,Body::Earth,TDB(tt.0)).unwrap();
+    let m98 = moon::moon_pv(tt);
+    for k in 0..3 {
+	compare_numbers("JPL moon98",moon.p[k],m98.p[k],1e-5);
+    }
+    assert!(moon.p.angle(m98.p) < 10.0*AS2R);
+
+    // t_dtdb: TDB-TT = -1.280368 ms, of which the topocentric terms
+    // are a few microseconds
+    if eph.has_time_ephemeris() {
+	let dt = eph.tt_minus_tdb(TDB((2448939.5,0.123))).unwrap();
+	compare_numbers("JPL TT-TDB",dt,0.1280368005936998991e-2,1e-5);
+    }
 }
@@ -1,4 +1,8 @@
-use crate::common::*;
+use crate::{
+    common::*,
+    calendar::{GregorianDate,CalendarError,MJD_ZERO},
+    delta_at::{DeltaAt,DeltaAtError}
+};
 
 /// GPS -> TAI -> TT -> TDB -> EPV00
 /// GPS -> TAI -> TT -> UT1 -> ERA00
@@ -18,6 +22,12 @@ pub struct UTC(pub (R,R));
 #[derive(Copy,Clone,Debug)]
 pub struct TDB(pub (R,R));
 
+custom_error!{pub TimeError
+	      Calendar{source:CalendarError} = "calendar error: {source}",
+	      DeltaAt{source:DeltaAtError}   = "TAI-UTC error: {source}",
+	      PreUtc                         = "date before the introduction of UTC"
+}
+
 pub const D2S : R = 86400.0;
 pub const DJY : R = 365.25;
 pub const DJ00 : R = 2451545.0;
@@ -42,6 +52,14 @@ impl TT {
 	let TT((tt1,tt2)) = self;
 	tt1 + tt2
     }
+
+    /// Julian Date to Julian Epoch
+    ///
+    /// Source: epj.for
+    pub fn julian_epoch(self)->R {
+	let TT((dj1,dj2)) = self;
+	2000.0 + ( ( dj1 - DJ00 ) + dj2 ) / DJY
+    }
 }
 
 impl UTC {
@@ -51,7 +69,52 @@ impl UTC {
     }
 }
 
+/// TAI-UTC for a calendar date and fraction of day
+fn delta_at(date:&GregorianDate,fd:R)->Result<R,TimeError> {
+    date.delta_at(fd)?.ok_or(TimeError::PreUtc)
+}
+
 impl TAI {
+    /// Time scale transformation: Coordinated Universal Time, UTC, to
+    /// International Atomic Time, TAI.  The UTC date is quasi-JD:
+    /// during a leap second day its fraction is spread over the
+    /// (longer or shorter) day.
+    ///
+    /// Source: utctai.for
+    pub fn from_utc(UTC((utc1,utc2)):UTC)->Result<Self,TimeError> {
+	// Put the two parts of the UTC into big-first order.
+	let big1 = abs(utc1) >= abs(utc2);
+	let (u1,u2) = if big1 { (utc1,utc2) } else { (utc2,utc1) };
+
+	// Get TAI-UTC at 0h today.
+	let (today,fd) = GregorianDate::from_julian(u1,u2)?;
+	let dat0 = delta_at(&today,0.0)?;
+
+	// Get TAI-UTC at 12h today (to detect drift).
+	let dat12 = delta_at(&today,0.5)?;
+
+	// Get TAI-UTC at 0h tomorrow (to detect jumps).
+	let (tomorrow,_) = GregorianDate::from_julian(u1 + 1.5,u2 - fd)?;
+	let dat24 = delta_at(&tomorrow,0.0)?;
+
+	// Separate TAI-UTC change into per-day (DLOD) and any jump (DLEAP).
+	let dlod = 2.0 * ( dat12 - dat0 );
+	let dleap = dat24 - ( dat0 + dlod );
+
+	// Remove any scaling applied to spread leap into preceding day.
+	let fd = fd * ( D2S + dleap ) / D2S;
+
+	// Scale from (pre-1972) UTC seconds to SI seconds.
+	let fd = fd * ( D2S + dlod ) / D2S;
+
+	// Today's calendar date to 2-part JD.
+	let (z1,z2) = today.to_julian();
+
+	// Assemble the TAI result, preserving the UTC split and order.
+	let a2 = z1 - u1 + z2 + ( fd + dat0 / D2S );
+	Ok(if big1 { Self((u1,a2)) } else { Self((a2,u1)) })
+    }
+
     pub fn from_utc_delta_at(UTC((utc1,utc2)):UTC,dat:f64)->Self {
 	let da = dat/86400.0;
 	let (tai1,tai2) =
@@ -84,6 +147,13 @@ impl From<TAI> for TT {
 }
 
 impl TDB {
+    /// Julian Epoch to two-part Julian Date.
+    ///
+    /// Source: epj2jd.for
+    pub fn from_julian_epoch(epj:R)->Self {
+	Self((MJD_ZERO,51544.5 + ( epj - 2000.0 ) * DJY))
+    }
+
     /// Time scale transformation: Terrestrial Time, TT, to
     /// Barycentric Dynamical Time, TDB.
     ///
@@ -101,6 +171,28 @@ impl TDB {
 }
 
 impl UT1 {
+    /// Time scale transformation: Coordinated Universal Time, UTC, to
+    /// Universal Time, UT1, given DUT1 = UT1-UTC (seconds).
+    ///
+    /// Source: utcut1.for
+    pub fn from_utc(utc@UTC((utc1,utc2)):UTC,dut1:R)->Result<Self,TimeError> {
+	// Look up TAI-UTC.
+	let (date,_) = GregorianDate::from_julian(utc1,utc2)?;
+	let dat = delta_at(&date,0.0)?;
+
+	// Form UT1-TAI.
+	let dta = dut1 - dat;
+
+	// UTC to TAI to UT1.
+	let TAI((tai1,tai2)) = TAI::from_utc(utc)?;
+	let dtad = dta / D2S;
+	Ok(if abs(tai1) >= abs(tai2) {
+	    Self((tai1,tai2 + dtad))
+	} else {
+	    Self((tai1 + dtad,tai2))
+	}) // taiut1
+    }
+
     /// Time scale transformation:  Terrestrial Time, TT, to Universal Time,
     /// UT1.
     ///
@@ -0,0 +1,112 @@
+// Frame-tagged positions and rotations, checked at compile time for
+// the frames and at run time for the epochs.  The public frame
+// transformations (frames::gcrs_to_itrs, gcrs_to_cirs, gcrs_to_tod
+// and their model variants, the precession-nutation model and the CIP
+// interpolator) return these rotations; the matrices of the functions
+// named after SOFA routines are internal to the crate.
+
+use std::marker::PhantomData;
+use std::fmt::Debug;
+
+use crate::{
+    common::*,
+    time::TT
+};
+
+custom_error!{pub FrameError
+	      EpochMismatch = "epochs of frame-tagged quantities do not match"
+}
+
+/// Largest difference between two epochs considered equal [d]
+pub const EPOCH_TOLERANCE : R = 1e-8;
+
+/// A reference frame, used as a type-level tag
+pub trait Frame : Copy+Clone+Debug {
+    const NAME : &'static str;
+}
+
+macro_rules! frame {
+    ($name:ident,$doc:literal) => {
+	#[doc = $doc]
+	#[derive(Copy,Clone,Debug)]
+	pub struct $name;
+
+	impl Frame for $name {
+	    const NAME : &'static str = stringify!($name);
+	}
+    }
+}
+
+frame!(Gcrs,"Geocentric Celestial Reference System");
+frame!(Cirs,"Celestial Intermediate Reference System");
+frame!(Tirs,"Terrestrial Intermediate Reference System");
+frame!(Itrs,"International Terrestrial Reference System");
+frame!(Mod,"Mean equator and equinox of date");
+frame!(Tod,"True equator and equinox of date");
+frame!(Pef,"Pseudo Earth-fixed frame");
+frame!(Ecliptic,"Mean ecliptic and equinox of date");
+
+fn check_epochs(a:TT,b:TT)->Result<(),FrameError> {
+    if abs(a.total() - b.total()) <= EPOCH_TOLERANCE {
+	Ok(())
+    } else {
+	Err(FrameError::EpochMismatch)
+    }
+}
+
+/// A position vector expressed in the frame F at a given epoch
+#[derive(Copy,Clone,Debug)]
+pub struct Position<F:Frame> {
+    pub xyz:Vec3,
+    pub epoch:TT,
+    frame:PhantomData<F>
+}
+
+impl<F:Frame> Position<F> {
+    pub fn new(xyz:Vec3,epoch:TT)->Self {
+	Self { xyz,epoch,frame:PhantomData }
+    }
+
+    pub fn norm(&self)->R {
+	self.xyz.norm()
+    }
+
+    /// Angle between two positions in the same frame and at the same
+    /// epoch
+    pub fn angle(&self,b:&Self)->Result<R,FrameError> {
+	check_epochs(self.epoch,b.epoch)?;
+	Ok(self.xyz.angle(b.xyz))
+    }
+}
+
+/// A rotation taking coordinates in the frame A to the frame B at a
+/// given epoch
+#[derive(Copy,Clone,Debug)]
+pub struct Rotation<A:Frame,B:Frame> {
+    pub matrix:Mat3,
+    pub epoch:TT,
+    frames:PhantomData<(A,B)>
+}
+
+impl<A:Frame,B:Frame> Rotation<A,B> {
+    pub fn new(matrix:Mat3,epoch:TT)->Self {
+	Self { matrix,epoch,frames:PhantomData }
+    }
+
+    /// The inverse rotation, from B to A
+    pub fn inverse(&self)->Rotation<B,A> {
+	Rotation::new(self.matrix.transpose(),self.epoch)
+    }
+
+    /// Rotate a position from A to B
+    pub fn apply(&self,p:&Position<A>)->Result<Position<B>,FrameError> {
+	check_epochs(self.epoch,p.epoch)?;
+	Ok(Position::new(self.matrix.mul_vec(p.xyz),p.epoch))
+    }
+
+    /// Follow this rotation by the rotation [next] from B to C
+    pub fn then<C:Frame>(&self,next:&Rotation<B,C>)->Result<Rotation<A,C>,FrameError> {
+	check_epochs(self.epoch,next.epoch)?;
+	Ok(Rotation::new(next.matrix.compose(&self.matrix),self.epoch))
+    }
+}
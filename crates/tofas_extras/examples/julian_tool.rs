@@ -39,7 +39,7 @@ fn julian(mut args:Arguments)->Result<()> {
 fn main()->Result<()> {
     let mut args = Arguments::from_env();
 
-    match args.subcommand()?.as_ref().map(|x| x.as_str()) {
+    match args.subcommand()?.as_deref() {
 	Some("gregorian"|"g") => gregorian(args),
 	Some("julian"|"j") => julian(args),
 	_ => bail!("Specify gregorian or julian")
@@ -4,11 +4,21 @@ use pico_args::Arguments;
 use tofas::{
     calendar::{GregorianDate,HMS},
     ellipsoid::Geodetic360,
+    frames::PrecessionNutationModel,
+    astrometry::Atmosphere,
+    jpl::JplEphemeris
 };
-use tofas_extras::sun_angle::{
-    SunAngleParameters,
-    SunAngleCalculator,
-    SunAngleResultBundle
+use tofas_extras::{
+    sun_angle::{
+	RefractionModel,
+	SunAngleParameters,
+	SunAngleCalculator,
+	SunAngleResultBundle
+    },
+    moon_angle::{
+	MoonAngleCalculator,
+	MoonAngleResultBundle
+    }
 };
 
 fn main()->Result<()> {
@@ -23,12 +33,27 @@ fn main()->Result<()> {
     let lat : f64 = args.opt_value_from_str("--lat")?.unwrap_or(0.0);
     let lon : f64 = args.opt_value_from_str("--lon")?.unwrap_or(-120.0);
     let height : f64 = args.opt_value_from_str("--height")?.unwrap_or(0.0);
+    let model : PrecessionNutationModel = args.opt_value_from_str("--model")?
+	.unwrap_or_default();
+    let refraction : RefractionModel = args.opt_value_from_str("--refraction")?
+	.unwrap_or_default();
+    let phpa : f64 = args.opt_value_from_str("--pressure")?
+	.unwrap_or(Atmosphere::STANDARD.phpa);
+    let tc : f64 = args.opt_value_from_str("--temperature")?
+	.unwrap_or(Atmosphere::STANDARD.tc);
+    let rh : f64 = args.opt_value_from_str("--humidity")?
+	.unwrap_or(Atmosphere::STANDARD.rh);
+    let wl : f64 = args.opt_value_from_str("--wavelength")?
+	.unwrap_or(Atmosphere::STANDARD.wl);
+
+    let ephemeris : Option<String> = args.opt_value_from_str("--ephemeris")?;
 
     let delta0 : f64 = args.opt_value_from_str("--delta0")?.unwrap_or(-600.0);
     let delta1 : f64 = args.opt_value_from_str("--delta1")?.unwrap_or(600.0);
     let delta_step : f64 = args.opt_value_from_str("--delta_step")?
 	.unwrap_or(60.0);
     let scan = args.contains("--scan");
+    let moon = args.contains("--moon");
     if !args.finish().is_empty() {
 	bail!("Unhandled extra arguments");
     }
@@ -40,10 +65,18 @@ fn main()->Result<()> {
     let parameters = SunAngleParameters {
 	date,
 	time,
-	position
+	position,
+	model,
+	refraction,
+	atmosphere:Atmosphere { phpa,tc,rh,wl }
     };
 
-    let calc = SunAngleCalculator::new(&parameters);
+    let mut calc = SunAngleCalculator::new(&parameters);
+    let mut moon_calc = MoonAngleCalculator::new(&parameters);
+    if let Some(path) = ephemeris {
+	calc = calc.with_ephemeris(JplEphemeris::open(&path)?);
+	moon_calc = moon_calc.with_ephemeris(JplEphemeris::open(&path)?);
+    }
 
     if scan {
 	println!("Will scan from {:+13.6}s to {:+13.6}s in steps of {:13.6}",
@@ -55,12 +88,20 @@ fn main()->Result<()> {
 	if scan {
 	    println!();
 	}
-	let result = calc.compute(delta);
+	let result = calc.compute(delta)?;
 	let bundle = SunAngleResultBundle {
 	    parameters:&parameters,
 	    result:&result
 	};
 	print!("{}",bundle);
+	if moon {
+	    let result = moon_calc.compute(delta)?;
+	    let bundle = MoonAngleResultBundle {
+		parameters:&parameters,
+		result:&result
+	    };
+	    print!("{}",bundle);
+	}
 	if !scan {
 	    break;
 	}
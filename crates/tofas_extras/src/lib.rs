@@ -1 +1,5 @@
 pub mod sun_angle;
+pub mod moon_angle;
+
+#[cfg(test)]
+mod tests;
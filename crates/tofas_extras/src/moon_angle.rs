@@ -0,0 +1,131 @@
+// Topocentric Moon zenith and azimuth angles, from the geocentric
+// position given by the JPL ephemeris if one is set, by moon98
+// otherwise.  The parameters and the time scales are those of the Sun
+// angle calculator.  Unlike for the Sun, the parallax (up to
+// one degree) is taken into account; the aberration and the light time
+// (below one arcsecond) are not.
+
+use std::fmt::Display;
+
+use tofas::{
+    common::*,
+    time::{TT,UT1,UTC},
+    earth,
+    jpl::JplEphemeris,
+    typed::{Position,Gcrs}
+};
+
+use crate::sun_angle::{Epochs,SunAngleCalculator,SunAngleParameters,SunAngleError};
+
+pub struct MoonAngleCalculator {
+    sun:SunAngleCalculator
+}
+
+#[derive(Clone,Debug)]
+pub struct MoonAngleResultBundle<'a,'b> {
+    pub parameters:&'a SunAngleParameters,
+    pub result:&'b MoonAngleResult
+}
+
+#[derive(Clone,Debug)]
+pub struct MoonAngleResult {
+    pub jd0:f64,
+    pub jd1:f64,
+    pub delta_s:f64,
+    pub utc:UTC,
+    pub tt:TT,
+    pub ut1:UT1,
+
+    /// Geocentric Moon position in the ITRS [m]
+    pub moon_e:[f64;3],
+
+    /// Topocentric Moon distance [m]
+    pub distance:f64,
+
+    /// Geometric topocentric Moon zenith angle [rad]
+    pub mza:f64,
+
+    /// Apparent topocentric Moon zenith angle, including refraction
+    /// [rad]
+    pub apparent_mza:f64,
+
+    /// Topocentric Moon azimuth angle, clockwise from geodetic North
+    /// (N=0, E=π/2), in [0,2π[ [rad]
+    pub maa:f64
+}
+
+impl<'a,'b> Display for MoonAngleResultBundle<'a,'b> {
+    fn fmt(&self,fmt:&mut std::fmt::Formatter<'_>)->Result<(),std::fmt::Error> {
+	let &SunAngleParameters {
+	    date,
+	    time,
+	    position,
+	    refraction,
+	    ..
+	} = self.parameters;
+	let &MoonAngleResult {
+	    jd0,jd1,delta_s,utc,tt,ut1,moon_e,distance,mza,apparent_mza,maa
+	} = self.result;
+	let mza_d = mza / DEGREE;
+	let apparent_mza_d = apparent_mza / DEGREE;
+	writeln!(fmt,"Date:                {}",date)?;
+	writeln!(fmt,"Time:                {} {:+13.6}s",time,delta_s)?;
+	writeln!(fmt,"Julian date:         {:.6} = {:.6} + {:.6}",
+		 jd0 + jd1,jd0,jd1)?;
+	writeln!(fmt,"Position:            {}",position)?;
+	writeln!(fmt,"UTC:                 {:.6}",utc.total())?;
+	writeln!(fmt,"TT:                  {:.6}",tt.total())?;
+	writeln!(fmt,"UT1:                 {:.6}",ut1.total())?;
+	writeln!(fmt,"Moon position (m):   X={:+16.0} Y={:+16.0} Z={:+16.0}",
+		 moon_e[0],moon_e[1],moon_e[2])?;
+	writeln!(fmt,"Moon distance (km):  {:10.0}",distance/1e3)?;
+	writeln!(fmt,"Moon Zenith angle:   {:7.2}° or elevation: {:7.2}°",
+		 mza_d,90.0 - mza_d)?;
+	writeln!(fmt,"Refraction:          {:?}",refraction)?;
+	writeln!(fmt,"Apparent angle:      {:7.2}° or elevation: {:7.2}°",
+		 apparent_mza_d,90.0 - apparent_mza_d)?;
+	writeln!(fmt,"Moon azimuth angle:  {:7.2}°",maa/DEGREE)?;
+	Ok(())
+    }
+}
+
+impl MoonAngleCalculator {
+    pub fn new(parameters:&SunAngleParameters)->Self {
+	Self { sun:SunAngleCalculator::new(parameters) }
+    }
+
+    /// Use a JPL DE ephemeris instead of moon98 for the Moon, and its
+    /// TT-TDB when available
+    pub fn with_ephemeris(self,ephemeris:JplEphemeris)->Self {
+	Self { sun:self.sun.with_ephemeris(ephemeris) }
+    }
+
+    pub fn compute(&self,delta_s:f64)->Result<MoonAngleResult,SunAngleError> {
+	let epochs = self.sun.epochs(delta_s)?;
+	let Epochs { jd1,utc,tt,ut1,.. } = epochs;
+
+	let c2t = self.sun.gcrs_to_itrs(&epochs);
+	let pv = self.sun.moon_pos_vel(&epochs)?;
+	let moon = Position::<Gcrs>::new(pv.p.scale(earth::AUM),c2t.epoch);
+	let moon_e = c2t.apply(&moon)?.xyz;
+
+	// Topocentric direction
+	let moon_t = moon_e.sub(self.sun.position());
+	let distance = moon_t.norm();
+	let (mza,apparent_mza,maa) = self.sun.horizontal(moon_t);
+
+	Ok(MoonAngleResult {
+	    jd0:utc.0.0,
+	    jd1,
+	    delta_s,
+	    utc,
+	    tt,
+	    ut1,
+	    moon_e,
+	    distance,
+	    mza,
+	    apparent_mza,
+	    maa
+	})
+    }
+}
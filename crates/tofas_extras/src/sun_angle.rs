@@ -3,18 +3,133 @@ use std::fmt::Display;
 use tofas::{
     common::*,
     time::{TT,TAI,TDB,UT1,UTC},
-    frames,
+    frames::PrecessionNutationModel,
     ellipsoid::{EllipsoidConverter,Geodetic,Geodetic360,WGS84},
-    earth::{self,EarthPosVel},
+    earth::{self,EarthPosVel,PosVel},
     calendar::{GregorianDate,HMS},
     delta_at::DeltaAt,
+    typed::{Position,Rotation,FrameError,Gcrs,Itrs},
+    astrometry::{self,Astrom,Atmosphere},
+    horizontal,
+    moon,
+    jpl::{JplEphemeris,JplError,Body}
 };
 
+custom_error::custom_error!{pub SunAngleError
+			    UnknownRefractionModel = "unknown refraction model",
+			    Ephemeris{source:JplError} = "ephemeris error: {source}",
+			    Frame{source:FrameError} = "frame error: {source}"
+}
+
+/// Atmospheric refraction model used for the apparent Sun zenith angle
+#[derive(Copy,Clone,Debug,Default,PartialEq,Eq)]
+pub enum RefractionModel {
+    /// No refraction: the apparent zenith angle is the geometric one
+    None,
+
+    /// SOFA refraction constants (refco) from the pressure,
+    /// temperature, humidity and wavelength, applied as in atioq.  The
+    /// model is accurate to a few arcseconds above 15 degrees of
+    /// elevation but degrades near the horizon.
+    Sofa,
+
+    /// Bennett (1982) formula for the refraction at a given apparent
+    /// elevation, solved iteratively for the apparent elevation
+    Bennett,
+
+    /// Saemundsson (1986) formula for the refraction at a given
+    /// geometric elevation
+    #[default]
+    Saemundsson
+}
+
+impl std::str::FromStr for RefractionModel {
+    type Err = SunAngleError;
+
+    fn from_str(s:&str)->Result<Self,Self::Err> {
+	match s {
+	    "none" => Ok(Self::None),
+	    "sofa" => Ok(Self::Sofa),
+	    "bennett" => Ok(Self::Bennett),
+	    "saemundsson" => Ok(Self::Saemundsson),
+	    _ => Err(SunAngleError::UnknownRefractionModel)
+	}
+    }
+}
+
+/// Below this elevation [deg], the Bennett and Saemundsson refractions
+/// are held at their value there: the formulae are fits to refraction
+/// tables down to the horizon and diverge a few degrees below it.  The
+/// refraction of a body below the horizon is thus never zero, which
+/// keeps the apparent elevation continuous across sunrise and sunset.
+/// The SOFA model is likewise held constant below about 3 degrees by
+/// the SELMIN limit of atioq.
+const MIN_ELEVATION : f64 = -1.0;
+
+/// Refraction [deg] from the Saemundsson formula at a geometric
+/// elevation H [deg], for 1010 hPa and 10 deg C
+fn saemundsson(h:f64)->f64 {
+    let h = h.max(MIN_ELEVATION);
+    1.02 / tan((h + 10.3 / (h + 5.11)) * DEGREE) / 60.0
+}
+
+/// Refraction [deg] from the Bennett formula at an apparent elevation
+/// H [deg], for 1010 hPa and 10 deg C
+fn bennett(h:f64)->f64 {
+    let h = h.max(MIN_ELEVATION);
+    1.0 / tan((h + 7.31 / (h + 4.4)) * DEGREE) / 60.0
+}
+
+impl RefractionModel {
+    /// Apparent zenith angle [rad] for a geometric zenith angle ZD
+    /// [rad] under the given ambient conditions
+    pub fn apparent_zenith_angle(self,zd:f64,atmosphere:&Atmosphere)->f64 {
+	// Pressure and temperature scaling of the Bennett and Saemundsson
+	// formulae
+	let k = atmosphere.phpa / 1010.0 * 283.0 / (273.0 + atmosphere.tc);
+	let h = 90.0 - zd / DEGREE;
+	match self {
+	    Self::None => zd,
+	    Self::Sofa => {
+		// Minimum cos(alt) and sin(alt) for refraction purposes
+		const CELMIN : f64 = 1e-6;
+		const SELMIN : f64 = 0.05;
+
+		let (refa,refb) = atmosphere.refraction_constants(); // refco
+		let r = sin(zd).max(CELMIN);
+		let z = cos(zd).max(SELMIN);
+		let tz = r / z;
+		let w = refb*tz*tz;
+		let del = (refa + w) * tz / (1.0 + (refa + 3.0*w) / (z*z));
+		zd - del
+	    },
+	    Self::Bennett => {
+		// Fixed-point iteration on the apparent elevation,
+		// starting from the Saemundsson value
+		let mut ha = h + k * saemundsson(h);
+		for _ in 0..20 {
+		    let ha1 = h + k * bennett(ha);
+		    let done = abs(ha1 - ha) < 1e-12;
+		    ha = ha1;
+		    if done {
+			break;
+		    }
+		}
+		(90.0 - ha) * DEGREE
+	    },
+	    Self::Saemundsson => (90.0 - h - k * saemundsson(h)) * DEGREE
+	}
+    }
+}
+
 #[derive(Clone,Debug)]
 pub struct SunAngleParameters {
     pub date:GregorianDate,
     pub time:HMS,
-    pub position:Geodetic360
+    pub position:Geodetic360,
+    pub model:PrecessionNutationModel,
+    pub refraction:RefractionModel,
+    pub atmosphere:Atmosphere
 }
 
 impl Default for SunAngleParameters {
@@ -34,11 +149,25 @@ impl Default for SunAngleParameters {
 		height:0.0,
 		lat:0.0,
 		lon:-120.0
-	    }
+	    },
+	    model:PrecessionNutationModel::default(),
+	    refraction:RefractionModel::default(),
+	    atmosphere:Atmosphere::STANDARD
 	}
     }
 }
 
+/// Time scales of a computation
+#[derive(Clone,Copy,Debug)]
+pub(crate) struct Epochs {
+    pub jd1:f64,
+    pub utc:UTC,
+    pub tai:TAI,
+    pub tt:TT,
+    pub ut1:UT1,
+    pub tdb:TDB
+}
+
 pub struct SunAngleCalculator {
     jd0:f64,
     jd1_date:f64,
@@ -49,7 +178,12 @@ pub struct SunAngleCalculator {
     xp:f64,
     yp:f64,
     p:[f64;3],
-    zen:[f64;3]
+    site:Geodetic,
+    zen:[f64;3],
+    model:PrecessionNutationModel,
+    refraction:RefractionModel,
+    atmosphere:Atmosphere,
+    ephemeris:Option<JplEphemeris>
 }
 
 #[derive(Clone,Debug)]
@@ -72,7 +206,30 @@ pub struct SunAngleResult {
     pub era:f64,
     pub earth:[f64;3],
     pub sun_e:[f64;3],
-    pub sza:f64
+
+    /// Geometric Sun zenith angle [rad].  Like the azimuth, it is
+    /// computed from the geometric geocentric direction of the Sun,
+    /// without the annual aberration (up to 20.5") that RA and Dec
+    /// include.
+    pub sza:f64,
+
+    /// Apparent Sun zenith angle, including refraction [rad]
+    pub apparent_sza:f64,
+
+    /// Geometric Sun azimuth angle, clockwise from geodetic North
+    /// (N=0, E=π/2), in [0,2π[ [rad]
+    pub saa:f64,
+
+    /// Apparent geocentric right ascension and declination of the Sun,
+    /// with respect to the true equator and equinox of date [rad]
+    pub ra:f64,
+    pub dec:f64,
+
+    /// Local apparent hour angle of the Sun, in [-π,π[ [rad]
+    pub hour_angle:f64,
+
+    /// Equation of time, apparent minus mean solar time [rad]
+    pub equation_of_time:f64
 }
 
 impl<'a,'b> Display for SunAngleResultBundle<'a,'b> {
@@ -80,17 +237,23 @@ impl<'a,'b> Display for SunAngleResultBundle<'a,'b> {
 	let &SunAngleParameters {
 	    date,
 	    time,
-	    position
+	    position,
+	    model,
+	    refraction,
+	    ..
 	} = self.parameters;
 	let &SunAngleResult {
-	    jd0,jd1,delta_s,p,utc,dat,tai,tt,ut1,era,earth,sun_e,sza,..
+	    jd0,jd1,delta_s,p,utc,dat,tai,tt,ut1,era,earth,sun_e,sza,apparent_sza,
+	    saa,ra,dec,hour_angle,equation_of_time
 	} = self.result;
 	let sza_d = sza / DEGREE;
+	let apparent_sza_d = apparent_sza / DEGREE;
 	writeln!(fmt,"Date:                {}",date)?;
 	writeln!(fmt,"Time:                {} {:+13.6}s",time,delta_s)?;
 	writeln!(fmt,"Julian date:         {:.6} = {:.6} + {:.6}",
 		 jd0 + jd1,jd0,jd1)?;
 	writeln!(fmt,"Position:            {}",position)?;
+	writeln!(fmt,"Model:               {:?}",model)?;
 	writeln!(fmt,"                     X={:16.1} Y={:16.1} Z={:16.1}",
 		 p[0],p[1],p[2])?;
 	writeln!(fmt,"UTC:                 {:.6}",utc.total())?;
@@ -105,6 +268,13 @@ impl<'a,'b> Display for SunAngleResultBundle<'a,'b> {
 		 sun_e[0],sun_e[1],sun_e[2])?;
 	writeln!(fmt,"Sun Zenith angle:    {:7.2}° or elevation: {:7.2}°",
 		 sza_d,90.0 - sza_d)?;
+	writeln!(fmt,"Refraction:          {:?}",refraction)?;
+	writeln!(fmt,"Apparent angle:      {:7.2}° or elevation: {:7.2}°",
+		 apparent_sza_d,90.0 - apparent_sza_d)?;
+	writeln!(fmt,"Sun azimuth angle:   {:7.2}°",saa/DEGREE)?;
+	writeln!(fmt,"Sun RA, Dec:         {:10.6}° {:+10.6}°",ra/DEGREE,dec/DEGREE)?;
+	writeln!(fmt,"Sun hour angle:      {:+10.6}°",hour_angle/DEGREE)?;
+	writeln!(fmt,"Equation of time:    {:+8.3} min",equation_of_time/DS2R/60.0)?;
 	Ok(())
     }
 }
@@ -114,7 +284,10 @@ impl SunAngleCalculator {
 	let &SunAngleParameters {
 	    date,
 	    time,
-	    position
+	    position,
+	    model,
+	    refraction,
+	    atmosphere
 	} = parameters;
 
 	// See example 5.1 in sofa_pn_f.pdf (p.18)
@@ -145,47 +318,120 @@ impl SunAngleCalculator {
 	    xp,
 	    yp,
 	    p,
-	    zen
+	    site:p_gd,
+	    zen,
+	    model,
+	    refraction,
+	    atmosphere,
+	    ephemeris:None
 	}
     }
 
-    pub fn compute(&self,delta_s:f64)->SunAngleResult {
-	let &Self {
-	    jd0,
-	    jd1_date,
-	    fr,
-	    dat,
-	    dut1,
-	    dtr,
-	    xp,
-	    yp,
-	    p,
-	    zen
-	} = self;
+    /// Use a JPL DE ephemeris instead of epv00 for the Earth, and its
+    /// TT-TDB when available
+    pub fn with_ephemeris(self,ephemeris:JplEphemeris)->Self {
+	Self { ephemeris:Some(ephemeris),..self }
+    }
 
-	let fr = fr + delta_s / 86400.0;
+    /// Time scales at DELTA_S seconds from the parameter date and time
+    pub(crate) fn epochs(&self,delta_s:f64)->Result<Epochs,SunAngleError> {
+	let fr = self.fr + delta_s / 86400.0;
 
-	let jd1 = jd1_date + fr;
-	let utc = UTC((jd0,jd1));
-	let tai = TAI::from_utc_delta_at(utc,dat);
+	let jd1 = self.jd1_date + fr;
+	let utc = UTC((self.jd0,jd1));
+	let tai = TAI::from_utc_delta_at(utc,self.dat);
 	
 	let tt : TT = tai.into();
 
 	// let ut1 = UT1::from_tt(tt,dut1);
-	let ut1 = UT1((jd0,jd1 + dut1/86400.0));
-	let era = earth::rotation_angle(ut1);
+	let ut1 = UT1((self.jd0,jd1 + self.dut1/86400.0));
 
+	let dtr = match &self.ephemeris {
+	    Some(eph) if eph.has_time_ephemeris() =>
+		-eph.tt_minus_tdb(TDB::from_tt(tt,0.0))?,
+	    _ => self.dtr
+	};
 	let tdb = TDB::from_tt(tt,dtr);
 
-	let epv : EarthPosVel = tdb.into();
-	let c2t = frames::celestial_to_terrestrial(tt,ut1,xp,yp);
+	Ok(Epochs { jd1,utc,tai,tt,ut1,tdb })
+    }
+
+    /// Geocentric GCRS position and velocity of the Moon [AU, AU/day],
+    /// from the ephemeris when there is one
+    pub(crate) fn moon_pos_vel(&self,epochs:&Epochs)->Result<PosVel,SunAngleError> {
+	match &self.ephemeris {
+	    Some(eph) => Ok(eph.pos_vel(Body::Moon,Body::Earth,epochs.tdb)?),
+	    None => Ok(moon::moon_pv(epochs.tt)) // moon98
+	}
+    }
+
+    /// Geocentric ITRS position of the site [m]
+    pub(crate) fn position(&self)->[f64;3] {
+	self.p
+    }
+
+    /// GCRS to ITRS rotation at the given epochs
+    pub(crate) fn gcrs_to_itrs(&self,epochs:&Epochs)->Rotation<Gcrs,Itrs> {
+	self.model.gcrs_to_itrs(epochs.tt,epochs.ut1,self.xp,self.yp)
+    }
+
+    /// Geometric zenith angle, apparent zenith angle and azimuth (from
+    /// geodetic North through East) [rad] of the ITRS direction V
+    pub(crate) fn horizontal(&self,v:[f64;3])->(f64,f64,f64) {
+	let zd = self.zen.angle(v);
+	let apparent_zd = self.refraction.apparent_zenith_angle(zd,&self.atmosphere);
+
+	// Azimuth, in the local geodetic East-North-Up frame
+	let east = [-sin(self.site.elong),cos(self.site.elong),0.0];
+	let north = self.zen.cross(east);
+	let az = anp(atan2(v.dot(east),v.dot(north)));
+	(zd,apparent_zd,az)
+    }
+
+    /// Sun angles at DELTA_S seconds from the parameter date and time.
+    /// Fails when the date is outside the range of the ephemeris.
+    pub fn compute(&self,delta_s:f64)->Result<SunAngleResult,SunAngleError> {
+	let &Self {
+	    dat,
+	    p,
+	    site,
+	    model,
+	    ..
+	} = self;
+
+	let epochs = self.epochs(delta_s)?;
+	let Epochs { jd1,utc,tai,tt,ut1,tdb } = epochs;
+	let era = earth::rotation_angle(ut1);
+
+	let epv : EarthPosVel = match &self.ephemeris {
+	    Some(eph) => eph.earth_pos_vel(tdb)?,
+	    None => tdb.into()
+	};
+	let c2t = self.gcrs_to_itrs(&epochs);
 	let earth = epv.heliocentric.p;
-	let sun = earth.neg().scale(earth::AUM);
-	let sun_e = c2t.transpose().apply(sun);
+	let sun = Position::<Gcrs>::new(earth.neg().scale(earth::AUM),c2t.epoch);
+	let sun_e = c2t.apply(&sun)?.xyz;
 
-	let sza = zen.angle(sun_e);
+	let (sza,apparent_sza,saa) = self.horizontal(sun_e);
 
-	SunAngleResult {
+	let jd0 = self.jd0;
+
+	// Apparent direction of the Sun: annual aberration, neglecting the
+	// light time (below 0.01 arcsec), then true equator and equinox
+	let astrom = Astrom::geocentric(tdb,&epv); // apcg
+	let ppr = astrometry::aberration(astrom.eh.neg(),astrom.v,astrom.em,astrom.bm1); // ab
+	let rbpn = model.gcrs_to_tod(tt);
+	let ppr = rbpn.apply(&Position::<Gcrs>::new(ppr,rbpn.epoch))?;
+	let (ra,dec) = cartesian_to_spherical(ppr.xyz); // c2s
+	let ra = anp(ra);
+	let hour_angle = horizontal::local_hour_angle_gast(model,tt,ut1,ra,&site);
+
+	// Equation of time: Greenwich apparent solar time minus UT1
+	let UT1((ut11,ut12)) = ut1;
+	let f = ((ut11 - 0.5).rem_euclid(1.0) + ut12.rem_euclid(1.0)).rem_euclid(1.0);
+	let equation_of_time = anpm(hour_angle - site.elong + PI - TWO_PI*f);
+
+	Ok(SunAngleResult {
 	    jd0,
 	    jd1,
 	    delta_s,
@@ -198,7 +444,13 @@ impl SunAngleCalculator {
 	    era,
 	    earth,
 	    sun_e,
-	    sza
-	}
+	    sza,
+	    apparent_sza,
+	    saa,
+	    ra,
+	    dec,
+	    hour_angle,
+	    equation_of_time
+	})
     }
 }
@@ -0,0 +1,154 @@
+use tofas::{
+    common::*,
+    calendar::{GregorianDate,HMS},
+    ellipsoid::Geodetic360,
+    time::TDB,
+    earth::EarthPosVel,
+    astrometry::{self,Atmosphere},
+    jpl::JplEphemeris
+};
+
+use crate::{
+    sun_angle::{RefractionModel,SunAngleCalculator,SunAngleParameters},
+    moon_angle::MoonAngleCalculator
+};
+
+fn compare_numbers(name:&str,a:f64,b:f64,tol:f64) {
+    let e = abs(a - b);
+    if e > tol {
+	panic!("Mismatch in {name} got {a} vs {b} error {e} > {tol}")
+    }
+}
+
+fn parameters(year:i32,month:i32,day:i32,hour:u8,lat:f64,lon:f64)->SunAngleParameters {
+    SunAngleParameters {
+	date:GregorianDate::new(year,month,day).unwrap(),
+	time:HMS { hour,minute:0,second:0.0 },
+	position:Geodetic360 { lat,lon,height:0.0 },
+	..SunAngleParameters::default()
+    }
+}
+
+#[test]
+fn test_azimuth_convention() {
+    // At (0,0) the local North is the ITRS Z axis and the East the Y
+    // axis
+    let calc = SunAngleCalculator::new(&parameters(2007,4,5,12,0.0,0.0));
+    for (v,az) in [([1.0,0.0,1.0],0.0),
+		   ([1.0,1.0,0.0],PI/2.0),
+		   ([1.0,0.0,-1.0],PI),
+		   ([1.0,-1.0,0.0],3.0*PI/2.0)] {
+	let (zd,_,a) = calc.horizontal(v);
+	compare_numbers("Zenith angle",zd,PI/4.0,1e-15);
+	compare_numbers("Azimuth",a,az,1e-15);
+    }
+
+    // At 45N 0E in April the Sun rises in the East, culminates due
+    // South a few minutes after noon and sets in the West
+    for (hour,lo,hi) in [(8,0.0,PI),(16,PI,TWO_PI)] {
+	let calc = SunAngleCalculator::new(&parameters(2007,4,5,hour,45.0,0.0));
+	let r = calc.compute(0.0).unwrap();
+	assert!(lo < r.saa && r.saa < hi,"azimuth {} at {hour}h",r.saa/DEGREE);
+    }
+    let calc = SunAngleCalculator::new(&parameters(2007,4,5,12,45.0,0.0));
+    let r = calc.compute(2.0*60.0).unwrap();
+    compare_numbers("Noon azimuth",r.saa,PI,1.0*DEGREE);
+    compare_numbers("Noon zenith angle",r.sza,(45.0 - 6.2)*DEGREE,0.5*DEGREE);
+}
+
+#[test]
+fn test_sun_ra_dec() {
+    // The Sun as a star at its barycentric distance, through atci13:
+    // parallax and aberration, without the light time
+    for (month,day) in [(1,1),(4,5),(7,14),(11,3)] {
+	let calc = SunAngleCalculator::new(&parameters(2007,month,day,12,0.0,0.0));
+	let r = calc.compute(0.0).unwrap();
+	let tdb = TDB::from_tt(r.tt,0.0);
+	let epv : EarthPosVel = tdb.into();
+	let sun_b = epv.barycentric.p.sub(epv.heliocentric.p);
+	let (rc,dc) = cartesian_to_spherical(sun_b);
+	let px = 1.0 / sun_b.norm() / AS2R;
+	let (ri,di,eo) = astrometry::icrs_to_cirs(rc,dc,0.0,0.0,px,0.0,tdb);
+	compare_numbers("Sun RA",r.ra,anp(ri - eo),2e-9);
+	compare_numbers("Sun Dec",r.dec,di,2e-9);
+    }
+}
+
+#[test]
+fn test_equation_of_time() {
+    // Near the extrema of the equation of time, around 12h UT:
+    // +16m25s on 3 November and -14m13s on 11 February
+    for (month,day,eot) in [(11,3,16.0*60.0 + 25.0),(2,11,-(14.0*60.0 + 13.0))] {
+	let calc = SunAngleCalculator::new(&parameters(2007,month,day,12,0.0,0.0));
+	let r = calc.compute(0.0).unwrap();
+	compare_numbers("EoT",r.equation_of_time/DS2R,eot,5.0);
+    }
+}
+
+#[test]
+fn test_refraction() {
+    // At 1010 hPa and 10 deg C the Bennett and Saemundsson formulae
+    // need no scaling.  Mean refraction [arcmin] at an apparent
+    // elevation [deg]: 34.5' at the horizon and about 1' at 45 deg;
+    // Saemundsson agrees with Bennett to 0.1'
+    let atm = Atmosphere { phpa:1010.0,tc:10.0,rh:0.5,wl:0.55 };
+    for model in [RefractionModel::Bennett,RefractionModel::Saemundsson] {
+	for (ha,r,tol) in [(0.0,34.5,0.1),(45.0,1.0,0.05),(90.0,0.0,0.01)] {
+	    let zd = (90.0 - ha + r/60.0)*DEGREE;
+	    let h = 90.0 - model.apparent_zenith_angle(zd,&atm)/DEGREE;
+	    compare_numbers("Refraction",h*60.0,ha*60.0,tol);
+	}
+    }
+
+    // refco at 45 deg, where dZ is about A + B, agrees with Bennett to
+    // 2"
+    let zd = 45.0*DEGREE;
+    let sofa = zd - RefractionModel::Sofa.apparent_zenith_angle(zd,&atm);
+    let (refa,refb) = atm.refraction_constants();
+    compare_numbers("SOFA refraction",sofa,refa + refb,0.1*AS2R);
+    let bennett = zd - RefractionModel::Bennett.apparent_zenith_angle(zd,&atm);
+    compare_numbers("SOFA vs Bennett",sofa,bennett,2.0*AS2R);
+    assert_eq!(RefractionModel::None.apparent_zenith_angle(zd,&atm),zd);
+
+    // Below the horizon the refraction is held at its value at -1 deg
+    // of elevation; it does not vanish
+    for model in [RefractionModel::Bennett,RefractionModel::Saemundsson] {
+	let refraction = |h:f64| {
+	    let zd = (90.0 - h)*DEGREE;
+	    zd - model.apparent_zenith_angle(zd,&atm)
+	};
+	assert!(refraction(-0.5) > 30.0/60.0*DEGREE);
+	compare_numbers("Refraction below -1 deg",refraction(-3.0),refraction(-10.0),1e-12);
+	assert!(refraction(-10.0) > 0.0);
+    }
+}
+
+#[test]
+fn test_calculators_are_sync() {
+    fn is_sync<T:Sync>() {}
+    is_sync::<SunAngleCalculator>();
+    is_sync::<MoonAngleCalculator>();
+}
+
+/// Check that the Moon angles use the JPL ephemeris when one is set:
+/// they differ from moon98 by no more than its accuracy of about 10"
+/// and 1500 km, and by more than rounding.  Run it with
+///
+///   TOFAS_JPL_EPHEMERIS=/path/to/linux_p1550p2650.440 \
+///     cargo test -p tofas_extras test_moon_ephemeris -- --ignored
+#[test]
+#[ignore = "needs a JPL DE file in TOFAS_JPL_EPHEMERIS"]
+fn test_moon_ephemeris() {
+    let path = std::env::var("TOFAS_JPL_EPHEMERIS")
+	.expect("TOFAS_JPL_EPHEMERIS must give the path of a JPL DE binary file");
+    let parameters = parameters(2007,4,5,12,45.0,0.0);
+    let m98 = MoonAngleCalculator::new(&parameters).compute(0.0).unwrap();
+    let jpl = MoonAngleCalculator::new(&parameters)
+	.with_ephemeris(JplEphemeris::open(path).unwrap())
+	.compute(0.0)
+	.unwrap();
+    compare_numbers("Moon distance",jpl.distance,m98.distance,1500e3);
+    compare_numbers("Moon zenith angle",jpl.mza,m98.mza,10.0*AS2R);
+    assert!(jpl.moon_e.angle(m98.moon_e) < 10.0*AS2R);
+    assert!(jpl.moon_e != m98.moon_e);
+}